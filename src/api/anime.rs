@@ -1,6 +1,6 @@
 use super::model::*;
-use super::Error;
-use super::{get, handle_response, API_URL};
+use super::{get, handle_response};
+use super::{ApiClient, Error};
 use crate::auth::OAuth;
 use serde::Serialize;
 
@@ -15,9 +15,18 @@ pub struct GetAnimeListQuery {
     pub fields: Option<String>,
 }
 
-pub async fn get_anime_list(query: &GetAnimeListQuery, auth: &OAuth) -> Result<Page<Anime>, Error> {
+pub async fn get_anime_list(
+    query: &GetAnimeListQuery,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<Page<Anime>, Error> {
     let response = get(
-        &format!("{}/anime?{}", API_URL, serde_urlencoded::to_string(query)?),
+        &format!(
+            "{}/anime?{}",
+            client.api_url(),
+            serde_urlencoded::to_string(query)?
+        ),
+        client,
         auth,
     )
     .await?;
@@ -34,15 +43,17 @@ pub struct GetAnimeDetailQuery {
 pub async fn get_anime_details(
     anime_id: u64,
     query: &GetAnimeDetailQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Anime, Error> {
     let response = get(
         &format!(
             "{}/anime/{}?{}",
-            API_URL,
+            client.api_url(),
             anime_id,
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -61,14 +72,16 @@ pub struct GetAnimeRankingQuery {
 
 pub async fn get_anime_ranking(
     query: &GetAnimeRankingQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Ranking<RankingAnimePair>, Error> {
     let response = get(
         &format!(
             "{}/anime/ranking?{}",
-            API_URL,
+            client.api_url(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -88,17 +101,19 @@ pub struct GetSeasonalAnimeQuery {
 pub async fn get_seasonal_anime(
     season: &AnimeSeason,
     query: &GetSeasonalAnimeQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Page<Anime>, Error> {
     let season_name: &'static str = season.season.clone().into();
     let response = get(
         &format!(
             "{}/anime/season/{}/{}?{}",
-            API_URL,
+            client.api_url(),
             season.year,
             season_name,
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -116,14 +131,16 @@ pub struct GetSuggestedAnimeQuery {
 
 pub async fn get_suggested_anime(
     query: &GetSuggestedAnimeQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Page<Anime>, Error> {
     let response = get(
         &format!(
             "{}/anime/suggestions?{}",
-            API_URL,
+            client.api_url(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -131,97 +148,95 @@ pub async fn get_suggested_anime(
 }
 
 #[cfg(test)]
-pub mod tests {
-
+mod tests {
     use super::*;
-
-    pub async fn get_anime<T: ToString>(q: T, auth: &OAuth) -> Result<Anime, Error> {
-        let anime_query = GetAnimeListQuery {
-            q: q.to_string(),
-            limit: 4,
-            offset: 0,
-            nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-        };
-        let anime_list = get_anime_list(&anime_query, auth).await.unwrap();
-        let anime = anime_list.data.first().unwrap().node.clone();
-        Ok(anime)
-    }
+    use crate::api::tests::*;
+    use reqwest::Method;
 
     #[tokio::test]
     async fn test_get_anime_list() {
-        let mut auth = crate::auth::tests::get_auth();
-        auth.refresh().unwrap();
-        // let oauth = crate::auth::OAuth::get_auth(app_config::AppConfig::Load())
         let query = GetAnimeListQuery {
-            q: "Code Geass".to_string(),
+            q: "Frieren".to_string(),
             limit: 4,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
-        let result = get_anime_list(&query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("anime", &query);
+        let body = list_body(&anime_fixtures()[1..2]);
+        let (client, auth) = replay("anime-list", &[(Method::GET, &url, &body)]).await;
+        let result = get_anime_list(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data.len(), 1);
+        assert_eq!(result.data[0].node.id, 52991);
     }
 
     #[tokio::test]
     async fn test_get_anime_details() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetAnimeDetailQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::detail().into()),
             nsfw: false,
         };
-
-        let anime = get_anime("Cowboy Bebop", &auth).await.unwrap();
-        let result = get_anime_details(anime.id, &query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert_eq!(result.title, anime.title);
+        let url = api_url("anime/5114", &query);
+        let body = anime_fixtures()[0].to_string();
+        let (client, auth) = replay("anime-details", &[(Method::GET, &url, &body)]).await;
+        let result = get_anime_details(5114, &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(result.title, "Fullmetal Alchemist: Brotherhood");
+        assert_eq!(result.num_episodes, Some(64));
     }
 
     #[tokio::test]
     async fn test_get_anime_ranking() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetAnimeRankingQuery {
             ranking_type: AnimeRankingType::All,
             limit: 4,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
-        let result = get_anime_ranking(&query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("anime/ranking", &query);
+        let body = ranking_body(&anime_fixtures());
+        let (client, auth) = replay("anime-ranking", &[(Method::GET, &url, &body)]).await;
+        let result = get_anime_ranking(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data.len(), 4);
+        assert_eq!(result.data[1].ranking.rank, 2);
     }
+
     #[tokio::test]
     async fn test_get_seasonal_anime() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetSeasonalAnimeQuery {
             sort: None,
             limit: 4,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
         let season = AnimeSeason {
-            year: 2020,
-            season: Season::Summer,
+            year: 2023,
+            season: Season::Fall,
         };
-        let result = get_seasonal_anime(&season, &query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("anime/season/2023/fall", &query);
+        let body = list_body(&anime_fixtures()[1..2]);
+        let (client, auth) = replay("anime-season", &[(Method::GET, &url, &body)]).await;
+        let result = get_seasonal_anime(&season, &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(result.data[0].node.title, "Sousou no Frieren");
     }
+
     #[tokio::test]
     async fn test_get_suggested_anime() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetSuggestedAnimeQuery {
             limit: 4,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
-        let result = get_suggested_anime(&query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("anime/suggestions", &query);
+        let body = list_body(&anime_fixtures()[2..3]);
+        let (client, auth) = replay("anime-suggestions", &[(Method::GET, &url, &body)]).await;
+        let result = get_suggested_anime(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data[0].node.id, 9253);
     }
 }
@@ -1,11 +1,11 @@
 use super::model::*;
-use super::Error;
-use super::{delete, get, handle_response, patch, API_URL};
+use super::{delete, error_from_response, get, handle_response, patch};
+use super::{ApiClient, Error};
 use crate::auth::OAuth;
 use serde::Serialize;
 
 /// Update specified anime in animelist
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct UpdateUserAnimeListStatusQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<UserWatchStatus>,
@@ -25,15 +25,28 @@ pub struct UpdateUserAnimeListStatusQuery {
     pub tags: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comments: Option<String>,
+    /// `Some(None)` clears the date
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_date_update"
+    )]
+    pub start_date: Option<Option<DateWrapper>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_date_update"
+    )]
+    pub finish_date: Option<Option<DateWrapper>>,
 }
 
 pub async fn update_anime_list_status(
     anime_id: u64,
     update: &UpdateUserAnimeListStatusQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<UserAnimeListStatus, Error> {
     let response = patch(
-        &format!("{}/anime/{}/my_list_status", API_URL, anime_id,),
+        &format!("{}/anime/{}/my_list_status", client.api_url(), anime_id,),
+        client,
         auth,
         update,
     )
@@ -41,16 +54,21 @@ pub async fn update_anime_list_status(
     handle_response(&response)
 }
 
-pub async fn delete_anime_from_list(anime_id: u64, auth: &OAuth) -> Result<(), Error> {
+pub async fn delete_anime_from_list(
+    anime_id: u64,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<(), Error> {
     let response = delete(
-        &format!("{}/anime/{}/my_list_status", API_URL, anime_id),
+        &format!("{}/anime/{}/my_list_status", client.api_url(), anime_id),
+        client,
         auth,
     )
     .await?;
     if response.status.is_success() {
         Ok(())
     } else {
-        Err(Error::HttpError(response.status))
+        Err(error_from_response(&response))
     }
 }
 
@@ -70,15 +88,17 @@ pub struct GetUserAnimeListQuery {
 pub async fn get_user_anime_list<U: ToString>(
     user: U,
     query: &GetUserAnimeListQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Page<Anime>, Error> {
     let response = get(
         &format!(
             "{}/users/{}/animelist?{}",
-            API_URL,
+            client.api_url(),
             user.to_string(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -88,60 +108,106 @@ pub async fn get_user_anime_list<U: ToString>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::anime::tests::*;
+    use crate::api::tests::*;
+    use reqwest::Method;
+    use time::{Date, Month};
+
+    #[test]
+    fn test_update_query_sends_dates() {
+        let date = Date::from_calendar_date(2024, Month::March, 2).unwrap();
+        let query = UpdateUserAnimeListStatusQuery {
+            status: Some(UserWatchStatus::Completed),
+            is_rewatching: None,
+            score: None,
+            num_watched_episodes: None,
+            priority: None,
+            num_times_rewatched: None,
+            rewatch_value: None,
+            tags: None,
+            comments: None,
+            start_date: None,
+            finish_date: Some(Some(DateWrapper { date })),
+        };
+        assert_eq!(
+            serde_urlencoded::to_string(&query).unwrap(),
+            "status=completed&finish_date=2024-03-02"
+        );
+    }
+
+    #[test]
+    fn test_update_query_clears_dates() {
+        let query = UpdateUserAnimeListStatusQuery {
+            status: None,
+            is_rewatching: None,
+            score: None,
+            num_watched_episodes: None,
+            priority: None,
+            num_times_rewatched: None,
+            rewatch_value: None,
+            tags: None,
+            comments: None,
+            start_date: Some(None),
+            finish_date: None,
+        };
+        assert_eq!(serde_urlencoded::to_string(&query).unwrap(), "start_date=");
+    }
 
     #[tokio::test]
-    #[ignore]
     async fn test_delete_anime_from_list() {
-        let auth = crate::auth::tests::get_auth();
-        let anime = get_anime("God of High School", &auth).await.unwrap();
-        delete_anime_from_list(anime.id, &auth).await.unwrap();
+        let url = "https://api.myanimelist.net/v2/anime/21/my_list_status";
+        let (client, auth) = replay("anime-delete", &[(Method::DELETE, url, "[]")]).await;
+        delete_anime_from_list(21, &client, &auth).await.unwrap();
+        assert!(delete_anime_from_list(5114, &client, &auth).await.is_err());
     }
 
     #[tokio::test]
     async fn test_update_anime_list() {
-        let auth = crate::auth::tests::get_auth();
         let query = UpdateUserAnimeListStatusQuery {
             status: Some(UserWatchStatus::Completed),
             is_rewatching: None,
             score: Some(8),
-            num_watched_episodes: Some(13),
+            num_watched_episodes: Some(24),
             priority: None,
             num_times_rewatched: None,
             rewatch_value: None,
             tags: None,
             comments: None,
+            start_date: None,
+            finish_date: None,
         };
-
-        let anime = get_anime(
-            "Yahari Ore no Seishun Love Comedy wa Machigatteiru. Kan",
-            &auth,
-        )
-        .await
-        .unwrap();
-
-        let result = update_anime_list_status(anime.id, &query, &auth)
+        let url = "https://api.myanimelist.net/v2/anime/9253/my_list_status";
+        let body = r#"{
+            "status": "completed",
+            "score": 8,
+            "num_episodes_watched": 24,
+            "is_rewatching": false,
+            "updated_at": "2024-03-02T12:00:00+00:00"
+        }"#;
+        let (client, auth) = replay("anime-update", &[(Method::PATCH, url, body)]).await;
+        let result = update_anime_list_status(9253, &query, &client, &auth)
             .await
             .unwrap();
-        println!("{:#?}", result);
-        assert_eq!(result.num_episodes_watched, 5);
+        assert_eq!(result.num_episodes_watched, 24);
+        assert_eq!(result.score, 8);
     }
 
     #[tokio::test]
     async fn test_get_user_anime_list() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetUserAnimeListQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::list().into()),
             status: None,
             sort: Some(SortStyle::ListScore),
             limit: 2,
             offset: 0,
             nsfw: true,
         };
-        let result = get_user_anime_list("@me", &query, &auth).await.unwrap();
-
-        print!("{:#?}", result);
-
-        assert!(!result.data.is_empty());
+        let url = api_url("users/@me/animelist", &query);
+        let body = list_body(&anime_fixtures()[..2]);
+        let (client, auth) = replay("anime-user-list", &[(Method::GET, &url, &body)]).await;
+        let result = get_user_anime_list("@me", &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(result.data.len(), 2);
+        assert!(result.data[0].node.my_list_status.is_some());
     }
 }
@@ -0,0 +1,411 @@
+use super::stable_hash;
+use crate::config::app_config::ResponseCacheConfig;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Entries written between two sweeps of the cache directory
+const SWEEP_EVERY: usize = 100;
+
+tokio::task_local! {
+    /// Unix time of the oldest stale answer the current request was given
+    static STALE: Cell<Option<u64>>;
+}
+
+/// Runs a request, noting the stale answers it is given apart from the other requests.
+/// [`served_stale`] tells the request itself about them.
+pub async fn track_stale<F: Future>(request: F) -> F::Output {
+    STALE.scope(Cell::new(None), request).await
+}
+
+/// When the oldest stale answer of the current request was stored, if any
+pub fn served_stale() -> Option<u64> {
+    STALE.try_with(Cell::get).ok().flatten()
+}
+
+/// Remembers that a stale answer was served, keeping the oldest one
+fn mark_stale(stored_at: u64) {
+    let _ = STALE.try_with(|stale| {
+        stale.set(Some(stale.get().map_or(stored_at, |s| s.min(stored_at))));
+    });
+}
+
+/// How long a kind of response stays fresh
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheKind {
+    /// rankings, seasons and suggestions
+    Ranking,
+    /// anime, manga and forum topic details
+    Details,
+    /// anything under `/users`, always revalidated but kept for offline use
+    User,
+    Other,
+}
+
+impl CacheKind {
+    /// Picks the kind from an endpoint path like `/anime/1`
+    pub fn of(path: &str) -> Self {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["users", ..] => CacheKind::User,
+            ["anime" | "manga", "ranking"] | ["anime", "season", ..] | ["anime", "suggestions"] => {
+                CacheKind::Ranking
+            }
+            ["anime" | "manga", id] | ["forum", "topic", id] if id.parse::<u64>().is_ok() => {
+                CacheKind::Details
+            }
+            _ => CacheKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    endpoint: String,
+    /// unix time in seconds
+    stored_at: u64,
+    body: String,
+}
+
+/// A cached body and whether it outlived its ttl
+#[derive(Debug)]
+pub struct Cached {
+    pub body: Bytes,
+    pub stored_at: u64,
+    pub fresh: bool,
+}
+
+/// GET responses of the api saved on disk, keyed by endpoint and query.
+///
+/// Files are named `<hash of path>-<hash of query>.json` so everything cached
+/// for an endpoint can be dropped when the user edits it. In offline mode only
+/// the cache is used and stale entries are served as they are; the request they
+/// were served to learns it from [`served_stale`] so the UI can mark its page.
+/// Named profiles keep their responses in `profiles/<name>` below the root.
+/// Entries past `max_age_days`, then the oldest ones past `max_size_mb`, are
+/// swept when a directory is opened and every [`SWEEP_EVERY`] writes.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    root: PathBuf,
+    dir: PathBuf,
+    config: ResponseCacheConfig,
+    offline: bool,
+    writes: Arc<AtomicUsize>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, config: ResponseCacheConfig) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let cache = Self {
+            root: dir.clone(),
+            dir,
+            config,
+            offline: false,
+            writes: Arc::new(AtomicUsize::new(0)),
+        };
+        cache.sweep();
+        Ok(cache)
+    }
+
+    /// Never go to the network, answer from the cache only
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// The same cache for another account, `None` being the default one
+    pub fn for_profile(&self, profile: Option<&str>) -> io::Result<Self> {
+        let dir = match profile {
+            Some(name) => self.root.join("profiles").join(name),
+            None => self.root.clone(),
+        };
+        fs::create_dir_all(&dir)?;
+        let cache = Self {
+            dir,
+            writes: Arc::new(AtomicUsize::new(0)),
+            ..self.clone()
+        };
+        cache.sweep();
+        Ok(cache)
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    fn ttl(&self, kind: CacheKind) -> Duration {
+        let minutes = match kind {
+            CacheKind::Ranking => self.config.rankings_ttl_minutes,
+            CacheKind::Details => self.config.details_ttl_minutes,
+            CacheKind::User => 0,
+            CacheKind::Other => self.config.other_ttl_minutes,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    fn file(&self, endpoint: &str) -> PathBuf {
+        let (path, query) = endpoint.split_once('?').unwrap_or((endpoint, ""));
+        self.dir.join(format!(
+            "{:016x}-{:016x}.json",
+            stable_hash(path),
+            stable_hash(query)
+        ))
+    }
+
+    /// Looks an endpoint (path and query, relative to the api url) up
+    pub fn get(&self, endpoint: &str) -> Option<Cached> {
+        let content = fs::read(self.file(endpoint)).ok()?;
+        let entry: Entry = serde_json::from_slice(&content).ok()?;
+        // two endpoints could share a file name
+        if entry.endpoint != endpoint {
+            return None;
+        }
+        let path = endpoint.split('?').next().unwrap_or_default();
+        let age = now().saturating_sub(entry.stored_at);
+        Some(Cached {
+            fresh: Duration::from_secs(age) < self.ttl(CacheKind::of(path)),
+            stored_at: entry.stored_at,
+            body: Bytes::from(entry.body),
+        })
+    }
+
+    pub fn put(&self, endpoint: &str, body: &[u8]) {
+        let Ok(body) = std::str::from_utf8(body) else {
+            return;
+        };
+        let entry = Entry {
+            endpoint: endpoint.to_string(),
+            stored_at: now(),
+            body: body.to_string(),
+        };
+        let file = self.file(endpoint);
+        let result = serde_json::to_vec(&entry)
+            .map_err(io::Error::other)
+            .and_then(|content| write_atomic(&file, &content));
+        if let Err(e) = result {
+            warn!("could not cache {}: {}", endpoint, e);
+        }
+        if self.writes.fetch_add(1, Ordering::Relaxed) % SWEEP_EVERY == SWEEP_EVERY - 1 {
+            self.sweep();
+        }
+    }
+
+    /// Drops the entries older than `max_age_days`, then the oldest ones until
+    /// the directory fits in `max_size_mb`
+    pub fn sweep(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let max_age = Duration::from_secs(self.config.max_age_days * 24 * 60 * 60);
+        let mut files = Vec::new();
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+            let age = SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default();
+            if age > max_age {
+                fs::remove_file(entry.path()).ok();
+            } else {
+                files.push((modified, metadata.len(), entry.path()));
+            }
+        }
+        // newest first, everything past the budget goes
+        files.sort_by_key(|&(modified, _, _)| std::cmp::Reverse(modified));
+        let budget = self.config.max_size_mb * 1024 * 1024;
+        let mut size = 0;
+        for (_, len, path) in files {
+            size += len;
+            if size > budget {
+                debug!("dropping cached {}", path.display());
+                fs::remove_file(path).ok();
+            }
+        }
+    }
+
+    /// Drops every cached query of `path`, e.g. after the user edited that entry
+    pub fn invalidate(&self, path: &str) {
+        let prefix = format!("{:016x}-", stable_hash(path));
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                debug!("dropping cached {}", path);
+                fs::remove_file(entry.path()).ok();
+            }
+        }
+    }
+
+    /// Serves a stale entry, noting it for the request being made
+    pub fn serve_stale(&self, cached: Cached) -> Bytes {
+        mark_stale(cached.stored_at);
+        cached.body
+    }
+}
+
+/// Unix time in seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Write then rename, readers see the old file or the whole new one. Every write
+/// has its own temporary file, two tasks may fetch the same endpoint or cover at once.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::write(&tmp, content).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        fs::remove_file(&tmp).ok();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(name: &str, config: ResponseCacheConfig) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("mal-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ResponseCache::new(dir, config).unwrap()
+    }
+
+    #[test]
+    fn test_concurrent_writes_stay_whole() {
+        let dir = std::env::temp_dir().join(format!("mal-writes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("entry.json");
+        let contents: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 64 * 1024]).collect();
+        std::thread::scope(|scope| {
+            for content in &contents {
+                let path = &path;
+                scope.spawn(move || write_atomic(path, content).unwrap());
+            }
+        });
+
+        assert!(contents.contains(&fs::read(&path).unwrap()));
+        // no temporary file is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_cache_kind() {
+        assert_eq!(CacheKind::of("/anime/ranking"), CacheKind::Ranking);
+        assert_eq!(
+            CacheKind::of("/anime/season/2024/winter"),
+            CacheKind::Ranking
+        );
+        assert_eq!(CacheKind::of("/manga/2"), CacheKind::Details);
+        assert_eq!(CacheKind::of("/forum/topic/12"), CacheKind::Details);
+        assert_eq!(CacheKind::of("/users/@me/animelist"), CacheKind::User);
+        assert_eq!(CacheKind::of("/anime"), CacheKind::Other);
+    }
+
+    #[test]
+    fn test_ttls_and_invalidation() {
+        let cache = cache("ttl", ResponseCacheConfig::default());
+        cache.put("/anime/1?fields=id", b"{\"id\":1}");
+        cache.put("/users/@me/animelist?status=watching", b"{\"data\":[]}");
+
+        let details = cache.get("/anime/1?fields=id").unwrap();
+        assert!(details.fresh);
+        assert_eq!(&details.body[..], b"{\"id\":1}");
+        // user lists are stored, but always revalidated
+        assert!(
+            !cache
+                .get("/users/@me/animelist?status=watching")
+                .unwrap()
+                .fresh
+        );
+        assert!(cache.get("/anime/1?fields=title").is_none());
+
+        cache.invalidate("/anime/1");
+        assert!(cache.get("/anime/1?fields=id").is_none());
+        fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[test]
+    fn test_profiles_do_not_share_entries() {
+        let cache = cache("profiles", ResponseCacheConfig::default());
+        let work = cache.for_profile(Some("work")).unwrap();
+        work.put("/users/@me", b"{\"name\":\"work\"}");
+        assert!(cache.get("/users/@me").is_none());
+        assert!(work.get("/users/@me").is_some());
+        // going back to the default account finds its own entries again
+        cache.put("/users/@me", b"{\"name\":\"me\"}");
+        let default = work.for_profile(None).unwrap();
+        assert_eq!(
+            &default.get("/users/@me").unwrap().body[..],
+            b"{\"name\":\"me\"}"
+        );
+        fs::remove_dir_all(&cache.dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_stale_answers_stay_with_their_request() {
+        let first = track_stale(async {
+            mark_stale(200);
+            mark_stale(100);
+            served_stale()
+        });
+        let second = track_stale(async { served_stale() });
+        assert_eq!(tokio::join!(first, second), (Some(100), None));
+        // outside of a request nothing is noted
+        mark_stale(100);
+        assert_eq!(served_stale(), None);
+    }
+
+    #[test]
+    fn test_sweep_keeps_the_newest_entries() {
+        let config = ResponseCacheConfig {
+            max_size_mb: 1,
+            ..ResponseCacheConfig::default()
+        };
+        let cache = cache("sweep", config);
+        let body = "x".repeat(400 * 1024);
+        for id in 1..=3 {
+            cache.put(&format!("/anime/{}", id), body.as_bytes());
+            // file times are not always finer than a second
+            std::thread::sleep(Duration::from_millis(1100));
+        }
+        cache.sweep();
+        assert!(cache.get("/anime/1").is_none());
+        assert!(cache.get("/anime/2").is_some());
+        assert!(cache.get("/anime/3").is_some());
+
+        let expired = ResponseCache {
+            config: ResponseCacheConfig {
+                max_age_days: 0,
+                ..ResponseCacheConfig::default()
+            },
+            ..cache.clone()
+        };
+        expired.sweep();
+        assert!(cache.get("/anime/3").is_none());
+        fs::remove_dir_all(&cache.dir).ok();
+    }
+}
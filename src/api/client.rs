@@ -0,0 +1,289 @@
+use super::cache::ResponseCache;
+use super::limiter::{Priority, RateLimiter};
+use super::replay::Recorder;
+use super::{Error, API_URL};
+use crate::config::app_config::NetworkConfig;
+use bytes::Bytes;
+use reqwest::{Method, Request, RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+use tracing::warn;
+
+/// Long-lived HTTP client shared by every api call.
+///
+/// Wraps a single `reqwest::Client` so connections are pooled, and retries
+/// rate limited (429), server side (5xx) and transient network failures with
+/// exponential backoff. Every request goes through the shared [`RateLimiter`]
+/// at the client's [`Priority`].
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    api_url: String,
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    limiter: RateLimiter,
+    priority: Priority,
+    cache: Option<ResponseCache>,
+    recorder: Option<Recorder>,
+}
+
+impl ApiClient {
+    pub fn new(config: &NetworkConfig) -> Result<Self, reqwest::Error> {
+        let http = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+            .timeout(Duration::from_secs(config.request_timeout_seconds))
+            .build()?;
+
+        Ok(Self {
+            http,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_milliseconds),
+            max_delay: Duration::from_millis(config.retry_max_delay_milliseconds),
+            limiter: RateLimiter::new(config.rate_limit_burst, config.rate_limit_per_second),
+            priority: Priority::Normal,
+            cache: None,
+            recorder: None,
+        })
+    }
+
+    /// Keep api responses on disk, see [`ResponseCache`]
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Go to the network for everything
+    pub fn without_cache(mut self) -> Self {
+        self.cache = None;
+        self
+    }
+
+    pub fn response_cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    /// Save every response to a fixtures directory, see [`Recorder`]
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// A client sharing this one's connections and rate limit, queued at `priority`
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Base url of the api, without a trailing slash
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Points a link handed out by MAL (e.g. `paging.next`) at the configured api url
+    pub fn rebase_url(&self, url: &str) -> String {
+        match url.strip_prefix(API_URL) {
+            Some(rest) if self.api_url != API_URL => format!("{}{}", self.api_url, rest),
+            _ => url.to_string(),
+        }
+    }
+
+    /// The underlying client, used to build requests
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Send a request, retrying it while the failure looks transient
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            // requests with a streaming body can't be cloned, so they are only sent once
+            let Some(current) = request.try_clone() else {
+                return request.send().await;
+            };
+            let can_retry = attempt < self.max_retries;
+
+            self.limiter.acquire(self.priority).await;
+            match current.send().await {
+                Ok(response) if can_retry && is_retryable_status(response.status()) => {
+                    let delay = retry_after(&response)
+                        .map(|d| d.min(self.max_delay))
+                        .unwrap_or_else(|| self.backoff_delay(attempt));
+                    warn!(
+                        "request to {} failed with {}, retrying in {:?}",
+                        response.url(),
+                        response.status(),
+                        delay
+                    );
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        // every request would get the same answer, so hold them all back;
+                        // the next acquire waits out the pause
+                        self.limiter.pause(delay);
+                    } else {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                Err(e) if can_retry && is_transient_error(&e) => {
+                    let delay = self.backoff_delay(attempt);
+                    warn!("request failed: {}, retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Send a request and read the whole body.
+    ///
+    /// GETs of the api go through the response cache when one is set: fresh entries
+    /// are answered without a request, stale ones when offline or the network fails.
+    pub async fn fetch(&self, request: RequestBuilder) -> Result<(StatusCode, Bytes), Error> {
+        let target = request.try_clone().and_then(|r| r.build().ok());
+        let endpoint = target.as_ref().and_then(|t| self.endpoint(t));
+        let cache = self.cache.as_ref().zip(endpoint.as_ref());
+        let is_get = target.as_ref().is_some_and(|t| t.method() == Method::GET);
+
+        let mut stale = None;
+        if let Some((cache, endpoint)) = cache.filter(|_| is_get) {
+            match cache.get(endpoint) {
+                Some(cached) if cached.fresh => return Ok((StatusCode::OK, cached.body)),
+                cached => stale = cached,
+            }
+        }
+        if self.cache.as_ref().is_some_and(|c| c.is_offline()) {
+            return match (self.cache.as_ref(), stale) {
+                (Some(cache), Some(cached)) => Ok((StatusCode::OK, cache.serve_stale(cached))),
+                _ => Err(Error::Offline),
+            };
+        }
+
+        let response = match self.execute(request).await {
+            Ok(response) => response,
+            Err(e) if e.is_connect() || e.is_timeout() => match (self.cache.as_ref(), stale) {
+                (Some(cache), Some(cached)) => {
+                    warn!("{}, using cached response", e);
+                    return Ok((StatusCode::OK, cache.serve_stale(cached)));
+                }
+                _ => return Err(e.into()),
+            },
+            Err(e) => return Err(e.into()),
+        };
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        if let (Some(recorder), Some(target)) = (&self.recorder, &target) {
+            recorder.record(target.method(), target.url(), status, &body);
+        }
+        if let Some((cache, endpoint)) = cache.filter(|_| status.is_success()) {
+            if is_get {
+                cache.put(endpoint, &body);
+            } else {
+                // an edit, e.g. PATCH /anime/1/my_list_status, outdates the entry's page
+                let path = endpoint.split('?').next().unwrap_or_default();
+                cache.invalidate(path.trim_end_matches("/my_list_status"));
+            }
+        }
+        Ok((status, body))
+    }
+
+    /// Path and query of an api request, relative to the api url
+    fn endpoint(&self, request: &Request) -> Option<String> {
+        let endpoint = request.url().as_str().strip_prefix(&self.api_url)?;
+        endpoint.starts_with('/').then(|| endpoint.to_string())
+    }
+
+    /// Download a raw resource (e.g. a cover image)
+    pub async fn get_bytes(&self, url: &str) -> Result<Bytes, Error> {
+        let (status, body) = self.fetch(self.http.get(url)).await?;
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(Error::RateLimited);
+        } else if !status.is_success() {
+            return Err(Error::HttpError(status));
+        }
+        Ok(body)
+    }
+
+    /// Delay before retry number `attempt` (starting at 0): base * 2^attempt, capped at max
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn is_transient_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect()
+}
+
+/// Reads the `Retry-After` header when it is given in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        let config = NetworkConfig {
+            retry_base_delay_milliseconds: 100,
+            retry_max_delay_milliseconds: 1000,
+            ..NetworkConfig::default()
+        };
+        let client = ApiClient::new(&config).unwrap();
+
+        assert_eq!(client.backoff_delay(0), Duration::from_millis(100));
+        assert_eq!(client.backoff_delay(1), Duration::from_millis(200));
+        assert_eq!(client.backoff_delay(3), Duration::from_millis(800));
+        assert_eq!(client.backoff_delay(4), Duration::from_millis(1000));
+        assert_eq!(client.backoff_delay(40), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn test_rebase_url() {
+        let config = NetworkConfig {
+            api_url: "http://127.0.0.1:2007/v2/".to_string(),
+            ..NetworkConfig::default()
+        };
+        let client = ApiClient::new(&config).unwrap();
+
+        assert_eq!(client.api_url(), "http://127.0.0.1:2007/v2");
+        assert_eq!(
+            client.rebase_url("https://api.myanimelist.net/v2/anime?offset=10"),
+            "http://127.0.0.1:2007/v2/anime?offset=10"
+        );
+        assert_eq!(
+            client.rebase_url("https://cdn.myanimelist.net/images/1.jpg"),
+            "https://cdn.myanimelist.net/images/1.jpg"
+        );
+    }
+}
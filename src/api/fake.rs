@@ -0,0 +1,733 @@
+use super::model::*;
+use super::*;
+use crate::auth::AuthError;
+use bytes::Bytes;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+const ANIME_FIXTURES: &str = include_str!("fixtures/anime.json");
+const MANGA_FIXTURES: &str = include_str!("fixtures/manga.json");
+const USER_FIXTURE: &str = include_str!("fixtures/user.json");
+const FORUM_FIXTURES: &str = include_str!("fixtures/forum.json");
+
+/// A list change sent to a [`FakeMalApi`]
+#[derive(Clone, Debug)]
+pub enum Mutation {
+    UpdateAnime(u64, UpdateUserAnimeListStatusQuery),
+    DeleteAnime(u64),
+    UpdateManga(u64, UpdateUserMangaStatus),
+    DeleteManga(u64),
+}
+
+/// In-memory [`MalApi`] serving fixture data.
+///
+/// Searches, rankings, seasons and the user's lists are all answered from
+/// the same anime and manga, and list updates are applied to them and
+/// recorded. Lists are split in pages like MAL does, the pages after the
+/// first are kept for [`get_next_page`](MalApi::get_next_page). Clones share
+/// their state, so a test can keep a handle on the fake it gave to the network.
+#[derive(Clone, Debug)]
+pub struct FakeMalApi {
+    state: Arc<Mutex<FakeState>>,
+}
+
+#[derive(Debug)]
+struct FakeState {
+    anime: Vec<Anime>,
+    manga: Vec<Manga>,
+    user: UserInfo,
+    topics: Vec<FakeTopic>,
+    mutations: Vec<Mutation>,
+    /// pages after the first of every list handed out, by their `next` url
+    pages: HashMap<String, serde_json::Value>,
+    lists: u64,
+    /// bodies served by `get_bytes`, by url
+    images: HashMap<String, Bytes>,
+    fail_with: Option<StatusCode>,
+    delay: Option<Duration>,
+    auth_error: Option<AuthError>,
+}
+
+/// A forum topic with all of its posts, and the anime or manga it discusses
+#[derive(Debug, Deserialize)]
+struct FakeTopic {
+    anime_id: Option<u64>,
+    manga_id: Option<u64>,
+    topic: ForumTopic,
+    thread: ForumTopicDetail,
+}
+
+impl Default for FakeMalApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeMalApi {
+    /// A fake loaded with the bundled fixtures
+    pub fn new() -> Self {
+        let state = FakeState {
+            anime: serde_json::from_str(ANIME_FIXTURES).expect("invalid anime fixtures"),
+            manga: serde_json::from_str(MANGA_FIXTURES).expect("invalid manga fixtures"),
+            user: serde_json::from_str(USER_FIXTURE).expect("invalid user fixture"),
+            topics: serde_json::from_str(FORUM_FIXTURES).expect("invalid forum fixtures"),
+            mutations: Vec::new(),
+            pages: HashMap::new(),
+            lists: 0,
+            images: HashMap::new(),
+            fail_with: None,
+            delay: None,
+            auth_error: None,
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Adds an anime, replacing the fixture with the same id
+    pub fn with_anime(self, anime: Anime) -> Self {
+        {
+            let mut state = self.state();
+            state.anime.retain(|a| a.id != anime.id);
+            state.anime.push(anime);
+        }
+        self
+    }
+
+    /// Adds a manga, replacing the fixture with the same id
+    pub fn with_manga(self, manga: Manga) -> Self {
+        {
+            let mut state = self.state();
+            state.manga.retain(|m| m.id != manga.id);
+            state.manga.push(manga);
+        }
+        self
+    }
+
+    pub fn with_user(self, user: UserInfo) -> Self {
+        self.state().user = user;
+        self
+    }
+
+    /// Serves `bytes` at `url`, every other image is not found
+    pub fn with_image(self, url: &str, bytes: impl Into<Bytes>) -> Self {
+        self.state().images.insert(url.to_string(), bytes.into());
+        self
+    }
+
+    /// Makes every following request fail with `status`, or succeed again with `None`
+    pub fn fail_with(&self, status: Option<StatusCode>) {
+        self.state().fail_with = status;
+    }
+
+    /// Makes every following request take `delay`, or answer right away with `None`
+    pub fn delay(&self, delay: Option<Duration>) {
+        self.state().delay = delay;
+    }
+
+    /// Makes refreshing the token fail with `error`, or succeed again with `None`
+    pub fn fail_auth_with(&self, error: Option<AuthError>) {
+        self.state().auth_error = error;
+    }
+
+    /// The list changes received so far, oldest first
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state().mutations.clone()
+    }
+
+    pub fn anime(&self, anime_id: u64) -> Option<Anime> {
+        self.state()
+            .anime
+            .iter()
+            .find(|a| a.id == anime_id)
+            .cloned()
+    }
+
+    pub fn manga(&self, manga_id: u64) -> Option<Manga> {
+        self.state()
+            .manga
+            .iter()
+            .find(|m| m.id == manga_id)
+            .cloned()
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeState> {
+        // a test that panicked while holding the lock already failed
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The state after the configured delay, or the configured failure
+    async fn respond(&self) -> Result<MutexGuard<'_, FakeState>, Error> {
+        let delay = self.state().delay;
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        let state = self.state();
+        match state.fail_with {
+            Some(status) => Err(Error::HttpError(status)),
+            None => Ok(state),
+        }
+    }
+}
+
+impl FakeState {
+    /// The page of `items` at `offset`, keeping the ones after it for `get_next_page`
+    fn page<T: Clone + Debug + Serialize>(
+        &mut self,
+        items: Vec<T>,
+        limit: u64,
+        offset: u64,
+    ) -> PageableData<Vec<T>> {
+        self.lists += 1;
+        let list = self.lists;
+        let url = |offset: u64| {
+            format!(
+                "https://api.myanimelist.net/v2/fake?list={}&offset={}",
+                list, offset
+            )
+        };
+        let limit = limit.max(1);
+
+        let mut first = None;
+        let mut offset = offset;
+        loop {
+            // like MAL, only pages with more after them link to the next one
+            let next = (((offset + limit) as usize) < items.len()).then(|| url(offset + limit));
+            let page = PageableData {
+                data: items
+                    .iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .cloned()
+                    .collect(),
+                paging: Paging {
+                    previous: None,
+                    next: next.clone(),
+                },
+            };
+            if first.is_none() {
+                first = Some(page);
+            } else {
+                let page = serde_json::to_value(&page).expect("fake pages serialize");
+                self.pages.insert(url(offset), page);
+            }
+            if next.is_none() {
+                break;
+            }
+            offset += limit;
+        }
+        first.unwrap()
+    }
+}
+
+fn nodes<T: Clone + Debug>(items: Vec<T>) -> Vec<Node<T>> {
+    items.into_iter().map(|node| Node { node }).collect()
+}
+
+fn matches_title(title: &str, q: &str) -> bool {
+    title.to_lowercase().contains(&q.to_lowercase())
+}
+
+fn by_mean<T>(items: &mut [T], mean: impl Fn(&T) -> Option<f64>) {
+    items.sort_by(|a, b| mean(b).unwrap_or(0.).total_cmp(&mean(a).unwrap_or(0.)));
+}
+
+fn not_found() -> Error {
+    Error::HttpError(StatusCode::NOT_FOUND)
+}
+
+fn now() -> DateTimeWrapper {
+    DateTimeWrapper {
+        datetime: time::PrimitiveDateTime::new(DateWrapper::today().date, time::Time::MIDNIGHT),
+    }
+}
+
+fn split_tags(tags: &Option<String>) -> Option<Vec<String>> {
+    tags.as_ref()
+        .map(|tags| tags.split(',').map(|t| t.trim().to_string()).collect())
+}
+
+impl MalApi for FakeMalApi {
+    async fn refresh_auth(&mut self) -> Result<(), AuthError> {
+        match self.state().auth_error.clone() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn begin_login(&mut self) -> Result<url::Url, AuthError> {
+        Ok(url::Url::parse("https://myanimelist.net/v1/oauth2/authorize?state=fake").unwrap())
+    }
+
+    /// Logging in fixes whatever [`fail_auth_with`](Self::fail_auth_with) broke
+    async fn finish_login(&mut self) -> Result<(), AuthError> {
+        self.state().auth_error = None;
+        Ok(())
+    }
+
+    fn switch_profile(&mut self, _profile: Option<&str>) -> Result<(), AuthError> {
+        Ok(())
+    }
+
+    async fn get_anime_list(&self, query: &GetAnimeListQuery) -> Result<Page<Anime>, Error> {
+        let mut state = self.respond().await?;
+        let found = state
+            .anime
+            .iter()
+            .filter(|a| matches_title(&a.title, &query.q))
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(found), query.limit, query.offset))
+    }
+
+    async fn get_anime_details(
+        &self,
+        anime_id: u64,
+        _query: &GetAnimeDetailQuery,
+    ) -> Result<Anime, Error> {
+        let state = self.respond().await?;
+        state
+            .anime
+            .iter()
+            .find(|a| a.id == anime_id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn get_anime_ranking(
+        &self,
+        query: &GetAnimeRankingQuery,
+    ) -> Result<Ranking<RankingAnimePair>, Error> {
+        let mut state = self.respond().await?;
+        let mut anime = state.anime.clone();
+        by_mean(&mut anime, |a| a.mean);
+        let ranking = anime
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| RankingAnimePair {
+                node,
+                ranking: RankingInfo {
+                    rank: i as u64 + 1,
+                    previous_rank: None,
+                },
+            })
+            .collect();
+        Ok(state.page(ranking, query.limit, query.offset))
+    }
+
+    async fn get_seasonal_anime(
+        &self,
+        season: &AnimeSeason,
+        query: &GetSeasonalAnimeQuery,
+    ) -> Result<Page<Anime>, Error> {
+        let mut state = self.respond().await?;
+        let seasonal = state
+            .anime
+            .iter()
+            .filter(|a| {
+                a.start_season
+                    .as_ref()
+                    .is_some_and(|s| s.season == season.season && s.year as u64 == season.year)
+            })
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(seasonal), query.limit, query.offset))
+    }
+
+    async fn get_suggested_anime(
+        &self,
+        query: &GetSuggestedAnimeQuery,
+    ) -> Result<Page<Anime>, Error> {
+        let mut state = self.respond().await?;
+        let suggested = state
+            .anime
+            .iter()
+            .filter(|a| a.my_list_status.is_none())
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(suggested), query.limit, query.offset))
+    }
+
+    async fn get_manga_list(&self, query: &GetMangaListQuery) -> Result<Page<Manga>, Error> {
+        let mut state = self.respond().await?;
+        let found = state
+            .manga
+            .iter()
+            .filter(|m| matches_title(&m.title, &query.q))
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(found), query.limit, query.offset))
+    }
+
+    async fn get_manga_details(
+        &self,
+        manga_id: u64,
+        _query: &GetMangaDetailQuery,
+    ) -> Result<Manga, Error> {
+        let state = self.respond().await?;
+        state
+            .manga
+            .iter()
+            .find(|m| m.id == manga_id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn get_manga_ranking(
+        &self,
+        query: &GetMangaRankingQuery,
+    ) -> Result<Ranking<RankingMangaPair>, Error> {
+        let mut state = self.respond().await?;
+        let mut manga = state.manga.clone();
+        by_mean(&mut manga, |m| m.mean);
+        let ranking = manga
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| RankingMangaPair {
+                node,
+                ranking: RankingInfo {
+                    rank: i as u64 + 1,
+                    previous_rank: None,
+                },
+            })
+            .collect();
+        Ok(state.page(ranking, query.limit, query.offset))
+    }
+
+    async fn get_user_anime_list(
+        &self,
+        _user: &str,
+        query: &GetUserAnimeListQuery,
+    ) -> Result<Page<Anime>, Error> {
+        let mut state = self.respond().await?;
+        let list = state
+            .anime
+            .iter()
+            .filter(|a| match (&a.my_list_status, &query.status) {
+                (Some(list), Some(status)) => list.status == *status,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(list), query.limit, query.offset))
+    }
+
+    async fn update_anime_list_status(
+        &self,
+        anime_id: u64,
+        update: &UpdateUserAnimeListStatusQuery,
+    ) -> Result<UserAnimeListStatus, Error> {
+        let mut state = self.respond().await?;
+        let anime = state
+            .anime
+            .iter_mut()
+            .find(|a| a.id == anime_id)
+            .ok_or_else(not_found)?;
+        let old = anime.my_list_status.take();
+        let status = UserAnimeListStatus {
+            status: update
+                .status
+                .clone()
+                .or(old.as_ref().map(|o| o.status.clone()))
+                .unwrap_or(UserWatchStatus::PlanToWatch),
+            score: update.score.or(old.as_ref().map(|o| o.score)).unwrap_or(0),
+            num_episodes_watched: update
+                .num_watched_episodes
+                .or(old.as_ref().map(|o| o.num_episodes_watched))
+                .unwrap_or(0),
+            is_rewatching: update
+                .is_rewatching
+                .or(old.as_ref().map(|o| o.is_rewatching))
+                .unwrap_or(false),
+            start_date: update
+                .start_date
+                .clone()
+                .unwrap_or_else(|| old.as_ref().and_then(|o| o.start_date.clone())),
+            finish_date: update
+                .finish_date
+                .clone()
+                .unwrap_or_else(|| old.as_ref().and_then(|o| o.finish_date.clone())),
+            priority: update.priority.or(old.as_ref().and_then(|o| o.priority)),
+            num_times_rewatched: update
+                .num_times_rewatched
+                .or(old.as_ref().and_then(|o| o.num_times_rewatched)),
+            rewatch_value: update
+                .rewatch_value
+                .or(old.as_ref().and_then(|o| o.rewatch_value)),
+            tags: split_tags(&update.tags).or(old.as_ref().and_then(|o| o.tags.clone())),
+            comments: update
+                .comments
+                .clone()
+                .or(old.as_ref().and_then(|o| o.comments.clone())),
+            updated_at: now(),
+        };
+        anime.my_list_status = Some(status.clone());
+        state
+            .mutations
+            .push(Mutation::UpdateAnime(anime_id, update.clone()));
+        Ok(status)
+    }
+
+    async fn delete_anime_from_list(&self, anime_id: u64) -> Result<(), Error> {
+        let mut state = self.respond().await?;
+        let anime = state
+            .anime
+            .iter_mut()
+            .find(|a| a.id == anime_id)
+            .ok_or_else(not_found)?;
+        anime.my_list_status = None;
+        state.mutations.push(Mutation::DeleteAnime(anime_id));
+        Ok(())
+    }
+
+    async fn get_user_manga_list(
+        &self,
+        _user: &str,
+        query: &GetUserMangaListQuery,
+    ) -> Result<Page<Manga>, Error> {
+        let mut state = self.respond().await?;
+        let list = state
+            .manga
+            .iter()
+            .filter(|m| match (&m.my_list_status, &query.status) {
+                (Some(list), Some(status)) => list.status == *status,
+                (Some(_), None) => true,
+                (None, _) => false,
+            })
+            .cloned()
+            .collect();
+        Ok(state.page(nodes(list), query.limit, query.offset))
+    }
+
+    async fn update_manga_list_status(
+        &self,
+        manga_id: u64,
+        update: &UpdateUserMangaStatus,
+    ) -> Result<UserMangaListStatus, Error> {
+        let mut state = self.respond().await?;
+        let manga = state
+            .manga
+            .iter_mut()
+            .find(|m| m.id == manga_id)
+            .ok_or_else(not_found)?;
+        let old = manga.my_list_status.take();
+        let status = UserMangaListStatus {
+            status: update
+                .status
+                .clone()
+                .or(old.as_ref().map(|o| o.status.clone()))
+                .unwrap_or(UserReadStatus::PlanToRead),
+            score: update.score.or(old.as_ref().map(|o| o.score)).unwrap_or(0),
+            num_volumes_read: update
+                .num_volumes_read
+                .or(old.as_ref().map(|o| o.num_volumes_read))
+                .unwrap_or(0),
+            num_chapters_read: update
+                .num_chapters_read
+                .or(old.as_ref().map(|o| o.num_chapters_read))
+                .unwrap_or(0),
+            is_rereading: update
+                .is_rereading
+                .or(old.as_ref().map(|o| o.is_rereading))
+                .unwrap_or(false),
+            start_date: update
+                .start_date
+                .clone()
+                .unwrap_or_else(|| old.as_ref().and_then(|o| o.start_date.clone())),
+            finish_date: update
+                .finish_date
+                .clone()
+                .unwrap_or_else(|| old.as_ref().and_then(|o| o.finish_date.clone())),
+            priority: update.priority.or(old.as_ref().and_then(|o| o.priority)),
+            num_times_reread: update
+                .num_times_reread
+                .or(old.as_ref().and_then(|o| o.num_times_reread)),
+            reread_value: update
+                .reread_value
+                .or(old.as_ref().and_then(|o| o.reread_value)),
+            tags: split_tags(&update.tags).or(old.as_ref().and_then(|o| o.tags.clone())),
+            comments: update
+                .comments
+                .clone()
+                .or(old.as_ref().and_then(|o| o.comments.clone())),
+            updated_at: now(),
+        };
+        manga.my_list_status = Some(status.clone());
+        state
+            .mutations
+            .push(Mutation::UpdateManga(manga_id, update.clone()));
+        Ok(status)
+    }
+
+    async fn delete_manga_from_list(&self, manga_id: u64) -> Result<(), Error> {
+        let mut state = self.respond().await?;
+        let manga = state
+            .manga
+            .iter_mut()
+            .find(|m| m.id == manga_id)
+            .ok_or_else(not_found)?;
+        manga.my_list_status = None;
+        state.mutations.push(Mutation::DeleteManga(manga_id));
+        Ok(())
+    }
+
+    async fn get_my_user_information(
+        &self,
+        _user: &str,
+        _query: &GetUserInformationQuery,
+    ) -> Result<UserInfo, Error> {
+        Ok(self.respond().await?.user.clone())
+    }
+
+    async fn get_forum_topics(
+        &self,
+        query: &GetForumTopicsQuery,
+    ) -> Result<PageableData<Vec<ForumTopic>>, Error> {
+        let mut state = self.respond().await?;
+        let found = state
+            .topics
+            .iter()
+            .filter(|t| query.anime_id.is_none_or(|id| t.anime_id == Some(id)))
+            .filter(|t| query.manga_id.is_none_or(|id| t.manga_id == Some(id)))
+            .filter(|t| {
+                query
+                    .q
+                    .as_ref()
+                    .is_none_or(|q| matches_title(&t.topic.title, q))
+            })
+            .map(|t| t.topic.clone())
+            .collect();
+        Ok(state.page(found, query.limit, query.offset))
+    }
+
+    async fn get_forum_topic_detail(
+        &self,
+        topic_id: u64,
+        query: &GetForumTopicDetailQuery,
+    ) -> Result<PageableData<ForumTopicDetail>, Error> {
+        let state = self.respond().await?;
+        let thread = state
+            .topics
+            .iter()
+            .find(|t| t.topic.id == topic_id)
+            .map(|t| &t.thread)
+            .ok_or_else(not_found)?;
+        let end = query.offset + query.limit;
+        let next = ((end as usize) < thread.posts.len()).then(|| {
+            format!(
+                "https://api.myanimelist.net/v2/forum/topic/{}?limit={}&offset={}",
+                topic_id, query.limit, end
+            )
+        });
+        Ok(PageableData {
+            data: ForumTopicDetail {
+                title: thread.title.clone(),
+                posts: thread
+                    .posts
+                    .iter()
+                    .skip(query.offset as usize)
+                    .take(query.limit as usize)
+                    .cloned()
+                    .collect(),
+                poll: thread.poll.clone(),
+            },
+            paging: Paging {
+                previous: None,
+                next,
+            },
+        })
+    }
+
+    /// Serves the pages kept when the first page of the list was handed out
+    async fn get_next_page<T>(&self, paging: &Paging) -> Result<Option<PageableData<Vec<T>>>, Error>
+    where
+        T: DeserializeOwned + Clone + Debug + Send,
+    {
+        let state = self.respond().await?;
+        let Some(next) = &paging.next else {
+            return Ok(None);
+        };
+        let page = state.pages.get(next).cloned().ok_or_else(not_found)?;
+        Ok(Some(serde_json::from_value(page)?))
+    }
+
+    async fn get_bytes(&self, url: &str) -> Result<Bytes, Error> {
+        self.respond()
+            .await?
+            .images
+            .get(url)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures_load() {
+        let fake = FakeMalApi::new();
+        assert!(fake.anime(5114).unwrap().my_list_status.is_some());
+        assert!(fake.manga(2).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_records_mutation() {
+        let fake = FakeMalApi::new();
+        let update = UpdateUserAnimeListStatusQuery {
+            status: Some(UserWatchStatus::Watching),
+            is_rewatching: None,
+            score: None,
+            num_watched_episodes: Some(3),
+            priority: None,
+            num_times_rewatched: None,
+            rewatch_value: None,
+            tags: None,
+            comments: None,
+            start_date: None,
+            finish_date: None,
+        };
+        let status = fake.update_anime_list_status(9253, &update).await.unwrap();
+        assert_eq!(status.num_episodes_watched, 3);
+        assert!(matches!(
+            fake.mutations().as_slice(),
+            [Mutation::UpdateAnime(9253, _)]
+        ));
+
+        fake.fail_with(Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(fake.update_anime_list_status(9253, &update).await.is_err());
+        assert_eq!(fake.mutations().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_next_pages_continue_the_list() {
+        let fake = FakeMalApi::new();
+        let query = GetMangaRankingQuery {
+            ranking_type: MangaRankingType::All,
+            limit: 1,
+            offset: 0,
+            nsfw: false,
+            fields: None,
+        };
+        let first = fake.get_manga_ranking(&query).await.unwrap();
+        assert_eq!(first.data[0].node.id, 2);
+
+        let second: Ranking<RankingMangaPair> =
+            fake.get_next_page(&first.paging).await.unwrap().unwrap();
+        assert_eq!(second.data[0].node.id, 13);
+        assert_eq!(second.data[0].ranking.rank, 2);
+        assert!(!second.has_next_page());
+        assert!(fake
+            .get_next_page::<RankingMangaPair>(&second.paging)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use super::model::*;
+use super::{get, handle_response};
+use super::{ApiClient, Error};
+use crate::auth::OAuth;
+use serde::Serialize;
+
+pub async fn get_forum_boards(client: &ApiClient, auth: &OAuth) -> Result<ForumBoards, Error> {
+    let response = get(&format!("{}/forum/boards", client.api_url()), client, auth).await?;
+    handle_response(&response)
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetForumTopicsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subboard_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anime_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manga_id: Option<u64>,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+pub async fn get_forum_topics(
+    query: &GetForumTopicsQuery,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<PageableData<Vec<ForumTopic>>, Error> {
+    let response = get(
+        &format!(
+            "{}/forum/topics?{}",
+            client.api_url(),
+            serde_urlencoded::to_string(query)?
+        ),
+        client,
+        auth,
+    )
+    .await?;
+    handle_response(&response)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GetForumTopicDetailQuery {
+    pub limit: u64,
+    pub offset: u64,
+}
+
+/// Fetches one page of a topic's posts
+pub async fn get_forum_topic_detail(
+    topic_id: u64,
+    query: &GetForumTopicDetailQuery,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<PageableData<ForumTopicDetail>, Error> {
+    let response = get(
+        &format!(
+            "{}/forum/topic/{}?{}",
+            client.api_url(),
+            topic_id,
+            serde_urlencoded::to_string(query)?
+        ),
+        client,
+        auth,
+    )
+    .await?;
+    handle_response(&response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::tests::replay;
+    use reqwest::Method;
+
+    #[test]
+    fn test_topics_query_skips_unset_filters() {
+        let query = GetForumTopicsQuery {
+            q: Some("Frieren Episode".to_string()),
+            anime_id: Some(52991),
+            limit: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_urlencoded::to_string(&query).unwrap(),
+            "q=Frieren+Episode&anime_id=52991&limit=10&offset=0"
+        );
+    }
+
+    #[test]
+    fn test_parse_topic_detail() {
+        let body = r#"{
+            "data": {
+                "title": "Sousou no Frieren Episode 1 Discussion",
+                "posts": [{
+                    "id": 1,
+                    "number": 1,
+                    "created_at": "2023-09-29T13:30:00+00:00",
+                    "created_by": {"id": 7, "name": "Stark", "forum_avator": ""},
+                    "body": "[b]Spoilers[/b] ahead",
+                    "signature": ""
+                }],
+                "poll": null
+            },
+            "paging": {"next": "https://api.myanimelist.net/v2/forum/topic/1?offset=1"}
+        }"#;
+        let page: PageableData<ForumTopicDetail> = serde_json::from_str(body).unwrap();
+        assert_eq!(page.data.posts.len(), 1);
+        assert_eq!(page.data.posts[0].created_by.name, "Stark");
+        assert!(page.paging.next.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_forum_boards() {
+        let boards = r#"{"categories": [{
+            "title": "MyAnimeList",
+            "boards": [{
+                "id": 5,
+                "title": "Updates & Announcements",
+                "description": "Updates, changes, and additions to MAL.",
+                "subboards": []
+            }]
+        }]}"#;
+        let (client, auth) = replay(
+            "boards",
+            &[(
+                Method::GET,
+                "https://api.myanimelist.net/v2/forum/boards",
+                boards,
+            )],
+        )
+        .await;
+        let result = get_forum_boards(&client, &auth).await.unwrap();
+        assert_eq!(result.categories[0].boards[0].id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_forum_topics() {
+        let topics = r#"{
+            "data": [{
+                "id": 2141189,
+                "title": "Kaguya-sama wa Kokurasetai Episode 1 Discussion",
+                "created_at": "2019-01-12T16:30:00+00:00",
+                "created_by": {"id": 7, "name": "Shirogane"},
+                "number_of_posts": 2,
+                "last_post_created_at": "2019-01-12T17:00:00+00:00",
+                "last_post_created_by": {"id": 8, "name": "Chika"},
+                "is_locked": false
+            }],
+            "paging": {}
+        }"#;
+        let topic = r#"{
+            "data": {
+                "title": "Kaguya-sama wa Kokurasetai Episode 1 Discussion",
+                "posts": [{
+                    "id": 1,
+                    "number": 1,
+                    "created_at": "2019-01-12T16:30:00+00:00",
+                    "created_by": {"id": 7, "name": "Shirogane", "forum_avator": ""},
+                    "body": "How cute",
+                    "signature": ""
+                }],
+                "poll": null
+            },
+            "paging": {}
+        }"#;
+        let (client, auth) = replay(
+            "topics",
+            &[
+                (
+                    Method::GET,
+                    "https://api.myanimelist.net/v2/forum/topics?q=Kaguya-sama+Episode&limit=5&offset=0",
+                    topics,
+                ),
+                (
+                    Method::GET,
+                    "https://api.myanimelist.net/v2/forum/topic/2141189?limit=5&offset=0",
+                    topic,
+                ),
+            ],
+        )
+        .await;
+        let query = GetForumTopicsQuery {
+            q: Some("Kaguya-sama Episode".to_string()),
+            limit: 5,
+            ..Default::default()
+        };
+        let result = get_forum_topics(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data.len(), 1);
+
+        let query = GetForumTopicDetailQuery {
+            limit: 5,
+            offset: 0,
+        };
+        let topic = get_forum_topic_detail(result.data[0].id, &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(topic.data.posts[0].body, "How cute");
+    }
+}
@@ -0,0 +1,369 @@
+use super::cache::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+const INDEX_FILE: &str = "index.json";
+
+/// Whose cover an image is, anime and manga ids overlap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImageKind {
+    Anime,
+    Manga,
+}
+
+/// Which of the `Picture` urls an image came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageSize {
+    Large,
+    Medium,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageKey {
+    pub kind: ImageKind,
+    pub id: u64,
+    pub size: ImageSize,
+}
+
+impl ImageKey {
+    pub fn new(kind: ImageKind, id: u64, size: ImageSize) -> Self {
+        Self { kind, id, size }
+    }
+
+    /// File name without the extension, e.g. `anime-1-large`
+    fn stem(&self) -> String {
+        let kind = match self.kind {
+            ImageKind::Anime => "anime",
+            ImageKind::Manga => "manga",
+        };
+        let size = match self.size {
+            ImageSize::Large => "large",
+            ImageSize::Medium => "medium",
+        };
+        format!("{}-{}-{}", kind, self.id, size)
+    }
+
+    fn from_file_name(name: &str) -> Option<Self> {
+        let (stem, _extension) = name.rsplit_once('.')?;
+        let mut parts = stem.split('-');
+        let kind = match parts.next()? {
+            "anime" => ImageKind::Anime,
+            "manga" => ImageKind::Manga,
+            _ => return None,
+        };
+        let id = parts.next()?.parse().ok()?;
+        let size = match parts.next()? {
+            "large" => ImageSize::Large,
+            "medium" => ImageSize::Medium,
+            _ => return None,
+        };
+        parts.next().is_none().then_some(Self { kind, id, size })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Entry {
+    file: String,
+    bytes: u64,
+}
+
+/// Entries from the least to the most recently used
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(|e| e.bytes).sum()
+    }
+
+    fn position(&self, key: &ImageKey) -> Option<usize> {
+        let stem = key.stem();
+        self.entries
+            .iter()
+            .position(|e| e.file.rsplit_once('.').is_some_and(|(s, _)| s == stem))
+    }
+}
+
+/// What `mal cache stats` prints
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageCacheStats {
+    pub images: usize,
+    pub bytes: u64,
+    pub max_images: usize,
+    pub max_bytes: u64,
+}
+
+/// Cover images on disk, bounded by a number of images and a byte budget.
+///
+/// Files are named `<kind>-<id>-<size>.<ext>` and kept in the format MAL sent.
+/// `index.json` orders them by last use, the least recently used ones are
+/// evicted first. Images and the index are written to a temporary file and
+/// renamed, so a crash never leaves half a file behind.
+#[derive(Clone, Debug)]
+pub struct ImageCache {
+    dir: PathBuf,
+    max_images: usize,
+    max_bytes: u64,
+    index: Arc<Mutex<Index>>,
+}
+
+impl ImageCache {
+    /// Opens the cache in `dir`, dropping whatever the index and the files disagree on
+    pub fn open(dir: impl Into<PathBuf>, max_images: usize, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut index = read_index(&dir);
+        index.entries.retain(|e| dir.join(&e.file).is_file());
+
+        for file in fs::read_dir(&dir)?.flatten() {
+            let name = file.file_name().to_string_lossy().to_string();
+            if name == INDEX_FILE || index.entries.iter().any(|e| e.file == name) {
+                continue;
+            }
+            match (ImageKey::from_file_name(&name), file.metadata()) {
+                // e.g. the index was lost, the image is still good
+                (Some(_), Ok(metadata)) if metadata.is_file() => index.entries.insert(
+                    0,
+                    Entry {
+                        file: name,
+                        bytes: metadata.len(),
+                    },
+                ),
+                // `<id>.png` of older versions, leftover temporary files
+                _ => {
+                    debug!("removing {} from the image cache", name);
+                    fs::remove_file(file.path()).ok();
+                }
+            }
+        }
+
+        let cache = Self {
+            dir,
+            max_images,
+            max_bytes,
+            index: Arc::new(Mutex::new(index)),
+        };
+        cache.update(|_| {});
+        Ok(cache)
+    }
+
+    /// The cached image, marking it as just used. The order of use is only
+    /// saved with the next change, so a hit never writes the index.
+    pub fn get(&self, key: &ImageKey) -> Option<PathBuf> {
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        let position = index.position(key)?;
+        let entry = index.entries.remove(position);
+        let path = self.dir.join(&entry.file);
+        if !path.is_file() {
+            return None;
+        }
+        index.entries.push(entry);
+        Some(path)
+    }
+
+    /// Saves the image as it was downloaded, evicting the least recently used
+    /// ones when over the limits
+    pub fn put(&self, key: &ImageKey, bytes: &[u8]) -> io::Result<PathBuf> {
+        let format = image::guess_format(bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let extension = format.extensions_str().first().copied().unwrap_or("img");
+        let file = format!("{}.{}", key.stem(), extension);
+        let path = self.dir.join(&file);
+        write_atomic(&path, bytes)?;
+
+        self.update(|index| {
+            if let Some(old) = index.position(key).map(|i| index.entries.remove(i)) {
+                // the same cover in another format
+                if old.file != file {
+                    fs::remove_file(self.dir.join(&old.file)).ok();
+                }
+            }
+            index.entries.push(Entry {
+                file,
+                bytes: bytes.len() as u64,
+            });
+        });
+        Ok(path)
+    }
+
+    /// The stats of the cache in `dir` from its index alone. Unlike [`open`](Self::open)
+    /// it neither evicts nor removes files, a running app may be writing to it.
+    pub fn read_stats(dir: &Path, max_images: usize, max_bytes: u64) -> ImageCacheStats {
+        let index = read_index(dir);
+        ImageCacheStats {
+            images: index.entries.len(),
+            bytes: index.total_bytes(),
+            max_images,
+            max_bytes,
+        }
+    }
+
+    pub fn stats(&self) -> ImageCacheStats {
+        let index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        ImageCacheStats {
+            images: index.entries.len(),
+            bytes: index.total_bytes(),
+            max_images: self.max_images,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    /// Deletes every image, returns how many there were
+    pub fn clear(&self) -> io::Result<usize> {
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        let removed = index.entries.len();
+        for entry in index.entries.drain(..) {
+            match fs::remove_file(self.dir.join(&entry.file)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        save_index(&self.dir, &index)?;
+        Ok(removed)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Runs `change` on the index, then evicts and saves it
+    fn update<T>(&self, change: impl FnOnce(&mut Index) -> T) -> T {
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        let result = change(&mut index);
+        // the newest image stays, even when it is bigger than the budget
+        while index.entries.len() > 1
+            && (index.entries.len() > self.max_images || index.total_bytes() > self.max_bytes)
+        {
+            let evicted = index.entries.remove(0);
+            debug!("evicting {} from the image cache", evicted.file);
+            fs::remove_file(self.dir.join(&evicted.file)).ok();
+        }
+        if let Err(e) = save_index(&self.dir, &index) {
+            warn!("could not save the image cache index: {}", e);
+        }
+        result
+    }
+}
+
+/// An empty index when there is none yet or it can't be read
+fn read_index(dir: &Path) -> Index {
+    fs::read(dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(dir: &Path, index: &Index) -> io::Result<()> {
+    let content = serde_json::to_vec(index).map_err(io::Error::other)?;
+    write_atomic(&dir.join(INDEX_FILE), &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, ImageFormat};
+
+    fn cache(name: &str, max_images: usize, max_bytes: u64) -> ImageCache {
+        let dir = std::env::temp_dir().join(format!("mal-images-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ImageCache::open(dir, max_images, max_bytes).unwrap()
+    }
+
+    fn encoded(format: ImageFormat) -> Vec<u8> {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(4, 4)
+            .write_to(&mut bytes, format)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    fn anime(id: u64) -> ImageKey {
+        ImageKey::new(ImageKind::Anime, id, ImageSize::Large)
+    }
+
+    #[test]
+    fn test_keys_and_formats() {
+        let cache = cache("keys", 10, u64::MAX);
+        let jpeg = encoded(ImageFormat::Jpeg);
+        let anime_path = cache.put(&anime(1), &jpeg).unwrap();
+        let manga = ImageKey::new(ImageKind::Manga, 1, ImageSize::Large);
+        let manga_path = cache.put(&manga, &encoded(ImageFormat::Png)).unwrap();
+
+        // same id, different covers
+        assert_ne!(anime_path, manga_path);
+        assert_eq!(anime_path.file_name().unwrap(), "anime-1-large.jpg");
+        assert_eq!(fs::read(&anime_path).unwrap(), jpeg);
+        assert_eq!(manga_path.extension().unwrap(), "png");
+        assert!(cache.put(&anime(2), b"not an image").is_err());
+        assert_eq!(
+            ImageKey::from_file_name("manga-7-medium.webp"),
+            Some(ImageKey::new(ImageKind::Manga, 7, ImageSize::Medium))
+        );
+        assert_eq!(ImageKey::from_file_name("7.png"), None);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = cache("lru", 2, u64::MAX);
+        let png = encoded(ImageFormat::Png);
+        let first = cache.put(&anime(1), &png).unwrap();
+        let second = cache.put(&anime(2), &png).unwrap();
+        let saved = fs::read(cache.dir().join(INDEX_FILE)).unwrap();
+        // used again, so 2 is now the oldest
+        assert!(cache.get(&anime(1)).is_some());
+        assert_eq!(fs::read(cache.dir().join(INDEX_FILE)).unwrap(), saved);
+        cache.put(&anime(3), &png).unwrap();
+
+        assert!(first.exists());
+        assert!(!second.exists());
+        assert!(cache.get(&anime(2)).is_none());
+        assert_eq!(cache.stats().images, 2);
+    }
+
+    #[test]
+    fn test_byte_budget_and_reopen() {
+        let png = encoded(ImageFormat::Png);
+        let size = png.len() as u64;
+        let cache = cache("bytes", 10, 2 * size);
+        for id in 1..=3 {
+            cache.put(&anime(id), &png).unwrap();
+        }
+        assert_eq!(cache.stats().bytes, 2 * size);
+        assert!(cache.get(&anime(1)).is_none());
+
+        // a file of an older version is dropped, the index survives
+        fs::write(cache.dir().join("1.png"), &png).unwrap();
+        let reopened = ImageCache::open(cache.dir(), 10, 2 * size).unwrap();
+        assert!(!cache.dir().join("1.png").exists());
+        assert!(reopened.get(&anime(3)).is_some());
+        assert_eq!(reopened.clear().unwrap(), 2);
+        assert_eq!(reopened.stats().images, 0);
+    }
+
+    #[test]
+    fn test_read_stats_leaves_files_alone() {
+        let png = encoded(ImageFormat::Png);
+        let cache = cache("stats", 10, u64::MAX);
+        for id in 1..=3 {
+            cache.put(&anime(id), &png).unwrap();
+        }
+        // written by a running app
+        let tmp = cache.dir().join("anime-4-large.png.tmp");
+        fs::write(&tmp, &png).unwrap();
+        let saved = fs::read(cache.dir().join(INDEX_FILE)).unwrap();
+
+        let stats = ImageCache::read_stats(cache.dir(), 1, u64::MAX);
+        assert_eq!(stats.images, 3);
+        assert_eq!(stats.bytes, 3 * png.len() as u64);
+        assert_eq!(stats.max_images, 1);
+        assert!(tmp.exists());
+        assert!(cache.dir().join("anime-1-large.png").exists());
+        assert_eq!(fs::read(cache.dir().join(INDEX_FILE)).unwrap(), saved);
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+// follows the paused clock of tests
+use tokio::time::Instant;
+
+/// Which requests go first when the limiter is out of tokens
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Something the user is waiting on, e.g. opening a detail page
+    High = 0,
+    /// Lists, searches and rankings
+    Normal = 1,
+    /// Background work: pictures and prefetching
+    Low = 2,
+}
+
+/// Token bucket shared by every clone of an [`ApiClient`](super::ApiClient).
+///
+/// Holds up to `burst` tokens refilled at `per_second`; a request takes one token.
+/// Waiting requests of a higher priority are always served before lower ones,
+/// and a 429 from MAL pauses the whole bucket.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    burst: f64,
+    per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    paused_until: Option<Instant>,
+    /// number of waiting requests per priority
+    waiting: [usize; 3],
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst);
+        self.refilled_at = now;
+        if self.paused_until.is_some_and(|until| until <= now) {
+            self.paused_until = None;
+        }
+    }
+
+    /// Takes a token, or says how long to wait before asking again
+    fn try_take(&mut self, priority: Priority, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        let refill_wait = Duration::from_secs_f64((1.0 - self.tokens).max(0.0) / self.per_second);
+
+        if let Some(until) = self.paused_until {
+            return Err((until - now).max(refill_wait));
+        }
+        let higher_waiting = self.waiting[..priority as usize].iter().any(|&n| n > 0);
+        if self.tokens >= 1.0 && !higher_waiting {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        // when only blocked by higher priorities, check again once they had their turn
+        Err(refill_wait.max(Duration::from_secs_f64(1.0 / self.per_second)))
+    }
+}
+
+/// Counts a request as waiting until dropped, so cancelled requests don't block others
+struct Waiting<'a> {
+    limiter: &'a RateLimiter,
+    priority: Priority,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.limiter.bucket().waiting[self.priority as usize] -= 1;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            bucket: Arc::new(Mutex::new(Bucket {
+                burst,
+                per_second: per_second.max(0.01),
+                tokens: burst,
+                refilled_at: Instant::now(),
+                paused_until: None,
+                waiting: [0; 3],
+            })),
+        }
+    }
+
+    fn bucket(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Waits for a token
+    pub async fn acquire(&self, priority: Priority) {
+        let mut waiting = None;
+        loop {
+            let wait = match self.bucket().try_take(priority, Instant::now()) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            };
+            if waiting.is_none() {
+                self.bucket().waiting[priority as usize] += 1;
+                waiting = Some(Waiting {
+                    limiter: self,
+                    priority,
+                });
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Stops handing out tokens for `delay`, used when MAL answers 429
+    pub fn pause(&self, delay: Duration) {
+        let mut bucket = self.bucket();
+        let until = Instant::now() + delay;
+        bucket.paused_until = Some(bucket.paused_until.map_or(until, |u| u.max(until)));
+        bucket.tokens = 0.0;
+    }
+
+    /// True while requests are held back, either waiting for tokens or paused after a 429
+    pub fn is_throttled(&self) -> bool {
+        let mut bucket = self.bucket();
+        bucket.refill(Instant::now());
+        bucket.paused_until.is_some() || bucket.waiting.iter().any(|&n| n > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_burst_then_wait() {
+        let limiter = RateLimiter::new(2, 20.0);
+        let start = Instant::now();
+        limiter.acquire(Priority::Normal).await;
+        limiter.acquire(Priority::Normal).await;
+        assert!(start.elapsed() < Duration::from_millis(20));
+
+        limiter.acquire(Priority::Normal).await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert!(!limiter.is_throttled());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_high_priority_goes_first() {
+        let limiter = RateLimiter::new(1, 20.0);
+        limiter.acquire(Priority::Normal).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let spawn = |priority| {
+            let limiter = limiter.clone();
+            let order = Arc::clone(&order);
+            tokio::spawn(async move {
+                limiter.acquire(priority).await;
+                order.lock().unwrap().push(priority);
+            })
+        };
+        let low = spawn(Priority::Low);
+        tokio::task::yield_now().await;
+        assert!(limiter.is_throttled());
+        // the clock only moves when told to, low can't get the token in between
+        tokio::time::advance(Duration::from_millis(5)).await;
+        let high = spawn(Priority::High);
+        low.await.unwrap();
+        high.await.unwrap();
+
+        assert_eq!(*order.lock().unwrap(), vec![Priority::High, Priority::Low]);
+    }
+
+    #[test]
+    fn test_pause_throttles() {
+        let limiter = RateLimiter::new(5, 1.0);
+        limiter.pause(Duration::from_secs(30));
+        assert!(limiter.is_throttled());
+        let wait = limiter
+            .bucket()
+            .try_take(Priority::High, Instant::now())
+            .unwrap_err();
+        assert!(wait > Duration::from_secs(25));
+    }
+}
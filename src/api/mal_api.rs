@@ -0,0 +1,345 @@
+use super::model::*;
+use super::*;
+use crate::auth::{manager::TokenManager, AuthError, OAuth};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::future::Future;
+use tracing::warn;
+use url::Url;
+
+/// Everything the app asks from MyAnimeList.
+///
+/// [`HttpMalApi`] talks to the real API, [`FakeMalApi`](super::fake::FakeMalApi)
+/// serves fixtures from memory so the network layer can be tested offline.
+pub trait MalApi {
+    /// Makes sure the access token is still valid, refreshing it ahead of expiry
+    fn refresh_auth(&mut self) -> impl Future<Output = Result<(), AuthError>> + Send;
+
+    /// Starts logging in again, returning the url the user has to open
+    fn begin_login(&mut self) -> Result<Url, AuthError>;
+
+    /// Waits until the user logged in and uses the new token from then on
+    fn finish_login(&mut self) -> impl Future<Output = Result<(), AuthError>> + Send;
+
+    /// Uses the account of another profile from now on, `None` being the default one
+    fn switch_profile(&mut self, profile: Option<&str>) -> Result<(), AuthError>;
+
+    fn get_anime_list(
+        &self,
+        query: &GetAnimeListQuery,
+    ) -> impl Future<Output = Result<Page<Anime>, Error>> + Send;
+
+    fn get_anime_details(
+        &self,
+        anime_id: u64,
+        query: &GetAnimeDetailQuery,
+    ) -> impl Future<Output = Result<Anime, Error>> + Send;
+
+    fn get_anime_ranking(
+        &self,
+        query: &GetAnimeRankingQuery,
+    ) -> impl Future<Output = Result<Ranking<RankingAnimePair>, Error>> + Send;
+
+    fn get_seasonal_anime(
+        &self,
+        season: &AnimeSeason,
+        query: &GetSeasonalAnimeQuery,
+    ) -> impl Future<Output = Result<Page<Anime>, Error>> + Send;
+
+    fn get_suggested_anime(
+        &self,
+        query: &GetSuggestedAnimeQuery,
+    ) -> impl Future<Output = Result<Page<Anime>, Error>> + Send;
+
+    fn get_manga_list(
+        &self,
+        query: &GetMangaListQuery,
+    ) -> impl Future<Output = Result<Page<Manga>, Error>> + Send;
+
+    fn get_manga_details(
+        &self,
+        manga_id: u64,
+        query: &GetMangaDetailQuery,
+    ) -> impl Future<Output = Result<Manga, Error>> + Send;
+
+    fn get_manga_ranking(
+        &self,
+        query: &GetMangaRankingQuery,
+    ) -> impl Future<Output = Result<Ranking<RankingMangaPair>, Error>> + Send;
+
+    fn get_user_anime_list(
+        &self,
+        user: &str,
+        query: &GetUserAnimeListQuery,
+    ) -> impl Future<Output = Result<Page<Anime>, Error>> + Send;
+
+    fn update_anime_list_status(
+        &self,
+        anime_id: u64,
+        update: &UpdateUserAnimeListStatusQuery,
+    ) -> impl Future<Output = Result<UserAnimeListStatus, Error>> + Send;
+
+    fn delete_anime_from_list(
+        &self,
+        anime_id: u64,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+
+    fn get_user_manga_list(
+        &self,
+        user: &str,
+        query: &GetUserMangaListQuery,
+    ) -> impl Future<Output = Result<Page<Manga>, Error>> + Send;
+
+    fn update_manga_list_status(
+        &self,
+        manga_id: u64,
+        update: &UpdateUserMangaStatus,
+    ) -> impl Future<Output = Result<UserMangaListStatus, Error>> + Send;
+
+    fn delete_manga_from_list(
+        &self,
+        manga_id: u64,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+
+    fn get_my_user_information(
+        &self,
+        user: &str,
+        query: &GetUserInformationQuery,
+    ) -> impl Future<Output = Result<UserInfo, Error>> + Send;
+
+    fn get_forum_topics(
+        &self,
+        query: &GetForumTopicsQuery,
+    ) -> impl Future<Output = Result<PageableData<Vec<ForumTopic>>, Error>> + Send;
+
+    fn get_forum_topic_detail(
+        &self,
+        topic_id: u64,
+        query: &GetForumTopicDetailQuery,
+    ) -> impl Future<Output = Result<PageableData<ForumTopicDetail>, Error>> + Send;
+
+    /// See [`pagination::get_next_page`]
+    fn get_next_page<T>(
+        &self,
+        paging: &Paging,
+    ) -> impl Future<Output = Result<Option<PageableData<Vec<T>>>, Error>> + Send
+    where
+        T: DeserializeOwned + Clone + Debug + Send;
+
+    /// Downloads a picture
+    fn get_bytes(&self, url: &str) -> impl Future<Output = Result<Bytes, Error>> + Send;
+}
+
+/// [`MalApi`] over HTTP, holding the client and the user's token
+#[derive(Clone, Debug)]
+pub struct HttpMalApi {
+    client: ApiClient,
+    tokens: TokenManager,
+}
+
+impl HttpMalApi {
+    pub fn new(client: ApiClient, tokens: TokenManager) -> Self {
+        Self { client, tokens }
+    }
+
+    fn oauth(&self) -> &OAuth {
+        self.tokens.oauth()
+    }
+
+    /// Details and list edits are what the user waits on, pictures can wait
+    fn client(&self, priority: Priority) -> ApiClient {
+        self.client.with_priority(priority)
+    }
+}
+
+impl MalApi for HttpMalApi {
+    async fn refresh_auth(&mut self) -> Result<(), AuthError> {
+        // an expired token is fine when everything comes from the cache
+        if self.client.response_cache().is_some_and(|c| c.is_offline()) {
+            return Ok(());
+        }
+        self.tokens.ensure_fresh().await
+    }
+
+    fn begin_login(&mut self) -> Result<Url, AuthError> {
+        self.tokens.begin_login()
+    }
+
+    async fn finish_login(&mut self) -> Result<(), AuthError> {
+        self.tokens.finish_login().await
+    }
+
+    fn switch_profile(&mut self, profile: Option<&str>) -> Result<(), AuthError> {
+        // the profile is checked before its cache directory is made
+        self.tokens.switch_profile(profile)?;
+        // responses of the previous account must not be served to the new one
+        if let Some(cache) = self.client.response_cache() {
+            self.client = match cache.for_profile(profile) {
+                Ok(cache) => self.client.clone().with_cache(cache),
+                Err(e) => {
+                    warn!("could not open the response cache of the profile: {}", e);
+                    self.client.clone().without_cache()
+                }
+            };
+        }
+        Ok(())
+    }
+
+    async fn get_anime_list(&self, query: &GetAnimeListQuery) -> Result<Page<Anime>, Error> {
+        super::anime::get_anime_list(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_anime_details(
+        &self,
+        anime_id: u64,
+        query: &GetAnimeDetailQuery,
+    ) -> Result<Anime, Error> {
+        super::anime::get_anime_details(anime_id, query, &self.client(Priority::High), self.oauth())
+            .await
+    }
+
+    async fn get_anime_ranking(
+        &self,
+        query: &GetAnimeRankingQuery,
+    ) -> Result<Ranking<RankingAnimePair>, Error> {
+        super::anime::get_anime_ranking(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_seasonal_anime(
+        &self,
+        season: &AnimeSeason,
+        query: &GetSeasonalAnimeQuery,
+    ) -> Result<Page<Anime>, Error> {
+        super::anime::get_seasonal_anime(season, query, &self.client, self.oauth()).await
+    }
+
+    async fn get_suggested_anime(
+        &self,
+        query: &GetSuggestedAnimeQuery,
+    ) -> Result<Page<Anime>, Error> {
+        super::anime::get_suggested_anime(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_manga_list(&self, query: &GetMangaListQuery) -> Result<Page<Manga>, Error> {
+        super::manga::get_manga_list(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_manga_details(
+        &self,
+        manga_id: u64,
+        query: &GetMangaDetailQuery,
+    ) -> Result<Manga, Error> {
+        super::manga::get_manga_details(manga_id, query, &self.client(Priority::High), self.oauth())
+            .await
+    }
+
+    async fn get_manga_ranking(
+        &self,
+        query: &GetMangaRankingQuery,
+    ) -> Result<Ranking<RankingMangaPair>, Error> {
+        super::manga::get_manga_ranking(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_user_anime_list(
+        &self,
+        user: &str,
+        query: &GetUserAnimeListQuery,
+    ) -> Result<Page<Anime>, Error> {
+        super::animelist::get_user_anime_list(user, query, &self.client, self.oauth()).await
+    }
+
+    async fn update_anime_list_status(
+        &self,
+        anime_id: u64,
+        update: &UpdateUserAnimeListStatusQuery,
+    ) -> Result<UserAnimeListStatus, Error> {
+        super::animelist::update_anime_list_status(
+            anime_id,
+            update,
+            &self.client(Priority::High),
+            self.oauth(),
+        )
+        .await
+    }
+
+    async fn delete_anime_from_list(&self, anime_id: u64) -> Result<(), Error> {
+        super::animelist::delete_anime_from_list(
+            anime_id,
+            &self.client(Priority::High),
+            self.oauth(),
+        )
+        .await
+    }
+
+    async fn get_user_manga_list(
+        &self,
+        user: &str,
+        query: &GetUserMangaListQuery,
+    ) -> Result<Page<Manga>, Error> {
+        super::mangalist::get_user_manga_list(user, query, &self.client, self.oauth()).await
+    }
+
+    async fn update_manga_list_status(
+        &self,
+        manga_id: u64,
+        update: &UpdateUserMangaStatus,
+    ) -> Result<UserMangaListStatus, Error> {
+        super::mangalist::update_manga_list_status(
+            manga_id,
+            update,
+            &self.client(Priority::High),
+            self.oauth(),
+        )
+        .await
+    }
+
+    async fn delete_manga_from_list(&self, manga_id: u64) -> Result<(), Error> {
+        super::mangalist::delete_manga_from_list(
+            manga_id,
+            &self.client(Priority::High),
+            self.oauth(),
+        )
+        .await
+    }
+
+    async fn get_my_user_information(
+        &self,
+        user: &str,
+        query: &GetUserInformationQuery,
+    ) -> Result<UserInfo, Error> {
+        super::user::get_my_user_information(
+            user,
+            query,
+            &self.client(Priority::High),
+            self.oauth(),
+        )
+        .await
+    }
+
+    async fn get_forum_topics(
+        &self,
+        query: &GetForumTopicsQuery,
+    ) -> Result<PageableData<Vec<ForumTopic>>, Error> {
+        super::forum::get_forum_topics(query, &self.client, self.oauth()).await
+    }
+
+    async fn get_forum_topic_detail(
+        &self,
+        topic_id: u64,
+        query: &GetForumTopicDetailQuery,
+    ) -> Result<PageableData<ForumTopicDetail>, Error> {
+        super::forum::get_forum_topic_detail(topic_id, query, &self.client, self.oauth()).await
+    }
+
+    async fn get_next_page<T>(&self, paging: &Paging) -> Result<Option<PageableData<Vec<T>>>, Error>
+    where
+        T: DeserializeOwned + Clone + Debug + Send,
+    {
+        super::pagination::get_next_page(paging, &self.client, self.oauth()).await
+    }
+
+    async fn get_bytes(&self, url: &str) -> Result<Bytes, Error> {
+        self.client(Priority::Low).get_bytes(url).await
+    }
+}
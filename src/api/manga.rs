@@ -1,6 +1,6 @@
 use super::model::*;
-use super::Error;
-use super::{get, handle_response, API_URL};
+use super::{get, handle_response};
+use super::{ApiClient, Error};
 use crate::auth::OAuth;
 use serde::Serialize;
 
@@ -14,9 +14,14 @@ pub struct GetMangaListQuery {
     pub fields: Option<String>,
 }
 
-pub async fn get_manga_list(query: &GetMangaListQuery, auth: &OAuth) -> Result<Page<Manga>, Error> {
+pub async fn get_manga_list(
+    query: &GetMangaListQuery,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<Page<Manga>, Error> {
     let response = get(
-        &format! {"{}/manga?{}", API_URL, serde_urlencoded::to_string(query)?},
+        &format! {"{}/manga?{}", client.api_url(), serde_urlencoded::to_string(query)?},
+        client,
         auth,
     )
     .await?;
@@ -33,15 +38,17 @@ pub struct GetMangaDetailQuery {
 pub async fn get_manga_details(
     manga_id: u64,
     query: &GetMangaDetailQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Manga, Error> {
     let response = get(
         &format!(
             "{}/manga/{}?{}",
-            API_URL,
+            client.api_url(),
             manga_id,
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -60,14 +67,16 @@ pub struct GetMangaRankingQuery {
 
 pub async fn get_manga_ranking(
     query: &GetMangaRankingQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Ranking<RankingMangaPair>, Error> {
     let response = get(
         &format!(
             "{}/manga/ranking?{}",
-            API_URL,
+            client.api_url(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -75,65 +84,55 @@ pub async fn get_manga_ranking(
 }
 
 #[cfg(test)]
-pub mod tests {
+mod tests {
     use super::*;
-
-    pub async fn get_manga<T: ToString>(q: T, auth: &OAuth) -> Result<Manga, Error> {
-        let manga_query = GetMangaListQuery {
-            q: q.to_string(),
-            limit: 4,
-            offset: 0,
-            nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-        };
-        let manga_list = get_manga_list(&manga_query, auth).await.unwrap();
-        let manga = manga_list.data.first().unwrap().node.clone();
-        Ok(manga)
-    }
+    use crate::api::tests::*;
+    use reqwest::Method;
 
     #[tokio::test]
     async fn test_get_manga_list() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetMangaListQuery {
-            q: "Kaguya-Sama Wa Kokurasetai".to_string(),
+            q: "Berserk".to_string(),
             limit: 2,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
-        let result = get_manga_list(&query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("manga", &query);
+        let body = list_body(&manga_fixtures()[..1]);
+        let (client, auth) = replay("manga-list", &[(Method::GET, &url, &body)]).await;
+        let result = get_manga_list(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data.len(), 1);
+        assert_eq!(result.data[0].node.id, 2);
     }
 
     #[tokio::test]
     async fn test_get_manga_details() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetMangaDetailQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::detail().into()),
             nsfw: false,
         };
-
-        let manga = get_manga("Kaguya-Sama Wa Kokurasetai", &auth)
-            .await
-            .unwrap();
-        let result = get_manga_details(manga.id, &query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert_eq!(result.title, manga.title);
+        let url = api_url("manga/13", &query);
+        let body = manga_fixtures()[1].to_string();
+        let (client, auth) = replay("manga-details", &[(Method::GET, &url, &body)]).await;
+        let result = get_manga_details(13, &query, &client, &auth).await.unwrap();
+        assert_eq!(result.title, "One Piece");
     }
 
     #[tokio::test]
     async fn test_get_manga_ranking() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetMangaRankingQuery {
             ranking_type: MangaRankingType::All,
             limit: 100,
             offset: 0,
             nsfw: false,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
         };
-        let result = get_manga_ranking(&query, &auth).await.unwrap();
-        println!("{:#?}", result);
-        assert!(!result.data.is_empty());
+        let url = api_url("manga/ranking", &query);
+        let body = ranking_body(&manga_fixtures());
+        let (client, auth) = replay("manga-ranking", &[(Method::GET, &url, &body)]).await;
+        let result = get_manga_ranking(&query, &client, &auth).await.unwrap();
+        assert_eq!(result.data.len(), 2);
+        assert_eq!(result.data[0].ranking.rank, 1);
     }
 }
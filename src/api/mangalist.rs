@@ -1,10 +1,10 @@
 use super::model::*;
-use super::Error;
-use super::{delete, get, handle_response, patch, API_URL};
+use super::{delete, error_from_response, get, handle_response, patch};
+use super::{ApiClient, Error};
 use crate::auth::OAuth;
 use serde::Serialize;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct UpdateUserMangaStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<UserReadStatus>,
@@ -26,15 +26,28 @@ pub struct UpdateUserMangaStatus {
     pub tags: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comments: Option<String>,
+    /// `Some(None)` clears the date
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_date_update"
+    )]
+    pub start_date: Option<Option<DateWrapper>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_date_update"
+    )]
+    pub finish_date: Option<Option<DateWrapper>>,
 }
 
 pub async fn update_manga_list_status(
     manga_id: u64,
     update: &UpdateUserMangaStatus,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<UserMangaListStatus, Error> {
     let response = patch(
-        &format!("{}/manga/{}/my_list_status", API_URL, manga_id),
+        &format!("{}/manga/{}/my_list_status", client.api_url(), manga_id),
+        client,
         auth,
         update,
     )
@@ -42,16 +55,21 @@ pub async fn update_manga_list_status(
     handle_response(&response)
 }
 
-pub async fn delete_manga_from_list(manga_id: u64, auth: &OAuth) -> Result<(), Error> {
+pub async fn delete_manga_from_list(
+    manga_id: u64,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<(), Error> {
     let response = delete(
-        &format!("{}/manga/{}/my_list_status", API_URL, manga_id),
+        &format!("{}/manga/{}/my_list_status", client.api_url(), manga_id),
+        client,
         auth,
     )
     .await?;
     if response.status.is_success() {
         Ok(())
     } else {
-        Err(Error::HttpError(response.status))
+        Err(error_from_response(&response))
     }
 }
 
@@ -62,7 +80,7 @@ pub struct GetUserMangaListQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<UserReadStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub sort: Option<SortStyle>,
+    pub sort: Option<MangaSortStyle>,
     pub limit: u64,
     pub offset: u64,
     pub nsfw: bool,
@@ -71,15 +89,17 @@ pub struct GetUserMangaListQuery {
 pub async fn get_user_manga_list<U: ToString>(
     user: U,
     query: &GetUserMangaListQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<Page<Manga>, Error> {
     let response = get(
         &format!(
             "{}/users/{}/mangalist?{}",
-            API_URL,
+            client.api_url(),
             user.to_string(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -89,18 +109,19 @@ pub async fn get_user_manga_list<U: ToString>(
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::api::manga::tests::*;
+    use crate::api::tests::*;
+    use reqwest::Method;
 
     #[tokio::test]
     async fn test_delete_manga_from_list() {
-        let auth = crate::auth::tests::get_auth();
-        let manga = get_manga("Grand Blue", &auth).await.unwrap();
-        delete_manga_from_list(manga.id, &auth).await.unwrap();
+        let url = "https://api.myanimelist.net/v2/manga/2/my_list_status";
+        let (client, auth) = replay("manga-delete", &[(Method::DELETE, url, "[]")]).await;
+        delete_manga_from_list(2, &client, &auth).await.unwrap();
+        assert!(delete_manga_from_list(13, &client, &auth).await.is_err());
     }
 
     #[tokio::test]
     async fn test_update_manga_list() {
-        let auth = crate::auth::tests::get_auth();
         let query = UpdateUserMangaStatus {
             status: Some(UserReadStatus::Reading),
             is_rereading: None,
@@ -112,30 +133,41 @@ mod test {
             reread_value: None,
             tags: None,
             comments: None,
+            start_date: None,
+            finish_date: None,
         };
-        let manga = get_manga("Grand Blue", &auth).await.unwrap();
-        let result = update_manga_list_status(manga.id, &query, &auth)
+        let url = "https://api.myanimelist.net/v2/manga/13/my_list_status";
+        let body = r#"{
+            "status": "reading",
+            "score": 9,
+            "num_volumes_read": 0,
+            "num_chapters_read": 62,
+            "is_rereading": false,
+            "updated_at": "2024-03-02T12:00:00+00:00"
+        }"#;
+        let (client, auth) = replay("manga-update", &[(Method::PATCH, url, body)]).await;
+        let result = update_manga_list_status(13, &query, &client, &auth)
             .await
             .unwrap();
-        println!("{:#?}", result);
         assert_eq!(result.num_chapters_read, 62);
     }
 
     #[tokio::test]
     async fn test_get_user_manga_list() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetUserMangaListQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::list().into()),
             status: None,
             sort: None,
             limit: 100,
             offset: 0,
             nsfw: true,
         };
-        let result = get_user_manga_list("@me", &query, &auth).await.unwrap();
-
-        print!("{:#?}", result);
-
-        assert!(!result.data.is_empty());
+        let url = api_url("users/@me/mangalist", &query);
+        let body = list_body(&manga_fixtures()[..1]);
+        let (client, auth) = replay("manga-user-list", &[(Method::GET, &url, &body)]).await;
+        let result = get_user_manga_list("@me", &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(result.data[0].node.title, "Berserk");
     }
 }
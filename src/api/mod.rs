@@ -1,52 +1,181 @@
 #![allow(ambiguous_glob_reexports)]
+/// On-disk cache of api responses
+pub mod cache;
+pub use cache::ResponseCache;
+/// Shared HTTP client
+pub mod client;
+pub use client::ApiClient;
+/// Cover images on disk, least recently used first out
+pub mod image_cache;
+pub use image_cache::{ImageCache, ImageCacheStats, ImageKey, ImageKind, ImageSize};
 /// Anime API endpoints
 pub mod anime;
 pub use anime::*;
 /// User animelist API endpoints
 pub mod animelist;
 pub use animelist::*;
+/// Client side rate limiting
+pub mod limiter;
+pub use limiter::{Priority, RateLimiter};
+/// In-memory MalApi for offline tests
+#[cfg(test)]
+pub mod fake;
+#[cfg(test)]
+pub use fake::{FakeMalApi, Mutation};
+/// Forum API endpoints
+pub mod forum;
+pub use forum::*;
 /// manga API endpoints
 pub mod manga;
 pub use manga::*;
+/// The MalApi trait and its HTTP implementation
+pub mod mal_api;
+pub use mal_api::{HttpMalApi, MalApi};
 /// User mangalist API endpoints
 pub mod mangalist;
 pub use mangalist::*;
+/// Recording responses and serving them back from a local server
+pub mod replay;
+pub use replay::{Recorder, ReplayServer};
 /// API objects
 pub mod model;
+/// Paging through list endpoints
+pub mod pagination;
 /// User API endpoints
 pub mod user;
 pub use user::*;
 
-use crate::auth::OAuth;
-use reqwest::{ClientBuilder, RequestBuilder};
+use crate::auth::{AuthError, OAuth};
+use reqwest::RequestBuilder;
 use serde::{Deserialize, Serialize};
 
+/// Default api url, see `NetworkConfig::api_url`
 pub const API_URL: &str = "https://api.myanimelist.net/v2";
 
 #[derive(Debug)]
 pub enum Error {
+    /// No access token is available
     NoAuth,
+    /// The access token could not be refreshed
+    Auth(AuthError),
+    /// MAL rejected the access token (401)
+    Unauthorized {
+        error: String,
+        message: Option<String>,
+    },
+    /// MAL answered with an error status and an `{error, message}` body
+    Api {
+        status: reqwest::StatusCode,
+        error: String,
+        message: Option<String>,
+    },
+    /// MAL answered with an error status and no usable body
+    HttpError(reqwest::StatusCode),
+    /// MAL kept answering 429 after every retry
+    RateLimited,
+    /// Offline mode and nothing cached for this request
+    Offline,
     TimedOut,
-    Unknown,
+    /// Connection or transport failure
+    Network(reqwest::Error),
+    /// TLS handshake or certificate failure
+    Tls(reqwest::Error),
+    /// The response body could not be decoded
+    Decode(String),
     NoBody,
-    ParseError(serde_json::Error),
     QuerySerializeError(serde_urlencoded::ser::Error),
-    HttpError(reqwest::StatusCode),
+}
+
+impl Error {
+    /// True when the request failed because of missing or rejected credentials
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Error::NoAuth | Error::Auth(_) | Error::Unauthorized { .. }
+        )
+    }
+
+    /// True when the session is gone and the user has to log in again
+    pub fn needs_login(&self) -> bool {
+        match self {
+            Error::NoAuth | Error::Unauthorized { .. } => true,
+            Error::Auth(e) => e.needs_login(),
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network(e) | Error::Tls(e) => Some(e),
+            Error::QuerySerializeError(e) => Some(e),
+            Error::Auth(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::NoAuth => write!(f, "Not logged in, please reload the app"),
+            Error::Auth(e) => write!(f, "Could not refresh the session: {}", e),
+            Error::Unauthorized { error, message } => match message {
+                Some(message) => write!(f, "Authentication failed ({}): {}", error, message),
+                None => write!(f, "Authentication failed ({})", error),
+            },
+            Error::Api {
+                status,
+                error,
+                message,
+            } => match message {
+                Some(message) => write!(f, "{} ({}, {})", message, error, status),
+                None => write!(f, "{} ({})", error, status),
+            },
+            Error::HttpError(status) => write!(f, "Http Error: {}", status),
+            Error::RateLimited => write!(
+                f,
+                "MyAnimeList is throttling requests, please wait a moment and try again"
+            ),
+            Error::Offline => write!(f, "Not available offline, open it once while online"),
+            Error::TimedOut => write!(f, "Connection timed out, please try again"),
+            Error::Network(e) => write!(
+                f,
+                "Network error, check your internet connection ({})",
+                root_cause(e)
+            ),
+            Error::Tls(e) => write!(f, "TLS error: {}", root_cause(e)),
+            Error::Decode(e) => write!(f, "Could not read the response: {}", e),
+            Error::NoBody => write!(f, "The response has no body"),
+            Error::QuerySerializeError(e) => write!(f, "Query Serialize Error: {}", e),
+        }
+    }
+}
+
+impl From<AuthError> for Error {
+    fn from(e: AuthError) -> Self {
+        Error::Auth(e)
+    }
 }
 
 impl From<reqwest::Error> for Error {
     fn from(e: reqwest::Error) -> Self {
         if e.is_timeout() {
             Error::TimedOut
+        } else if e.is_decode() {
+            Error::Decode(e.to_string())
+        } else if is_tls_error(&e) {
+            Error::Tls(e)
         } else {
-            Error::Unknown
+            Error::Network(e)
         }
     }
 }
 
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
-        Error::ParseError(e)
+        Error::Decode(e.to_string())
     }
 }
 
@@ -56,6 +185,64 @@ impl From<serde_urlencoded::ser::Error> for Error {
     }
 }
 
+/// FNV-1a, stable across builds so it can name files on disk
+pub(crate) fn stable_hash(value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// reqwest doesn't expose TLS failures directly, so look for a rustls error in the
+/// chain of a failed connection
+fn is_tls_error(e: &reqwest::Error) -> bool {
+    if !e.is_connect() {
+        return false;
+    }
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(err) = source {
+        if is_rustls_error(err) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// rustls errors arrive wrapped in `io::Error`s, whose `source` skips what they wrap
+fn is_rustls_error(err: &(dyn std::error::Error + 'static)) -> bool {
+    if err.is::<rustls::Error>() {
+        return true;
+    }
+    match err
+        .downcast_ref::<std::io::Error>()
+        .and_then(|io| io.get_ref())
+    {
+        Some(wrapped) => is_rustls_error(wrapped),
+        None => false,
+    }
+}
+
+/// The innermost error of the chain, what actually went wrong
+fn root_cause<'a>(
+    e: &'a (dyn std::error::Error + 'static),
+) -> &'a (dyn std::error::Error + 'static) {
+    let mut cause = e;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause
+}
+
+/// Error body sent by MAL along with non-2xx responses
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    error: Option<String>,
+    message: Option<String>,
+}
+
 #[derive(Debug)]
 pub(crate) struct ApiResponse {
     status: reqwest::StatusCode,
@@ -70,6 +257,7 @@ pub(crate) fn apply_headers(req: RequestBuilder, auth: &OAuth) -> ApiResult<Requ
         None => return Err(Error::NoAuth),
     };
     Ok(req
+        .header(reqwest::header::USER_AGENT, auth.user_agent())
         .header(reqwest::header::ACCEPT, "application/json")
         .header(
             reqwest::header::CONTENT_TYPE,
@@ -81,48 +269,53 @@ pub(crate) fn apply_headers(req: RequestBuilder, auth: &OAuth) -> ApiResult<Requ
         ))
 }
 
-pub(crate) async fn send(request: RequestBuilder, auth: &OAuth) -> ApiResult<ApiResponse> {
+pub(crate) async fn send(
+    request: RequestBuilder,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> ApiResult<ApiResponse> {
     let request = apply_headers(request, auth)?;
-    let response = request.send().await?;
-    let status = response.status();
+    let (status, body) = client.fetch(request).await?;
     Ok(ApiResponse {
         status,
-        body: (response.text().await).ok(),
+        body: String::from_utf8(body.to_vec()).ok(),
     })
 }
 
-pub(crate) async fn get<U: reqwest::IntoUrl>(url: U, auth: &OAuth) -> ApiResult<ApiResponse> {
-    let request = ClientBuilder::new()
-        .user_agent(auth.user_agent())
-        .build()?
-        .get(url);
-    send(request, auth).await
+pub(crate) async fn get<U: reqwest::IntoUrl>(
+    url: U,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> ApiResult<ApiResponse> {
+    let request = client.http().get(url);
+    send(request, client, auth).await
 }
 
 pub(crate) async fn patch<U: reqwest::IntoUrl, B: Serialize>(
     url: U,
+    client: &ApiClient,
     auth: &OAuth,
     body: &B,
 ) -> ApiResult<ApiResponse> {
-    let request = ClientBuilder::new()
-        .user_agent(auth.user_agent())
-        .build()?
+    let request = client
+        .http()
         .patch(url)
         .body(serde_urlencoded::to_string(body)?);
-    send(request, auth).await
+    send(request, client, auth).await
 }
 
-pub(crate) async fn delete<U: reqwest::IntoUrl>(url: U, auth: &OAuth) -> ApiResult<ApiResponse> {
-    let request = ClientBuilder::new()
-        .user_agent(auth.user_agent())
-        .build()?
-        .delete(url);
-    send(request, auth).await
+pub(crate) async fn delete<U: reqwest::IntoUrl>(
+    url: U,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> ApiResult<ApiResponse> {
+    let request = client.http().delete(url);
+    send(request, client, auth).await
 }
 
 pub(crate) fn handle_response<'a, D: Deserialize<'a>>(res: &'a ApiResponse) -> ApiResult<D> {
     if !res.status.is_success() {
-        return Err(Error::HttpError(res.status));
+        return Err(error_from_response(res));
     }
     if let Some(body) = &res.body {
         Ok(serde_json::from_str::<D>(body)?)
@@ -130,3 +323,181 @@ pub(crate) fn handle_response<'a, D: Deserialize<'a>>(res: &'a ApiResponse) -> A
         Err(Error::NoBody)
     }
 }
+
+/// Turns a non-2xx response into the most specific `Error` the body allows
+pub(crate) fn error_from_response(res: &ApiResponse) -> Error {
+    if res.status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Error::RateLimited;
+    }
+    let body = res
+        .body
+        .as_deref()
+        .and_then(|body| serde_json::from_str::<ApiErrorBody>(body).ok());
+
+    let (error, message) = match body {
+        Some(ApiErrorBody {
+            error: Some(error),
+            message,
+        }) => (error, message.filter(|m| !m.is_empty())),
+        _ => return Error::HttpError(res.status),
+    };
+
+    if res.status == reqwest::StatusCode::UNAUTHORIZED {
+        Error::Unauthorized { error, message }
+    } else {
+        Error::Api {
+            status: res.status,
+            error,
+            message,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::config::app_config::NetworkConfig;
+    use reqwest::{Method, StatusCode};
+    use serde_json::{json, Value};
+
+    /// A client answered by a replay server with `fixtures`: method, url and body as MAL sends them
+    pub async fn replay(name: &str, fixtures: &[(Method, &str, &str)]) -> (ApiClient, OAuth) {
+        let dir = std::env::temp_dir().join(format!("mal-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = Recorder::new(&dir).unwrap();
+        for (method, url, body) in fixtures {
+            let url = reqwest::Url::parse(url).unwrap();
+            recorder.record(method, &url, StatusCode::OK, body.as_bytes());
+        }
+        let server = ReplayServer::bind(&dir, 0).await.unwrap();
+        let config = server.network_config(&NetworkConfig::default());
+        tokio::spawn(server.run());
+        let auth = ReplayServer::login(&config).await.unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        (ApiClient::new(&config).unwrap(), auth)
+    }
+
+    /// `path` on MAL's api with `query` as the client sends it
+    pub fn api_url<Q: Serialize>(path: &str, query: &Q) -> String {
+        format!(
+            "{}/{}?{}",
+            API_URL,
+            path,
+            serde_urlencoded::to_string(query).unwrap()
+        )
+    }
+
+    /// The anime served by the fake api, as MAL sends them
+    pub fn anime_fixtures() -> Vec<Value> {
+        serde_json::from_str(include_str!("fixtures/anime.json")).unwrap()
+    }
+
+    /// The manga served by the fake api, as MAL sends them
+    pub fn manga_fixtures() -> Vec<Value> {
+        serde_json::from_str(include_str!("fixtures/manga.json")).unwrap()
+    }
+
+    /// `items` as the only page of a list
+    pub fn list_body(items: &[Value]) -> String {
+        let nodes: Vec<Value> = items.iter().map(|node| json!({ "node": node })).collect();
+        json!({ "data": nodes, "paging": {} }).to_string()
+    }
+
+    /// `items` as the only page of a ranking, best first
+    pub fn ranking_body(items: &[Value]) -> String {
+        let nodes: Vec<Value> = items
+            .iter()
+            .enumerate()
+            .map(|(i, node)| json!({ "node": node, "ranking": { "rank": i + 1 } }))
+            .collect();
+        json!({ "data": nodes, "paging": {} }).to_string()
+    }
+
+    fn response(status: StatusCode, body: Option<&str>) -> ApiResponse {
+        ApiResponse {
+            status,
+            body: body.map(|b| b.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_api_error_from_body() {
+        let res = response(
+            StatusCode::NOT_FOUND,
+            Some(r#"{"error":"not_found","message":""}"#),
+        );
+        match error_from_response(&res) {
+            Error::Api {
+                status,
+                error,
+                message,
+            } => {
+                assert_eq!(status, StatusCode::NOT_FOUND);
+                assert_eq!(error, "not_found");
+                assert_eq!(message, None);
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_unauthorized_from_body() {
+        let res = response(
+            StatusCode::UNAUTHORIZED,
+            Some(r#"{"error":"invalid_token","message":"token is invalid"}"#),
+        );
+        let error = error_from_response(&res);
+        assert!(error.is_auth_error());
+        assert_eq!(
+            error.to_string(),
+            "Authentication failed (invalid_token): token is invalid"
+        );
+    }
+
+    #[test]
+    fn test_http_error_without_body() {
+        let res = response(StatusCode::BAD_GATEWAY, Some("<html>bad gateway</html>"));
+        assert!(matches!(
+            error_from_response(&res),
+            Error::HttpError(StatusCode::BAD_GATEWAY)
+        ));
+    }
+
+    #[test]
+    fn test_too_many_requests_is_rate_limited() {
+        let res = response(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(r#"{"error":"too_many_requests"}"#),
+        );
+        assert!(matches!(error_from_response(&res), Error::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn test_refused_connection_is_network_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let e = reqwest::get(format!("http://{}", addr)).await.unwrap_err();
+        let error = Error::from(e);
+        assert!(matches!(error, Error::Network(_)));
+        // the cause is shown, not only that something failed
+        assert!(error
+            .to_string()
+            .starts_with("Network error, check your internet connection ("));
+        assert!(!error.to_string().contains("error sending request"));
+    }
+
+    #[tokio::test]
+    async fn test_bad_handshake_is_tls_error() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // plain http where a tls server hello is expected
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").await;
+        });
+        let e = reqwest::get(format!("https://{}", addr)).await.unwrap_err();
+        assert!(matches!(Error::from(e), Error::Tls(_)));
+    }
+}
@@ -11,53 +11,93 @@ pub struct AnimeSeason {
     pub season: Season,
 }
 
-#[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
-#[strum(serialize_all = "snake_case")]
-pub enum AnimeField {
-    Id,
-    Titel,
-    MainPicture,
-    AlternativeTitles,
-    StartDate,
-    EndDate,
-    Synopsis,
-    Mean,
-    Rank,
-    Popularity,
-    NumListUsers,
-    NumScoringUsers,
-    NSFW,
-    CreatedAt,
-    UpdatedAt,
-    MediaType,
-    Status,
-    MyListStatus,
-    NumEpisodes,
-    Broadcast,
-    Source,
-    AverageEpisodeDuration,
-    Rating,
-    Pictures,
-    Background,
-    RelatedAnime,
-    RelatedManga,
-    Recommendations,
-    Studios,
-    Statistics,
-
-    NumVolumes,
-    NumChapters,
-    Authors,
-
-    Name,
-    Picture,
-    Gender,
-    Birthday,
-    Location,
-    JoinedAt,
-    AnimeStatistics,
-    TimeZone,
-    IsSupporter,
+impl AnimeSeason {
+    /// Oldest season MAL has entries for
+    pub const FIRST_YEAR: u64 = 1917;
+
+    pub fn new(year: u64, season: Season) -> Self {
+        Self { year, season }
+    }
+
+    /// The season airing today
+    pub fn current() -> Self {
+        Self::from_date(DateWrapper::today().date)
+    }
+
+    pub fn from_date(date: time::Date) -> Self {
+        Self {
+            year: date.year().max(0) as u64,
+            season: Season::from_month(date.month() as u8),
+        }
+    }
+
+    /// Unknown seasons count as the first one of their year
+    fn position(&self) -> u64 {
+        self.year * 4 + self.season.index().unwrap_or(0) as u64
+    }
+
+    fn from_position(position: u64) -> Self {
+        Self {
+            year: position / 4,
+            season: Season::from_index((position % 4) as usize),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        Self::from_position(self.position() + 1)
+    }
+
+    pub fn prev(&self) -> Self {
+        Self::from_position(self.position().saturating_sub(1))
+    }
+
+    /// Every season from `self` to `end`, both included, oldest first
+    pub fn range_to(&self, end: &AnimeSeason) -> impl Iterator<Item = AnimeSeason> {
+        (self.position()..=end.position()).map(Self::from_position)
+    }
+
+    /// Keeps the season between the first one on MAL and `latest`
+    pub fn clamp_to_available(self, latest: &AnimeSeason) -> Self {
+        let first = Self::new(Self::FIRST_YEAR, Season::Winter);
+        if self < first {
+            first
+        } else if &self > latest {
+            latest.clone()
+        } else {
+            self
+        }
+    }
+
+    /// What seasons are compared by, unknown ones sorting by name after the first season
+    fn key(&self) -> (u64, &str) {
+        (self.position(), self.season.name())
+    }
+}
+
+impl PartialEq for AnimeSeason {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for AnimeSeason {}
+
+impl Ord for AnimeSeason {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for AnimeSeason {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for AnimeSeason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.season.name(), self.year)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
@@ -190,6 +230,12 @@ pub struct StartSeason {
     pub year: u16,
 }
 
+impl From<&StartSeason> for AnimeSeason {
+    fn from(start: &StartSeason) -> Self {
+        AnimeSeason::new(start.year as u64, start.season.clone())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 pub enum RelationType {
@@ -284,3 +330,72 @@ impl Anime {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month};
+
+    #[test]
+    fn test_season_from_date() {
+        let date = |month, day| Date::from_calendar_date(2024, month, day).unwrap();
+        assert_eq!(
+            AnimeSeason::from_date(date(Month::March, 31)),
+            AnimeSeason::new(2024, Season::Winter)
+        );
+        assert_eq!(
+            AnimeSeason::from_date(date(Month::April, 1)),
+            AnimeSeason::new(2024, Season::Spring)
+        );
+        assert_eq!(
+            AnimeSeason::from_date(date(Month::December, 25)),
+            AnimeSeason::new(2024, Season::Fall)
+        );
+    }
+
+    #[test]
+    fn test_season_arithmetic() {
+        let fall = AnimeSeason::new(2023, Season::Fall);
+        let winter = AnimeSeason::new(2024, Season::Winter);
+        assert_eq!(fall.next(), winter);
+        assert_eq!(winter.prev(), fall);
+        assert!(fall < winter);
+        assert_eq!(winter.to_string(), "Winter 2024");
+
+        let seasons: Vec<String> = fall
+            .range_to(&AnimeSeason::new(2024, Season::Spring))
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(seasons, vec!["Fall 2023", "Winter 2024", "Spring 2024"]);
+        assert_eq!(winter.range_to(&fall).count(), 0);
+
+        // equal exactly when neither sorts first
+        let unknown = AnimeSeason::new(2024, Season::Other("Winter".to_string()));
+        assert_eq!(unknown.cmp(&winter), std::cmp::Ordering::Equal);
+        assert_eq!(unknown, winter);
+        let other = AnimeSeason::new(2024, Season::Other("Monsoon".to_string()));
+        assert_ne!(other, winter);
+        assert!(other < winter);
+    }
+
+    #[test]
+    fn test_clamp_to_available() {
+        let latest = AnimeSeason::new(2025, Season::Spring);
+        let too_old = AnimeSeason::new(1917, Season::Winter).prev();
+        assert_eq!(
+            too_old.clamp_to_available(&latest),
+            AnimeSeason::new(1917, Season::Winter)
+        );
+        assert_eq!(latest.next().clamp_to_available(&latest), latest);
+    }
+
+    #[test]
+    fn test_sort_styles_follow_the_list_kind() {
+        let anime: &str = SortStyle::AnimeTitle.into();
+        assert_eq!(anime, "anime_title");
+        let manga: &str = MangaSortStyle::MangaStartDate.into();
+        assert_eq!(manga, "manga_start_date");
+        let score: &str = MangaSortStyle::ListScore.into();
+        assert_eq!(score, "list_score");
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+use strum_macros::{EnumString, IntoStaticStr};
+
+/// A field that can be asked for with the `fields` query parameter.
+///
+/// Covers the anime and manga fields as well as the sub fields of
+/// `my_list_status` and `authors`, which are requested with the nested
+/// `field{sub,fields}` syntax (see [`FieldSet::nested`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum Field {
+    Id,
+    Title,
+    MainPicture,
+    AlternativeTitles,
+    StartDate,
+    EndDate,
+    Synopsis,
+    Mean,
+    Rank,
+    Popularity,
+    NumListUsers,
+    NumScoringUsers,
+    Nsfw,
+    Genres,
+    CreatedAt,
+    UpdatedAt,
+    MediaType,
+    Status,
+    MyListStatus,
+    NumEpisodes,
+    StartSeason,
+    Broadcast,
+    Source,
+    AverageEpisodeDuration,
+    Rating,
+    Pictures,
+    Background,
+    RelatedAnime,
+    RelatedManga,
+    Recommendations,
+    Studios,
+    Statistics,
+    NumVolumes,
+    NumChapters,
+    Authors,
+
+    // my_list_status
+    Score,
+    NumEpisodesWatched,
+    NumVolumesRead,
+    NumChaptersRead,
+    IsRewatching,
+    IsRereading,
+    FinishDate,
+    Priority,
+    NumTimesRewatched,
+    NumTimesReread,
+    RewatchValue,
+    RereadValue,
+    Tags,
+    Comments,
+
+    // authors
+    FirstName,
+    LastName,
+}
+
+/// The fields shown on every media card
+const CARD_FIELDS: [Field; 13] = [
+    Field::Id,
+    Field::Title,
+    Field::MainPicture,
+    Field::AlternativeTitles,
+    Field::StartDate,
+    Field::Mean,
+    Field::NumListUsers,
+    Field::MediaType,
+    Field::Status,
+    Field::MyListStatus,
+    Field::NumEpisodes,
+    Field::NumVolumes,
+    Field::NumChapters,
+];
+
+/// Builds the value of the `fields` query parameter.
+///
+/// ```
+/// use mal::api::model::{Field, FieldSet};
+///
+/// let fields = FieldSet::new()
+///     .with(Field::Title)
+///     .nested(Field::MyListStatus, [Field::StartDate, Field::FinishDate]);
+/// assert_eq!(fields.to_string(), "title,my_list_status{start_date,finish_date}");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldSet {
+    fields: Vec<(Field, Option<FieldSet>)>,
+}
+
+impl FieldSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field, doing nothing when it is already in the set
+    pub fn with(mut self, field: Field) -> Self {
+        if !self.contains(field) {
+            self.fields.push((field, None));
+        }
+        self
+    }
+
+    pub fn with_all(self, fields: impl IntoIterator<Item = Field>) -> Self {
+        fields.into_iter().fold(self, Self::with)
+    }
+
+    /// Adds a field and the sub fields to request inside of it,
+    /// merging them with any sub fields already set
+    pub fn nested(mut self, field: Field, sub_fields: impl IntoIterator<Item = Field>) -> Self {
+        let index = match self.fields.iter().position(|(f, _)| *f == field) {
+            Some(index) => index,
+            None => {
+                self.fields.push((field, None));
+                self.fields.len() - 1
+            }
+        };
+        let (_, nested) = &mut self.fields[index];
+        *nested = Some(nested.take().unwrap_or_default().with_all(sub_fields));
+        self
+    }
+
+    pub fn contains(&self, field: Field) -> bool {
+        self.fields.iter().any(|(f, _)| *f == field)
+    }
+
+    /// Grid cards: search results, seasonal, suggestions and rankings
+    pub fn card() -> Self {
+        Self::new().with_all(CARD_FIELDS)
+    }
+
+    /// The top three bar
+    pub fn top_three() -> Self {
+        Self::new().with_all([
+            Field::Id,
+            Field::Title,
+            Field::AlternativeTitles,
+            Field::StartDate,
+            Field::Mean,
+            Field::NumListUsers,
+            Field::MediaType,
+            Field::MyListStatus,
+            Field::NumEpisodes,
+            Field::NumChapters,
+        ])
+    }
+
+    /// The user's anime and manga lists, cards plus the list entry,
+    /// and the genres to filter by
+    pub fn list() -> Self {
+        Self::card().with(Field::Genres).nested(
+            Field::MyListStatus,
+            [
+                Field::Status,
+                Field::Score,
+                Field::NumEpisodesWatched,
+                Field::NumVolumesRead,
+                Field::NumChaptersRead,
+                Field::StartDate,
+                Field::FinishDate,
+                Field::Tags,
+                Field::UpdatedAt,
+            ],
+        )
+    }
+
+    /// The details page, everything MAL has for a title
+    pub fn detail() -> Self {
+        Self::card()
+            .with_all([
+                Field::EndDate,
+                Field::Synopsis,
+                Field::Rank,
+                Field::Popularity,
+                Field::NumScoringUsers,
+                Field::Nsfw,
+                Field::Genres,
+                Field::CreatedAt,
+                Field::UpdatedAt,
+                Field::StartSeason,
+                Field::Broadcast,
+                Field::Source,
+                Field::AverageEpisodeDuration,
+                Field::Rating,
+                Field::Pictures,
+                Field::Background,
+                Field::RelatedAnime,
+                Field::RelatedManga,
+                Field::Recommendations,
+                Field::Studios,
+                Field::Statistics,
+            ])
+            .nested(Field::Authors, [Field::FirstName, Field::LastName])
+            .nested(
+                Field::MyListStatus,
+                [
+                    Field::Status,
+                    Field::Score,
+                    Field::NumEpisodesWatched,
+                    Field::NumVolumesRead,
+                    Field::NumChaptersRead,
+                    Field::IsRewatching,
+                    Field::IsRereading,
+                    Field::StartDate,
+                    Field::FinishDate,
+                    Field::Priority,
+                    Field::NumTimesRewatched,
+                    Field::NumTimesReread,
+                    Field::RewatchValue,
+                    Field::RereadValue,
+                    Field::Tags,
+                    Field::Comments,
+                    Field::UpdatedAt,
+                ],
+            )
+    }
+}
+
+impl fmt::Display for FieldSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, nested)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(field.into())?;
+            if let Some(nested) = nested {
+                write!(f, "{{{}}}", nested)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<FieldSet> for String {
+    fn from(fields: FieldSet) -> Self {
+        fields.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_fields_merge() {
+        let fields = FieldSet::new()
+            .with(Field::Id)
+            .nested(Field::MyListStatus, [Field::Status])
+            .with(Field::MyListStatus)
+            .nested(Field::MyListStatus, [Field::Status, Field::FinishDate]);
+        assert_eq!(fields.to_string(), "id,my_list_status{status,finish_date}");
+    }
+
+    #[test]
+    fn test_presets() {
+        let detail = FieldSet::detail().to_string();
+        assert!(detail.contains("created_at"));
+        assert!(detail.contains("my_list_status{status,score,"));
+        assert!(detail.contains("authors{first_name,last_name}"));
+
+        let card = FieldSet::card();
+        assert!(!card.contains(Field::Statistics));
+        assert!(!card.contains(Field::Recommendations));
+        assert!(FieldSet::list().to_string().contains("tags"));
+    }
+}
@@ -0,0 +1,81 @@
+use super::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumBoards {
+    pub categories: Vec<ForumCategory>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumCategory {
+    pub title: String,
+    pub boards: Vec<ForumBoard>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumBoard {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub subboards: Vec<ForumSubboard>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumSubboard {
+    pub id: u64,
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumUser {
+    pub id: u64,
+    pub name: String,
+    /// avatar url, only sent with posts (the spelling is MAL's)
+    pub forum_avator: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumTopic {
+    pub id: u64,
+    pub title: String,
+    pub created_at: DateTimeWrapper,
+    pub created_by: ForumUser,
+    pub number_of_posts: u64,
+    pub last_post_created_at: DateTimeWrapper,
+    pub last_post_created_by: ForumUser,
+    pub is_locked: bool,
+}
+
+/// One page of a topic's posts
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumTopicDetail {
+    pub title: String,
+    pub posts: Vec<ForumPost>,
+    pub poll: Option<ForumPoll>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumPost {
+    pub id: u64,
+    pub number: u64,
+    pub created_at: DateTimeWrapper,
+    pub created_by: ForumUser,
+    /// BBCode formatted
+    pub body: String,
+    pub signature: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumPoll {
+    pub id: u64,
+    pub question: String,
+    pub close: bool,
+    pub options: Vec<ForumPollOption>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumPollOption {
+    pub id: u64,
+    pub text: String,
+    pub votes: u64,
+}
@@ -28,6 +28,17 @@ pub struct RankingMangaPair {
     pub ranking: RankingInfo,
 }
 
+#[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum MangaSortStyle {
+    ListScore,
+    ListUpdatedAt,
+    MangaTitle,
+    MangaStartDate,
+    MangaId,
+    Other(String),
+}
+
 #[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 pub enum MangaMediaType {
@@ -1,6 +1,12 @@
 /// Anime related structs
 pub mod anime;
 pub use anime::*;
+/// Field selection for requests
+pub mod fields;
+pub use fields::*;
+/// Forum related structs
+pub mod forum;
+pub use forum::*;
 /// Manga related structs
 pub mod manga;
 pub use manga::*;
@@ -9,6 +15,7 @@ pub mod user;
 use serde::de::{self, Visitor};
 pub use user::*;
 
+use chrono::Datelike;
 use core::fmt;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Debug;
@@ -29,7 +36,7 @@ use time::{
 pub type Page<T> = PageableData<Vec<Node<T>>>;
 pub type Ranking<T> = PageableData<Vec<T>>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RankingType {
     AnimeRankingType(AnimeRankingType),
     MangaRankingType(MangaRankingType),
@@ -53,6 +60,18 @@ pub struct PageableData<D: Clone + Debug> {
     pub paging: Paging,
 }
 
+impl<T: Clone + Debug> PageableData<Vec<T>> {
+    /// Adds the items of the following page, taking over its `next` link
+    pub fn append(&mut self, page: PageableData<Vec<T>>) {
+        self.data.extend(page.data);
+        self.paging.next = page.paging.next;
+    }
+
+    pub fn has_next_page(&self) -> bool {
+        self.paging.next.is_some()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Node<N: Clone + std::fmt::Debug> {
     pub node: N,
@@ -82,7 +101,7 @@ pub struct Genre {
     pub name: String,
 }
 
-#[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr, Display)]
+#[derive(Clone, Debug, PartialEq, Eq, EnumString, IntoStaticStr, Display)]
 #[strum(serialize_all = "snake_case")]
 pub enum Season {
     Winter,
@@ -92,11 +111,42 @@ pub enum Season {
     Other(String),
 }
 
+impl Season {
+    /// The four seasons in the order they happen in a year
+    pub const ALL: [Season; 4] = [Season::Winter, Season::Spring, Season::Summer, Season::Fall];
+
+    /// Position in the year, `None` for unknown seasons
+    pub fn index(&self) -> Option<usize> {
+        Season::ALL.iter().position(|s| s == self)
+    }
+
+    /// Wraps around, so 4 is winter again
+    pub fn from_index(index: usize) -> Self {
+        Season::ALL[index % 4].clone()
+    }
+
+    /// MAL's seasons: winter is January to March, spring April to June and so on
+    pub fn from_month(month: u8) -> Self {
+        Season::from_index((month.clamp(1, 12) as usize - 1) / 3)
+    }
+
+    /// Capitalized name, e.g. `Winter`
+    pub fn name(&self) -> &str {
+        match self {
+            Season::Winter => "Winter",
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Fall => "Fall",
+            Season::Other(s) => s,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TimeWrapper {
     pub time: Time,
 }
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DateWrapper {
     pub date: Date,
 }
@@ -176,19 +226,9 @@ where
     deserializer.deserialize_any(StringOrIntVisitor)
 }
 
-pub const ALL_ANIME_AND_MANGA_FIELDS: &str = "id,title,main_picture,alternative_titles,start_date,end_date,synopsis,mean,rank,popularity,num_list_users,num_scoring_users,nsfw,genres,create_at,updated_at,media_type,status,my_list_status,num_episodes,broadcast,source,average_episode_duration,rating,pictures,background,related_anime,related_manga,recommendations,studios,statistics,num_volumes,num_chapters,authors,start_season";
 pub const ALL_USER_FIELDS: &str =
     "id,name,picture,gender,birthday,location,joined_at,anime_statistics,time_zone,is_supporter";
 
-/// Utility to convert a list of fields to a string (in the format expected by query objects)
-pub fn fields_to_string(fields: &[AnimeField]) -> String {
-    fields
-        .iter()
-        .map(|field| field.into())
-        .collect::<Vec<&str>>()
-        .join(",")
-}
-
 #[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
 #[strum(serialize_all = "snake_case")]
 pub enum NSFW {
@@ -273,6 +313,7 @@ impl_serialize_deserialize!(
     MangaRankingType,
     Season,
     SortStyle,
+    MangaSortStyle,
     UserReadStatus,
     MangaMediaType,
     MangaStatus,
@@ -392,6 +433,15 @@ impl<'de> Deserialize<'de> for TimeWrapper {
     }
 }
 
+impl DateWrapper {
+    /// The current date on the user's clock
+    pub fn today() -> Self {
+        let today = chrono::Local::now().date_naive();
+        let date = Date::from_ordinal_date(today.year(), today.ordinal() as u16).unwrap();
+        DateWrapper { date }
+    }
+}
+
 impl Serialize for DateWrapper {
     fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
     where
@@ -403,6 +453,20 @@ impl Serialize for DateWrapper {
     }
 }
 
+/// Sends `Some(None)` as an empty value, which makes MAL clear the date
+pub fn serialize_date_update<S>(
+    update: &Option<Option<DateWrapper>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match update {
+        Some(Some(date)) => date.serialize(serializer),
+        _ => serializer.serialize_str(""),
+    }
+}
+
 impl<'de> Deserialize<'de> for DateWrapper {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
     where
@@ -486,7 +550,14 @@ impl Serialize for DateTimeWrapper {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.datetime.format(&FORMAT).unwrap())
+        use serde::ser::Error;
+        // MAL sends UTC times, the format needs the offset back
+        let datetime = self
+            .datetime
+            .assume_utc()
+            .format(&FORMAT)
+            .map_err(S::Error::custom)?;
+        serializer.serialize_str(&datetime)
     }
 }
 
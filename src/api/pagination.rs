@@ -0,0 +1,218 @@
+use super::model::*;
+use super::{
+    get, handle_response, ApiClient, Error, GetAnimeListQuery, GetAnimeRankingQuery,
+    GetForumTopicDetailQuery, GetForumTopicsQuery, GetMangaListQuery, GetMangaRankingQuery,
+    GetSeasonalAnimeQuery, GetSuggestedAnimeQuery, GetUserAnimeListQuery, GetUserMangaListQuery,
+};
+use crate::auth::OAuth;
+use futures::{stream, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+use std::future::Future;
+
+/// A list query that can be moved through the results with `limit`/`offset`
+pub trait PagedQuery: Clone {
+    fn limit(&self) -> u64;
+    fn offset(&self) -> u64;
+    fn set_limit(&mut self, limit: u64);
+    fn set_offset(&mut self, offset: u64);
+}
+
+macro_rules! impl_paged_query {
+    (for $( $t:ty ),+) => {
+        $(
+        impl PagedQuery for $t {
+            fn limit(&self) -> u64 {
+                self.limit
+            }
+            fn offset(&self) -> u64 {
+                self.offset
+            }
+            fn set_limit(&mut self, limit: u64) {
+                self.limit = limit;
+            }
+            fn set_offset(&mut self, offset: u64) {
+                self.offset = offset;
+            }
+        }
+        )*
+    };
+}
+
+impl_paged_query!(
+    for
+    GetAnimeListQuery,
+    GetMangaListQuery,
+    GetAnimeRankingQuery,
+    GetMangaRankingQuery,
+    GetSeasonalAnimeQuery,
+    GetSuggestedAnimeQuery,
+    GetUserAnimeListQuery,
+    GetUserMangaListQuery,
+    GetForumTopicsQuery,
+    GetForumTopicDetailQuery
+);
+
+/// Lazily fetches consecutive pages of a list endpoint.
+///
+/// Starts at the query's offset and keeps requesting until MAL stops sending a
+/// `paging.next` link, a page comes back empty or `max_items` items were yielded.
+/// `fetch` is the endpoint call, e.g.
+/// `|q| async move { api::get_anime_ranking(&q, client, auth).await }`.
+pub fn pages<Q, T, F, Fut>(
+    query: Q,
+    max_items: Option<usize>,
+    mut fetch: F,
+) -> impl Stream<Item = Result<PageableData<Vec<T>>, Error>>
+where
+    Q: PagedQuery,
+    T: Clone + Debug,
+    F: FnMut(Q) -> Fut,
+    Fut: Future<Output = Result<PageableData<Vec<T>>, Error>>,
+{
+    stream::try_unfold(Some((query, 0usize)), move |state| {
+        let request = state.and_then(|(mut query, fetched)| {
+            let remaining = max_items.map(|max| max.saturating_sub(fetched));
+            if remaining == Some(0) {
+                return None;
+            }
+            // don't ask for more than the cap allows
+            if let Some(remaining) = remaining {
+                query.set_limit(query.limit().min(remaining as u64));
+            }
+            let fut = fetch(query.clone());
+            Some((query, fetched, fut))
+        });
+
+        async move {
+            let Some((mut query, fetched, fut)) = request else {
+                return Ok(None);
+            };
+            let page = fut.await?;
+            if page.data.is_empty() {
+                return Ok(None);
+            }
+
+            let fetched = fetched + page.data.len();
+            let next = if page.paging.next.is_some() {
+                query.set_offset(query.offset() + page.data.len() as u64);
+                Some((query, fetched))
+            } else {
+                None
+            };
+            Ok(Some((page, next)))
+        }
+    })
+}
+
+/// Same as [`pages`] but yields the entries one by one
+pub fn items<Q, T, F, Fut>(
+    query: Q,
+    max_items: Option<usize>,
+    fetch: F,
+) -> impl Stream<Item = Result<T, Error>>
+where
+    Q: PagedQuery,
+    T: Clone + Debug,
+    F: FnMut(Q) -> Fut,
+    Fut: Future<Output = Result<PageableData<Vec<T>>, Error>>,
+{
+    pages(query, max_items, fetch)
+        .map_ok(|page| stream::iter(page.data.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+/// Fetches the page behind `paging.next`, or `None` when this was the last page
+pub async fn get_next_page<T>(
+    paging: &Paging,
+    client: &ApiClient,
+    auth: &OAuth,
+) -> Result<Option<PageableData<Vec<T>>>, Error>
+where
+    T: DeserializeOwned + Clone + Debug,
+{
+    let Some(next) = &paging.next else {
+        return Ok(None);
+    };
+    let response = get(client.rebase_url(next), client, auth).await?;
+    handle_response(&response).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+
+    fn query() -> GetSuggestedAnimeQuery {
+        GetSuggestedAnimeQuery {
+            limit: 4,
+            offset: 0,
+            nsfw: false,
+            fields: None,
+        }
+    }
+
+    /// serves `0..total` in pages of `query.limit`
+    async fn fake_fetch(
+        query: GetSuggestedAnimeQuery,
+        total: u64,
+    ) -> Result<PageableData<Vec<u64>>, Error> {
+        let end = (query.offset + query.limit).min(total);
+        Ok(PageableData {
+            data: (query.offset..end).collect(),
+            paging: Paging {
+                previous: None,
+                next: (end < total).then(|| "next".to_string()),
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_next() {
+        let pages: Vec<_> = pages(query(), None, |q| fake_fetch(q, 10))
+            .try_collect()
+            .await
+            .unwrap();
+        let sizes: Vec<usize> = pages.iter().map(|p| p.data.len()).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_items_respect_max() {
+        let items: Vec<u64> = items(query(), Some(6), |q| fake_fetch(q, 10))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_pages_stop_on_error() {
+        let mut calls = 0;
+        let result: Result<Vec<_>, Error> = pages(query(), None, |q| {
+            calls += 1;
+            let fail = calls == 2;
+            async move {
+                if fail {
+                    Err(Error::NoBody)
+                } else {
+                    fake_fetch(q, 10).await
+                }
+            }
+        })
+        .try_collect()
+        .await;
+        assert!(matches!(result, Err(Error::NoBody)));
+    }
+
+    #[tokio::test]
+    async fn test_append_takes_next_link() {
+        let mut first = fake_fetch(query(), 6).await.unwrap();
+        let mut rest = query();
+        rest.set_offset(4);
+        first.append(fake_fetch(rest, 6).await.unwrap());
+
+        assert_eq!(first.data, vec![0, 1, 2, 3, 4, 5]);
+        assert!(!first.has_next_page());
+    }
+}
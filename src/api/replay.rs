@@ -0,0 +1,368 @@
+use super::{stable_hash, API_URL};
+use crate::auth::{AuthError, OAuth, AUTHORIZE_URL, TOKEN_URL};
+use crate::config::app_config::NetworkConfig;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
+use url::Url;
+
+/// Hosts whose links are always pointed at the replay server
+const REWRITTEN_HOSTS: [&str; 2] = ["api.myanimelist.net", "cdn.myanimelist.net"];
+
+/// Token handed out by the replay server when no token response was recorded
+const REPLAY_TOKEN: &str = r#"{"token_type":"Bearer","expires_in":2678400,"access_token":"replay","refresh_token":"replay"}"#;
+
+/// One recorded exchange, stored as `<id>.json` next to its body in `<id>.body`
+#[derive(Debug, Deserialize, Serialize)]
+struct FixtureMeta {
+    method: String,
+    url: String,
+    status: u16,
+}
+
+/// Writes every response the [`ApiClient`](super::ApiClient) receives to a fixtures
+/// directory. Tokens never pass through the client, so they are never recorded.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Saves a response, replacing an earlier recording of the same request
+    pub fn record(&self, method: &Method, url: &Url, status: StatusCode, body: &[u8]) {
+        let id = fixture_id(method.as_str(), url);
+        let meta = FixtureMeta {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: status.as_u16(),
+        };
+        let result = serde_json::to_vec_pretty(&meta)
+            .map_err(io::Error::other)
+            .and_then(|meta| {
+                fs::write(self.dir.join(format!("{}.body", id)), body)?;
+                fs::write(self.dir.join(format!("{}.json", id)), meta)
+            });
+        match result {
+            Ok(()) => debug!("recorded {} {}", method, url),
+            Err(e) => warn!("could not record {} {}: {}", method, url, e),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Fixture {
+    status: StatusCode,
+    body: Vec<u8>,
+}
+
+/// Serves a directory written by [`Recorder`] over plain HTTP on localhost.
+///
+/// Requests are matched on method, host, path and query: a recording of
+/// `GET https://api.myanimelist.net/v2/anime/1` is served at
+/// `http://127.0.0.1:<port>/api.myanimelist.net/v2/anime/1`. Links to recorded
+/// hosts inside the bodies are rewritten the same way, so paging and picture
+/// downloads stay on the server. Token requests that weren't recorded get a
+/// dummy token, anything else unknown gets a MAL style 404.
+pub struct ReplayServer {
+    listener: TcpListener,
+    fixtures: Arc<HashMap<String, Fixture>>,
+}
+
+impl ReplayServer {
+    /// Loads the fixtures and listens on `127.0.0.1:port` (0 picks a free port)
+    pub async fn bind(dir: &Path, port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).await?;
+        let base_url = format!("http://{}", listener.local_addr()?);
+        let fixtures = load_fixtures(dir, &base_url)?;
+        Ok(Self {
+            listener,
+            fixtures: Arc::new(fixtures),
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        match self.listener.local_addr() {
+            Ok(addr) => format!("http://{}", addr),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// Number of recorded responses being served
+    pub fn len(&self) -> usize {
+        self.fixtures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixtures.is_empty()
+    }
+
+    /// `url` as it is reached through this server
+    pub fn local_url(&self, url: &str) -> String {
+        rewrite_url(url, &self.base_url())
+    }
+
+    /// `config` with every url pointed at this server
+    pub fn network_config(&self, config: &NetworkConfig) -> NetworkConfig {
+        NetworkConfig {
+            api_url: self.local_url(API_URL),
+            authorize_url: self.local_url(AUTHORIZE_URL),
+            token_url: self.local_url(TOKEN_URL),
+            ..config.clone()
+        }
+    }
+
+    /// Goes through the token exchange against the server `network` points at
+    pub async fn login(network: &NetworkConfig) -> Result<OAuth, AuthError> {
+        let mut auth = OAuth::new("mal-cli", "replay", None, "127.0.0.1")
+            .with_endpoints(network.auth_endpoints());
+        auth.auth_code = Some("replay".to_string());
+        auth.get_access_token_async().await?;
+        Ok(auth)
+    }
+
+    /// Answers requests until the process exits
+    pub async fn run(self) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, _)) => {
+                    let fixtures = Arc::clone(&self.fixtures);
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream, &fixtures).await {
+                            warn!("replay server: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("replay server: {}", e),
+            }
+        }
+    }
+}
+
+/// Stable file name for a request
+fn fixture_id(method: &str, url: &Url) -> String {
+    format!("{:016x}", stable_hash(&fixture_key(method, url)))
+}
+
+/// `GET host[:port]/path?query`, what the replay server matches requests on
+fn fixture_key(method: &str, url: &Url) -> String {
+    let mut key = format!("{} {}", method, url.host_str().unwrap_or_default());
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{}", port));
+    }
+    key.push_str(url.path());
+    if let Some(query) = url.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
+}
+
+fn rewrite_url(url: &str, base_url: &str) -> String {
+    match url.split_once("://") {
+        Some((_, rest)) => format!("{}/{}", base_url, rest),
+        None => url.to_string(),
+    }
+}
+
+fn load_fixtures(dir: &Path, base_url: &str) -> io::Result<HashMap<String, Fixture>> {
+    let mut recorded = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let meta: FixtureMeta =
+            serde_json::from_slice(&fs::read(&path)?).map_err(io::Error::other)?;
+        let body = fs::read(path.with_extension("body"))?;
+        let (Ok(url), Ok(status)) = (Url::parse(&meta.url), StatusCode::from_u16(meta.status))
+        else {
+            warn!("skipping invalid fixture {}", path.display());
+            continue;
+        };
+        recorded.push((meta.method, url, status, body));
+    }
+
+    let mut hosts: BTreeSet<String> = REWRITTEN_HOSTS.iter().map(|h| h.to_string()).collect();
+    hosts.extend(
+        recorded
+            .iter()
+            .filter_map(|(_, url, _, _)| url.host_str().map(str::to_string)),
+    );
+
+    Ok(recorded
+        .into_iter()
+        .map(|(method, url, status, body)| {
+            let fixture = Fixture {
+                status,
+                body: rewrite_links(body, &hosts, base_url),
+            };
+            (fixture_key(&method, &url), fixture)
+        })
+        .collect())
+}
+
+/// Points `https://<host>/` links in text bodies at the replay server
+fn rewrite_links(body: Vec<u8>, hosts: &BTreeSet<String>, base_url: &str) -> Vec<u8> {
+    let Ok(mut text) = String::from_utf8(body.clone()) else {
+        return body;
+    };
+    for host in hosts {
+        for scheme in ["https://", "http://"] {
+            text = text.replace(
+                &format!("{}{}/", scheme, host),
+                &format!("{}/{}/", base_url, host),
+            );
+        }
+    }
+    text.into_bytes()
+}
+
+async fn serve(mut stream: TcpStream, fixtures: &HashMap<String, Fixture>) -> io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let (method, target, body_start, content_length) = loop {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..read]);
+
+        let mut headers = [httparse::EMPTY_HEADER; 32];
+        let mut parsed = httparse::Request::new(&mut headers);
+        match parsed.parse(&request) {
+            Ok(httparse::Status::Complete(len)) => {
+                let content_length = parsed
+                    .headers
+                    .iter()
+                    .find(|h| h.name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|h| std::str::from_utf8(h.value).ok()?.trim().parse().ok())
+                    .unwrap_or(0usize);
+                break (
+                    parsed.method.unwrap_or("GET").to_string(),
+                    parsed.path.unwrap_or("/").to_string(),
+                    len,
+                    content_length,
+                );
+            }
+            Ok(httparse::Status::Partial) => continue,
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    };
+    // the body isn't matched on, but has to be read before answering
+    let mut remaining = (body_start + content_length).saturating_sub(request.len());
+    while remaining > 0 {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        remaining = remaining.saturating_sub(read);
+    }
+
+    let key = format!("{} {}", method, target.trim_start_matches('/'));
+    let (status, body) = match fixtures.get(&key) {
+        Some(fixture) => (fixture.status, fixture.body.clone()),
+        None if method == "POST" && target.ends_with("/oauth2/token") => {
+            (StatusCode::OK, REPLAY_TOKEN.as_bytes().to_vec())
+        }
+        None => {
+            warn!("no recording for {}", key);
+            let body = serde_json::json!({
+                "error": "not_found",
+                "message": format!("no recording for {}", key),
+            });
+            (StatusCode::NOT_FOUND, body.to_string().into_bytes())
+        }
+    };
+    debug!("replay {} -> {}", key, status);
+
+    let content_type = match body.first() {
+        Some(b'{') | Some(b'[') => "application/json",
+        _ => "application/octet-stream",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or(""),
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiClient;
+
+    fn fixtures_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mal-replay-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fixture_key() {
+        let url = Url::parse("https://api.myanimelist.net/v2/anime/1?fields=id,title").unwrap();
+        assert_eq!(
+            fixture_key("GET", &url),
+            "GET api.myanimelist.net/v2/anime/1?fields=id,title"
+        );
+        assert_eq!(fixture_id("GET", &url), fixture_id("GET", &url));
+        assert_ne!(fixture_id("GET", &url), fixture_id("PATCH", &url));
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = fixtures_dir("roundtrip");
+        let recorder = Recorder::new(&dir).unwrap();
+        let url = Url::parse("https://api.myanimelist.net/v2/anime/1?fields=id").unwrap();
+        let body = r#"{"id":1,"paging":{"next":"https://api.myanimelist.net/v2/anime?offset=1"}}"#;
+        recorder.record(&Method::GET, &url, StatusCode::OK, body.as_bytes());
+
+        let server = ReplayServer::bind(&dir, 0).await.unwrap();
+        assert_eq!(server.len(), 1);
+        let config = server.network_config(&NetworkConfig::default());
+        let base_url = server.base_url();
+        tokio::spawn(server.run());
+
+        let auth = ReplayServer::login(&config).await.unwrap();
+        assert_eq!(auth.token().unwrap().token.access_token, "replay");
+
+        let client = ApiClient::new(&config).unwrap();
+        let request = client
+            .http()
+            .get(format!("{}/anime/1?fields=id", client.api_url()));
+        let (status, body) = client.fetch(request).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(&format!(
+            "{}/api.myanimelist.net/v2/anime?offset=1",
+            base_url
+        )));
+
+        let missing = client
+            .get_bytes(&format!("{}/anime/2", client.api_url()))
+            .await;
+        assert!(
+            matches!(missing, Err(super::super::Error::HttpError(s)) if s == StatusCode::NOT_FOUND)
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,6 +1,6 @@
 use super::model::*;
-use super::Error;
-use super::{get, handle_response, API_URL};
+use super::{get, handle_response};
+use super::{ApiClient, Error};
 use crate::auth::OAuth;
 use serde::Serialize;
 
@@ -12,15 +12,17 @@ pub struct GetUserInformationQuery {
 pub async fn get_my_user_information<U: ToString>(
     user: U,
     query: &GetUserInformationQuery,
+    client: &ApiClient,
     auth: &OAuth,
 ) -> Result<UserInfo, Error> {
     let response = get(
         &format!(
             "{}/users/{}?{}",
-            API_URL,
+            client.api_url(),
             user.to_string(),
             serde_urlencoded::to_string(query)?
         ),
+        client,
         auth,
     )
     .await?;
@@ -30,14 +32,21 @@ pub async fn get_my_user_information<U: ToString>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::tests::*;
+    use reqwest::Method;
 
     #[tokio::test]
     async fn test_get_user_information() {
-        let auth = crate::auth::tests::get_auth();
         let query = GetUserInformationQuery {
             fields: Some(ALL_USER_FIELDS.to_string()),
         };
-        let result = get_my_user_information("@me", &query, &auth).await.unwrap();
-        println!("{:#?}", result);
+        let url = api_url("users/@me", &query);
+        let body = include_str!("fixtures/user.json");
+        let (client, auth) = replay("user", &[(Method::GET, &url, body)]).await;
+        let result = get_my_user_information("@me", &query, &client, &auth)
+            .await
+            .unwrap();
+        assert_eq!(result.name, "fake_user");
+        assert_eq!(result.anime_statistics.unwrap().num_items, 3);
     }
 }
@@ -1,9 +1,12 @@
 #![allow(clippy::new_without_default)]
 #![allow(clippy::large_enum_variant)]
-use crate::api::{self, model::*};
+use crate::api::{self, model::*, ImageKind};
 use crate::config::app_config::AppConfig;
-use crate::network::IoEvent;
-use chrono::Datelike;
+use crate::network::{CancelToken, IoEvent, Request};
+use crate::user_list::{
+    get_manga_status_string, get_status_string, has_read_status, has_watch_status, ListFilter,
+    ListSort,
+};
 use image::{DynamicImage, ImageError};
 use ratatui::layout::Rect;
 use ratatui::style::Style;
@@ -13,7 +16,7 @@ use ratatui_image::picker::Picker;
 use ratatui_image::protocol::StatefulProtocol;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
-use std::sync::mpsc::Sender;
+use tokio::sync::mpsc::UnboundedSender;
 use tui_logger::{TuiLoggerWidget, TuiWidgetState};
 
 use strum_macros::IntoStaticStr;
@@ -28,13 +31,13 @@ const DEFAULT_ROUTE: Route = Route {
 
 pub const DISPLAY_RAWS_NUMBER: usize = 5;
 
-pub const SEASONS: [&str; 4] = ["Winter", "Spring", "Summer", "Fall"];
-
 pub const DISPLAY_COLUMN_NUMBER: usize = 3;
 
+pub const FORUM_POSTS_PER_PAGE: u64 = 20;
+
 pub const ANIME_OPTIONS: [&str; 3] = ["Seasonal", "Ranking", "Suggested"];
 
-pub const USER_OPTIONS: [&str; 3] = ["Stats", "AnimeList", "MangaList"];
+pub const USER_OPTIONS: [&str; 4] = ["Stats", "AnimeList", "MangaList", "Profiles"];
 
 pub const GENERAL_OPTIONS: [&str; 3] = ["Help", "About", "Quit"];
 
@@ -50,9 +53,9 @@ pub const USER_READ_STATUS: [&str; 5] =
 
 pub const ANIME_OPTIONS_RANGE: std::ops::Range<usize> = 0..3;
 
-pub const USER_OPTIONS_RANGE: std::ops::Range<usize> = 3..6;
+pub const USER_OPTIONS_RANGE: std::ops::Range<usize> = 3..7;
 
-pub const GENERAL_OPTIONS_RANGE: std::ops::Range<usize> = 6..9;
+pub const GENERAL_OPTIONS_RANGE: std::ops::Range<usize> = 7..10;
 
 pub const RATING_OPTIONS: [&str; 11] = [
     "None",
@@ -119,7 +122,22 @@ pub enum ActiveDisplayBlock {
     Empty,
     AnimeDetails,
     MangaDetails,
+    ForumTopics,
+    ForumTopic,
+    Login,
+    Profiles,
 }
+
+/// Where logging in again from inside the app stands
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoginState {
+    /// the session is gone, waiting for the user to start logging in
+    Expired,
+    /// waiting for the browser to come back, with the url to open
+    Waiting(String),
+    Failed(String),
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum SelectedSearchTab {
     Anime,
@@ -170,6 +188,15 @@ pub struct Library {
     pub saved_manga: ScrollablePages<Page<Manga>>,
 }
 
+impl Library {
+    /// Position of the selection inside the side menu of `range`, `None` when it is elsewhere
+    pub fn index_in(&self, range: std::ops::Range<usize>) -> Option<usize> {
+        range
+            .contains(&self.selected_index)
+            .then(|| self.selected_index - range.start)
+    }
+}
+
 #[derive(Debug)]
 pub struct Navigator {
     pub history: Vec<u16>,
@@ -254,6 +281,18 @@ impl Navigator {
         self.data.retain(|k, _| active_routes.contains(k));
     }
 
+    /// Drops every route `stale` says yes to, keeping the home route
+    pub fn remove_routes(&mut self, stale: impl Fn(&Route) -> bool) {
+        let current = self.history.get(self.index).copied();
+        self.history
+            .retain(|id| *id == 0 || self.data.get(id).is_some_and(|r| !stale(r)));
+        // stay on the current route when it is kept, fall back to the last one otherwise
+        self.index = current
+            .and_then(|id| self.history.iter().position(|h| *h == id))
+            .unwrap_or(self.history.len() - 1);
+        self.clear_unused_data();
+    }
+
     pub fn get_current_title(&self) -> &String {
         let id = self.history[self.index];
         &self.data[&id].title
@@ -266,10 +305,22 @@ impl Navigator {
 }
 
 pub struct App {
-    pub io_tx: Option<Sender<IoEvent>>,
+    pub io_tx: Option<UnboundedSender<Request>>,
+    /// requests sent to the network that did not finish yet
+    pub requests: Vec<Request>,
+    /// id of the last dispatched request
+    last_request_id: u64,
     pub app_config: AppConfig,
     pub is_loading: bool,
+    /// set by main, tells when requests are held back by the rate limit
+    pub rate_limiter: Option<api::RateLimiter>,
+    /// set by main, tells which pages were served from stale cache
+    pub response_cache: Option<api::ResponseCache>,
+    /// route id -> unix time its cached data was stored, for pages shown from stale cache
+    pub stale_routes: HashMap<u16, u64>,
     pub api_error: String,
+    /// set once a request found the session expired
+    pub login: Option<LoginState>,
     pub search_results: SearchResult,
     pub size: Rect,
     pub input: Vec<char>,
@@ -289,6 +340,10 @@ pub struct App {
     pub picker: Option<Picker>,
     pub media_image: Option<(String, u32, u32)>,
     pub image_state: Option<StatefulProtocol>,
+    /// posters of the cards on screen
+    pub thumbnails: HashMap<(ImageKind, u64), Thumbnail>,
+    /// cards drawn with room for a poster, with the url of their medium picture
+    drawn_thumbnails: Vec<(ImageKind, u64, Option<String>)>,
     // state:
     pub active_block: ActiveBlock,
     pub active_display_block: ActiveDisplayBlock,
@@ -324,6 +379,7 @@ pub struct App {
     pub selected_popup_status: u8,
     pub selected_popup_rate: u8,
     pub temp_popup_num: u16,
+    pub date_picker: DatePicker,
     // seasonal
     pub anime_season: Seasonal,
     //ranking
@@ -339,8 +395,30 @@ pub struct App {
     pub anime_list_status: Option<UserWatchStatus>,
     // use UserReadStatus to determine the current tab
     pub manga_list_status: Option<UserReadStatus>,
+    // the whole lists of the user, synced once and shown from here
+    pub anime_list: Option<Vec<Anime>>,
+    pub manga_list: Option<Vec<Manga>>,
+    // sort of the user lists, picked in the sort popup
+    pub anime_list_sort: ListSort,
+    pub manga_list_sort: ListSort,
+    pub anime_list_filter: ListFilter,
+    pub manga_list_filter: ListFilter,
+    // highlighted entry of the sort popup
+    pub list_sort_index: usize,
+    // what is typed in the filter prompt, while it is open
+    pub list_filter_input: Option<String>,
+    pub list_filter_error: Option<String>,
     // to track pagination (with local data)
     pub start_card_list_index: u16,
+    // forum:
+    pub forum_topics: Option<ForumTopics>,
+    pub forum_thread: Option<ForumThread>,
+    pub forum_thread_scroll: u16,
+    // profiles:
+    pub profiles: Vec<String>,
+    /// `None` is the default account
+    pub active_profile: Option<String>,
+    pub profile_index: usize,
 }
 #[derive(Debug, Clone)]
 pub enum DetailPopup {
@@ -349,6 +427,79 @@ pub enum DetailPopup {
     Episodes,
     Chapters,
     Volumes,
+    Dates,
+    Discussion,
+}
+
+/// The start and finish dates edited in the dates popup
+#[derive(Debug, Clone, Default)]
+pub struct DatePicker {
+    pub start_date: Option<DateWrapper>,
+    pub finish_date: Option<DateWrapper>,
+    /// year, month and day of the start date, then of the finish date
+    pub selected_field: usize,
+}
+
+impl DatePicker {
+    pub fn new(start_date: Option<DateWrapper>, finish_date: Option<DateWrapper>) -> Self {
+        Self {
+            start_date,
+            finish_date,
+            selected_field: 0,
+        }
+    }
+
+    pub fn next_field(&mut self) {
+        self.selected_field = (self.selected_field + 1) % 6;
+    }
+
+    pub fn previous_field(&mut self) {
+        self.selected_field = (self.selected_field + 5) % 6;
+    }
+
+    /// Unsets the date of the selected field
+    pub fn clear(&mut self) {
+        if self.selected_field < 3 {
+            self.start_date = None;
+        } else {
+            self.finish_date = None;
+        }
+    }
+
+    /// Moves the selected year, month or day by `delta`,
+    /// an unset date is set to today first
+    pub fn shift(&mut self, delta: i32) {
+        let date = if self.selected_field < 3 {
+            &mut self.start_date
+        } else {
+            &mut self.finish_date
+        };
+        let Some(date) = date else {
+            *date = Some(DateWrapper::today());
+            return;
+        };
+        date.date = shift_date(date.date, self.selected_field % 3, delta);
+    }
+}
+
+fn shift_date(date: time::Date, part: usize, delta: i32) -> time::Date {
+    match part {
+        0 => with_year_month(date, date.year() + delta, date.month()),
+        1 => {
+            let months = date.year() * 12 + date.month() as i32 - 1 + delta;
+            let month = time::Month::try_from(months.rem_euclid(12) as u8 + 1).unwrap();
+            with_year_month(date, months.div_euclid(12), month)
+        }
+        _ => date
+            .checked_add(time::Duration::days(delta as i64))
+            .unwrap_or(date),
+    }
+}
+
+/// Keeps the day inside the new month, e.g. 31 March becomes 29 February
+fn with_year_month(date: time::Date, year: i32, month: time::Month) -> time::Date {
+    let day = date.day().min(month.length(year));
+    time::Date::from_calendar_date(year, month, day).unwrap_or(date)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -358,6 +509,8 @@ pub enum ActiveAnimeDetailBlock {
     AddToList,
     Rate,
     Episodes,
+    Dates,
+    Discussion,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -368,17 +521,19 @@ pub enum ActiveMangaDetailBlock {
     Rate,
     Chapters,
     Volumes,
+    Dates,
+    Discussion,
 }
 
 pub struct Seasonal {
     pub anime_season: AnimeSeason,
     pub popup_season_highlight: bool,
     pub anime_sort: SortStyle,
-    pub selected_season: u8,
-    pub selected_year: u16,
+    /// what the season popup currently points at
+    pub popup_season: AnimeSeason,
 }
 
-#[derive(Debug, Clone, IntoStaticStr)]
+#[derive(Debug, Clone, PartialEq, IntoStaticStr)]
 pub enum TopThreeBlock {
     Anime(AnimeRankingType),
     Manga(MangaRankingType),
@@ -424,17 +579,45 @@ pub enum Data {
     UserMangaList(UserMangaList),
     AnimeRanking(Ranking<RankingAnimePair>),
     MangaRanking(Ranking<RankingMangaPair>),
+    ForumTopics(ForumTopics),
+    ForumThread(ForumThread),
 }
 
 #[derive(Debug, Clone)]
 pub struct UserAnimeList {
     pub anime_list: Page<Anime>,
     pub status: Option<UserWatchStatus>,
+    pub sort: ListSort,
+    pub filter: ListFilter,
 }
 #[derive(Debug, Clone)]
 pub struct UserMangaList {
     pub manga_list: Page<Manga>,
     pub status: Option<UserReadStatus>,
+    pub sort: ListSort,
+    pub filter: ListFilter,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForumTopics {
+    pub topics: PageableData<Vec<ForumTopic>>,
+    pub selected_index: usize,
+}
+
+/// The page of posts open in the reader
+#[derive(Debug, Clone)]
+pub struct ForumThread {
+    pub topic_id: u64,
+    pub offset: u64,
+    pub page: PageableData<ForumTopicDetail>,
+}
+
+/// The poster of a card, in [`App::thumbnails`]
+pub enum Thumbnail {
+    Loading,
+    Ready(StatefulProtocol),
+    /// not fetched again until its page is drawn again
+    Failed,
 }
 
 #[derive(Debug, Clone)]
@@ -446,28 +629,20 @@ pub struct Route {
 }
 
 impl App {
-    pub fn new(io_tx: Sender<IoEvent>, app_config: AppConfig) -> Self {
+    pub fn new(io_tx: UnboundedSender<Request>, app_config: AppConfig) -> Self {
         // let can_render =
 
-        let year = chrono::Utc::now().year();
-        let season = get_season();
-        let selected_season = get_selected_season(&season);
-        let picker_res = Picker::from_query_stdio();
-        let mut picker: Option<Picker> = None;
-        if picker_res.is_ok() {
-            picker = Some(picker_res.unwrap());
-        }
+        let season = AnimeSeason::current();
+        let picker = Picker::from_query_stdio().ok();
         Self {
             io_tx: Some(io_tx),
+            requests: Vec::new(),
+            last_request_id: 0,
             anime_season: Seasonal {
-                anime_season: AnimeSeason {
-                    year: year as u64,
-                    season,
-                },
+                anime_season: season.clone(),
                 anime_sort: SortStyle::ListScore,
                 popup_season_highlight: true,
-                selected_season,
-                selected_year: year as u16,
+                popup_season: season,
             },
             // logger:
             logger_state: TuiWidgetState::default().set_default_display_level(app_config.log_level),
@@ -477,7 +652,11 @@ impl App {
             available_manga_ranking_types: app_config.top_three_manga_types.clone(),
             app_config,
             is_loading: false,
+            rate_limiter: None,
+            response_cache: None,
+            stale_routes: HashMap::new(),
             api_error: String::new(),
+            login: None,
             search_results: SearchResult {
                 anime: None,
                 manga: None,
@@ -493,7 +672,7 @@ impl App {
             library: Library {
                 saved_anime: ScrollablePages::new(),
                 saved_manga: ScrollablePages::new(),
-                selected_index: 9, // out of range to show nothing
+                selected_index: 10, // out of range to show nothing
             },
             help_menu_offset: 0,
             help_menu_page: 0,
@@ -521,6 +700,15 @@ impl App {
             anime_list_status: None,
             // manga list
             manga_list_status: None,
+            anime_list: None,
+            manga_list: None,
+            anime_list_sort: ListSort::Score,
+            manga_list_sort: ListSort::Score,
+            anime_list_filter: ListFilter::default(),
+            manga_list_filter: ListFilter::default(),
+            list_sort_index: 0,
+            list_filter_input: None,
+            list_filter_error: None,
             // detail
             active_detail_popup: DetailPopup::AddToList,
             active_anime_detail_block: ActiveAnimeDetailBlock::Synopsis,
@@ -533,6 +721,7 @@ impl App {
             selected_popup_status: 0,
             selected_popup_rate: 0,
             temp_popup_num: 0,
+            date_picker: DatePicker::default(),
             popup_post_req_success: false,
             popup_post_req_success_message: None,
             popup_is_loading: false,
@@ -542,11 +731,19 @@ impl App {
             media_image: None,
             picker,
             image_state: None,
+            thumbnails: HashMap::new(),
+            drawn_thumbnails: Vec::new(),
             anime_details_synopsys_scroll_view_state: ScrollViewState::default(),
             anime_details_info_scroll_view_state: ScrollViewState::default(),
             manga_details_info_scroll_view_state: ScrollViewState::default(),
             manga_details_synopsys_scroll_view_state: ScrollViewState::default(),
             start_card_list_index: 0,
+            forum_topics: None,
+            forum_thread: None,
+            forum_thread_scroll: 0,
+            profiles: Vec::new(),
+            active_profile: None,
+            profile_index: 0,
             // exit:
             exit_flag: false,
             exit_confirmation_popup: false,
@@ -561,30 +758,19 @@ impl App {
         f.render_widget(logs, area);
     }
 
+    /// True while requests are waiting on the rate limit or MAL asked us to slow down
+    pub fn is_throttled(&self) -> bool {
+        self.rate_limiter
+            .as_ref()
+            .is_some_and(|limiter| limiter.is_throttled())
+    }
+
     pub fn write_error(&mut self, e: api::Error) {
-        match e {
-            api::Error::NoAuth => {
-                self.api_error = "Auth Error, Please reload the App".to_string();
-            }
-            api::Error::TimedOut => {
-                self.api_error = "Conntection Timed Out, Please try again".to_string();
-            }
-            api::Error::Unknown => {
-                self.api_error = "Check you internet connection".to_string();
-            }
-            api::Error::NoBody => {
-                self.api_error = "there is No Body".to_string();
-            }
-            api::Error::ParseError(e) => {
-                self.api_error = format!("Parse Error: {}", e);
-            }
-            api::Error::QuerySerializeError(e) => {
-                self.api_error = format!("Query Serialize Error: {}", e);
-            }
-            api::Error::HttpError(e) => {
-                self.api_error = format!("Http Error: {}", e);
-            }
+        warn!("api error: {:?}", e);
+        if e.needs_login() {
+            self.login = Some(LoginState::Expired);
         }
+        self.api_error = e.to_string();
     }
 
     pub fn get_top_three(&mut self) {
@@ -592,15 +778,87 @@ impl App {
     }
 
     pub fn dispatch(&mut self, event: IoEvent) {
-        self.is_loading = true;
+        if !event.is_background() {
+            self.is_loading = true;
+        }
+        // the same request is already on its way
+        if event.can_merge()
+            && self
+                .requests
+                .iter()
+                .any(|r| r.event == event && !r.cancel.is_cancelled())
+        {
+            return;
+        }
+        if event.is_navigation() {
+            self.cancel_navigation();
+        }
+        self.last_request_id += 1;
+        let request = Request {
+            id: self.last_request_id,
+            event,
+            cancel: CancelToken::default(),
+        };
         if let Some(io_tx) = &self.io_tx {
-            if let Err(e) = io_tx.send(event) {
-                self.is_loading = false;
-                warn!("Error from dispatch {}", e);
+            match io_tx.send(request.clone()) {
+                Ok(()) => self.requests.push(request),
+                Err(e) => {
+                    self.is_loading = false;
+                    warn!("Error from dispatch {}", e);
+                }
             }
         };
     }
 
+    /// Drops the pages still loading, the user went somewhere else
+    pub fn cancel_navigation(&mut self) {
+        for request in self.requests.iter().filter(|r| r.event.is_navigation()) {
+            request.cancel.cancel();
+        }
+    }
+
+    /// Notes that a card was drawn with room for its poster
+    pub fn want_thumbnail(&mut self, kind: ImageKind, id: u64, url: Option<String>) {
+        self.drawn_thumbnails.push((kind, id, url));
+    }
+
+    /// Fetches the posters missing from the last draw, forgetting the ones of other pages.
+    /// Nothing changes after a draw without cards, e.g. of a details page.
+    pub fn request_thumbnails(&mut self) {
+        let drawn = std::mem::take(&mut self.drawn_thumbnails);
+        if drawn.is_empty() {
+            return;
+        }
+        self.thumbnails
+            .retain(|key, _| drawn.iter().any(|(kind, id, _)| (*kind, *id) == *key));
+
+        let mut missing = Vec::new();
+        for (kind, id, url) in drawn {
+            if let (Some(url), false) = (url, self.thumbnails.contains_key(&(kind, id))) {
+                self.thumbnails.insert((kind, id), Thumbnail::Loading);
+                missing.push((kind, id, url));
+            }
+        }
+        if !missing.is_empty() {
+            self.dispatch(IoEvent::GetThumbnails(missing));
+        }
+    }
+
+    /// Forgets a request the network is done with
+    pub fn finish_request(&mut self, id: u64) {
+        let Some(index) = self.requests.iter().position(|r| r.id == id) else {
+            return;
+        };
+        // posters a cancelled fetch left loading are asked for again by the next draw
+        if let IoEvent::GetThumbnails(covers) = self.requests.remove(index).event {
+            for (kind, id, _) in covers {
+                if let Some(Thumbnail::Loading) = self.thumbnails.get(&(kind, id)) {
+                    self.thumbnails.remove(&(kind, id));
+                }
+            }
+        }
+    }
+
     pub fn clear_route_before_push(&mut self) {
         // here we take the current index (position) and delete everything after it in the history
         let index = self.navigator.index;
@@ -627,9 +885,25 @@ impl App {
     pub fn push_navigation_stack(&mut self, r: Route) {
         self.clear_route_before_push();
         self.navigator.add_route(r);
+        // set while the network pushes the page of a request answered from stale cache
+        if let Some(stored_at) = api::cache::served_stale() {
+            self.stale_routes.insert(self.navigator.last_id, stored_at);
+        }
         self.remove_old_history();
     }
 
+    /// Unix time the data on screen was cached at, when it is stale
+    pub fn current_route_stale_since(&self) -> Option<u64> {
+        let id = self.navigator.history.get(self.navigator.index)?;
+        self.stale_routes.get(id).copied()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.response_cache
+            .as_ref()
+            .is_some_and(|cache| cache.is_offline())
+    }
+
     fn remove_old_history(&mut self) {
         // when the history length exceeds the limit, we remove the oldest page wich is 1 (0 is the home page)
         if self.navigator.history.len() - 1 > self.app_config.navigation_stack_limit as usize {
@@ -658,6 +932,59 @@ impl App {
         }
     }
 
+    /// Writes the results on screen back into the active route,
+    /// e.g. after the next page was appended to them
+    pub fn update_current_route_data(&mut self) {
+        let data = match self.active_display_block {
+            ActiveDisplayBlock::SearchResultBlock | ActiveDisplayBlock::Seasonal => {
+                Data::SearchResult(self.search_results.clone())
+            }
+            ActiveDisplayBlock::Suggestions => Data::Suggestions(self.search_results.clone()),
+            ActiveDisplayBlock::AnimeRanking => match &self.anime_ranking_data {
+                Some(d) => Data::AnimeRanking(d.clone()),
+                None => return,
+            },
+            ActiveDisplayBlock::MangaRanking => match &self.manga_ranking_data {
+                Some(d) => Data::MangaRanking(d.clone()),
+                None => return,
+            },
+            ActiveDisplayBlock::UserAnimeList => match &self.search_results.anime {
+                Some(d) => Data::UserAnimeList(UserAnimeList {
+                    anime_list: d.clone(),
+                    status: self.anime_list_status.clone(),
+                    sort: self.anime_list_sort,
+                    filter: self.anime_list_filter.clone(),
+                }),
+                None => return,
+            },
+            ActiveDisplayBlock::UserMangaList => match &self.search_results.manga {
+                Some(d) => Data::UserMangaList(UserMangaList {
+                    manga_list: d.clone(),
+                    status: self.manga_list_status.clone(),
+                    sort: self.manga_list_sort,
+                    filter: self.manga_list_filter.clone(),
+                }),
+                None => return,
+            },
+            ActiveDisplayBlock::ForumTopics => match &self.forum_topics {
+                Some(d) => Data::ForumTopics(d.clone()),
+                None => return,
+            },
+            ActiveDisplayBlock::ForumTopic => match &self.forum_thread {
+                Some(d) => Data::ForumThread(d.clone()),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let Some(id) = self.navigator.history.get(self.navigator.index) else {
+            return;
+        };
+        if let Some(route) = self.navigator.data.get_mut(id) {
+            route.data = Some(data);
+        }
+    }
+
     pub fn calculate_help_menu_offset(&mut self) {
         let old_offset = self.help_menu_offset;
         if self.help_menu_max_lines < self.help_docs_size {
@@ -670,6 +997,11 @@ impl App {
     }
 
     pub fn load_previous_route(&mut self) {
+        if self.list_filter_input.is_some() {
+            self.list_filter_input = None;
+            self.list_filter_error = None;
+            return;
+        }
         if self.popup {
             // reset everything
             self.popup = false;
@@ -679,6 +1011,15 @@ impl App {
             return;
         }
 
+        if self.active_display_block == ActiveDisplayBlock::Loading {
+            // back to the page shown before the one that was loading
+            self.cancel_navigation();
+            let route = &self.navigator.data[&self.navigator.history[self.navigator.index]];
+            self.active_display_block = route.block;
+            self.display_block_title = route.title.clone();
+            return;
+        }
+
         if self.navigator.index == 1 {
             self.active_display_block = ActiveDisplayBlock::Empty;
             self.display_block_title = "Home".to_string();
@@ -686,12 +1027,10 @@ impl App {
             return;
         }
 
-        if self.active_display_block == ActiveDisplayBlock::Loading {
-            return;
-        }
-
         if self.active_display_block == ActiveDisplayBlock::Error
             || self.active_display_block == ActiveDisplayBlock::Help
+            || self.active_display_block == ActiveDisplayBlock::Login
+            || self.active_display_block == ActiveDisplayBlock::Profiles
         {
             self.active_display_block = self.navigator.get_current_block();
             return;
@@ -769,28 +1108,12 @@ impl App {
                         // self.set_image_from_route(route.as_ref().unwrap(), Some(d.clone()));
                         self.anime_details = Some(d.clone());
 
-                        if let Some(image) = &route.image {
-                            self.media_image = Some(image.clone());
-                            self.image_state = Some(
-                                self.picker
-                                    .as_ref()
-                                    .unwrap()
-                                    .new_resize_protocol(self.get_picture_from_cache().unwrap()),
-                            );
-                        }
+                        self.restore_poster(&route.image);
                     }
 
                     Data::Manga(d) => {
                         self.manga_details = Some(d.clone());
-                        if let Some(image) = &route.image {
-                            self.media_image = Some(image.clone());
-                            self.image_state = Some(
-                                self.picker
-                                    .as_ref()
-                                    .unwrap()
-                                    .new_resize_protocol(self.get_picture_from_cache().unwrap()),
-                            );
-                        }
+                        self.restore_poster(&route.image);
                     }
 
                     Data::AnimeRanking(d) => {
@@ -805,12 +1128,26 @@ impl App {
 
                     Data::UserAnimeList(d) => {
                         self.anime_list_status = d.status.clone();
-                        self.search_results.anime = Some(d.anime_list.clone());
+                        self.anime_list_sort = d.sort;
+                        self.anime_list_filter = d.filter.clone();
+                        // the synced list may have changed since, e.g. a new score
+                        self.search_results.anime =
+                            self.anime_list_view().or(Some(d.anime_list.clone()));
                     }
 
                     Data::UserMangaList(d) => {
                         self.manga_list_status = d.status.clone();
-                        self.search_results.manga = Some(d.manga_list.clone());
+                        self.manga_list_sort = d.sort;
+                        self.manga_list_filter = d.filter.clone();
+                        self.search_results.manga =
+                            self.manga_list_view().or(Some(d.manga_list.clone()));
+                    }
+
+                    Data::ForumTopics(d) => self.forum_topics = Some(d.clone()),
+
+                    Data::ForumThread(d) => {
+                        self.forum_thread = Some(d.clone());
+                        self.forum_thread_scroll = 0;
                     }
                 }
 
@@ -840,6 +1177,20 @@ impl App {
         }
     }
 
+    pub fn next_manga_list_status(&self) -> Option<UserReadStatus> {
+        match &self.manga_list_status {
+            Some(s) => match s {
+                UserReadStatus::Reading => Some(UserReadStatus::Completed),
+                UserReadStatus::Completed => Some(UserReadStatus::OnHold),
+                UserReadStatus::OnHold => Some(UserReadStatus::Dropped),
+                UserReadStatus::Dropped => Some(UserReadStatus::PlanToRead),
+                UserReadStatus::PlanToRead => None,
+                UserReadStatus::Other(_) => None,
+            },
+            None => Some(UserReadStatus::Reading),
+        }
+    }
+
     pub fn previous_anime_list_status(&self) -> Option<UserWatchStatus> {
         match &self.anime_list_status {
             Some(s) => match s {
@@ -854,6 +1205,21 @@ impl App {
         }
     }
 
+    /// Shows the poster of a route again, unless it left the image cache since
+    fn restore_poster(&mut self, image: &Option<(String, u32, u32)>) {
+        self.media_image = image.clone();
+        self.image_state = match (self.picker, image) {
+            (Some(picker), Some(_)) => self
+                .get_picture_from_cache()
+                .ok()
+                .map(|decoded| picker.new_resize_protocol(decoded)),
+            _ => None,
+        };
+        if self.image_state.is_none() {
+            self.media_image = None;
+        }
+    }
+
     pub fn get_picture_from_cache(&self) -> Result<DynamicImage, ImageError> {
         // all images are stored in $HOME?/.cache/mal-cli/images/
         let file_name = self.media_image.as_ref().unwrap().0.clone();
@@ -861,30 +1227,184 @@ impl App {
         let image = image::ImageReader::open(file_path)?.decode()?;
         Ok(image)
     }
+    /// The default account first, then every named profile
+    pub fn profile_choices(&self) -> Vec<Option<String>> {
+        std::iter::once(None)
+            .chain(self.profiles.iter().cloned().map(Some))
+            .collect()
+    }
+
+    /// Forgets everything about the user, e.g. after switching to another account
+    pub fn clear_user_data(&mut self) {
+        // nor what is still loading for them
+        self.cancel_navigation();
+        self.user_profile = None;
+        self.anime_list_status = None;
+        self.manga_list_status = None;
+        self.anime_list = None;
+        self.manga_list = None;
+        self.anime_list_filter = ListFilter::default();
+        self.manga_list_filter = ListFilter::default();
+        self.anime_details = None;
+        self.manga_details = None;
+        // details carry the list status of the user, suggestions follow their list
+        self.navigator.remove_routes(|route| {
+            matches!(
+                route.block,
+                ActiveDisplayBlock::UserInfo
+                    | ActiveDisplayBlock::UserAnimeList
+                    | ActiveDisplayBlock::UserMangaList
+                    | ActiveDisplayBlock::Suggestions
+                    | ActiveDisplayBlock::AnimeDetails
+                    | ActiveDisplayBlock::MangaDetails
+            )
+        });
+        let routes = &self.navigator.data;
+        self.stale_routes.retain(|id, _| routes.contains_key(id));
+        self.active_display_block = self.navigator.get_current_block();
+        self.display_block_title = self.navigator.get_current_title().clone();
+    }
+
     pub fn reset_result_index(&mut self) {
         // reset the selected index in the search results
         self.search_results.selected_display_card_index = Some(0);
         self.start_card_list_index = 0;
     }
+
+    /// Shows the synced anime list for the status tab, sort and filter picked,
+    /// nothing is fetched
+    pub fn show_anime_list(&mut self) {
+        let Some(page) = self.anime_list_view() else {
+            return;
+        };
+        self.search_results.anime = Some(page);
+        let title = list_title(
+            "My Anime List",
+            get_status_string(&self.anime_list_status),
+            &self.anime_list_filter,
+        );
+        self.show_user_list(ActiveDisplayBlock::UserAnimeList, title);
+    }
+
+    /// Same as [`show_anime_list`](Self::show_anime_list) for the manga list
+    pub fn show_manga_list(&mut self) {
+        let Some(page) = self.manga_list_view() else {
+            return;
+        };
+        self.search_results.manga = Some(page);
+        let title = list_title(
+            "My Manga List",
+            get_manga_status_string(&self.manga_list_status),
+            &self.manga_list_filter,
+        );
+        self.show_user_list(ActiveDisplayBlock::UserMangaList, title);
+    }
+
+    fn anime_list_view(&self) -> Option<Page<Anime>> {
+        let status = &self.anime_list_status;
+        let shown = self.anime_list_filter.apply(
+            self.anime_list
+                .as_ref()?
+                .iter()
+                .filter(|a| has_watch_status(a, status)),
+            self.anime_list_sort,
+        );
+        Some(local_page(shown))
+    }
+
+    fn manga_list_view(&self) -> Option<Page<Manga>> {
+        let status = &self.manga_list_status;
+        let shown = self.manga_list_filter.apply(
+            self.manga_list
+                .as_ref()?
+                .iter()
+                .filter(|m| has_read_status(m, status)),
+            self.manga_list_sort,
+        );
+        Some(local_page(shown))
+    }
+
+    /// Keeps the synced list in line with a change sent from the details page
+    pub fn update_synced_anime(&mut self, anime_id: u64, status: UserAnimeListStatus) {
+        let details = self.anime_details.as_ref().filter(|a| a.id == anime_id);
+        let Some(list) = self.anime_list.as_mut() else {
+            return;
+        };
+        match list.iter_mut().find(|a| a.id == anime_id) {
+            Some(anime) => anime.my_list_status = Some(status),
+            None => {
+                if let Some(anime) = details {
+                    let mut anime = anime.clone();
+                    anime.my_list_status = Some(status);
+                    list.push(anime);
+                }
+            }
+        }
+    }
+
+    /// See [`update_synced_anime`](Self::update_synced_anime)
+    pub fn update_synced_manga(&mut self, manga_id: u64, status: UserMangaListStatus) {
+        let details = self.manga_details.as_ref().filter(|m| m.id == manga_id);
+        let Some(list) = self.manga_list.as_mut() else {
+            return;
+        };
+        match list.iter_mut().find(|m| m.id == manga_id) {
+            Some(manga) => manga.my_list_status = Some(status),
+            None => {
+                if let Some(manga) = details {
+                    let mut manga = manga.clone();
+                    manga.my_list_status = Some(status);
+                    list.push(manga);
+                }
+            }
+        }
+    }
+
+    /// Replaces the list route on screen, or pushes one when another page is shown
+    fn show_user_list(&mut self, block: ActiveDisplayBlock, title: String) {
+        self.reset_result_index();
+        let on_screen = self.active_display_block == block
+            && self.get_current_route().is_some_and(|r| r.block == block);
+        if !on_screen {
+            self.push_navigation_stack(Route {
+                data: None,
+                block,
+                title: title.clone(),
+                image: None,
+            });
+        }
+        self.active_block = ActiveBlock::DisplayBlock;
+        self.active_display_block = block;
+        self.display_block_title = title.clone();
+        if let Some(route) = self
+            .navigator
+            .history
+            .get(self.navigator.index)
+            .and_then(|id| self.navigator.data.get_mut(id))
+        {
+            route.title = title;
+        }
+        self.update_current_route_data();
+    }
 }
 
-fn get_season() -> Season {
-    let month = chrono::Utc::now().month();
-    match month {
-        3..=5 => Season::Spring,
-        6..=8 => Season::Summer,
-        9..=11 => Season::Fall,
-        _ => Season::Winter,
+/// All entries in one page, there is nothing left to fetch
+fn local_page<T: Clone + Debug>(entries: Vec<T>) -> Page<T> {
+    PageableData {
+        data: entries.into_iter().map(|node| Node { node }).collect(),
+        paging: Paging {
+            previous: None,
+            next: None,
+        },
     }
 }
 
-fn get_selected_season(season: &Season) -> u8 {
-    match *season {
-        Season::Winter => 0,
-        Season::Spring => 1,
-        Season::Summer => 2,
-        Season::Fall => 3,
-        Season::Other(_) => panic!("no season selected"),
+/// e.g. `My Anime List: completed (type:tv score:8)`
+fn list_title(list: &str, status: String, filter: &ListFilter) -> String {
+    if filter.is_empty() {
+        format!("{}: {}", list, status)
+    } else {
+        format!("{}: {} ({})", list, status, filter)
     }
 }
 
@@ -894,9 +1414,9 @@ pub mod test {
     use crate::config::app_config::AppConfig;
     pub fn get_app() -> App {
         let config = AppConfig::load();
-        let (sync_io_tx, _) = std::sync::mpsc::channel::<IoEvent>();
+        let (io_tx, _) = tokio::sync::mpsc::unbounded_channel::<Request>();
 
-        let mut app = App::new(sync_io_tx, config.unwrap());
+        let mut app = App::new(io_tx, config.unwrap());
         let route = Route {
             data: None,
             block: ActiveDisplayBlock::Empty,
@@ -943,4 +1463,189 @@ pub mod test {
         app.load_next_route();
         assert_eq!(app.navigator.index, 4);
     }
+
+    /// An app whose dispatched requests can be read back
+    fn get_connected_app() -> (App, tokio::sync::mpsc::UnboundedReceiver<Request>) {
+        let (io_tx, io_rx) = tokio::sync::mpsc::unbounded_channel::<Request>();
+        let mut app = get_app();
+        app.io_tx = Some(io_tx);
+        (app, io_rx)
+    }
+
+    #[test]
+    fn test_dispatch_merges_identical_requests() {
+        let (mut app, mut io_rx) = get_connected_app();
+        app.dispatch(IoEvent::GetAnime(1));
+        app.dispatch(IoEvent::GetAnime(1));
+        assert_eq!(io_rx.try_recv().unwrap().event, IoEvent::GetAnime(1));
+        assert!(io_rx.try_recv().is_err());
+
+        // a list change is never dropped
+        app.dispatch(IoEvent::DeleteAnimeListStatus("1".to_string()));
+        app.dispatch(IoEvent::DeleteAnimeListStatus("1".to_string()));
+        assert!(io_rx.try_recv().is_ok());
+        assert!(io_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_navigation_cancels_older_requests() {
+        let (mut app, mut io_rx) = get_connected_app();
+        app.dispatch(IoEvent::GetAnime(1));
+        app.dispatch(IoEvent::GetTopThree(TopThreeBlock::Anime(
+            AnimeRankingType::Airing,
+        )));
+        app.dispatch(IoEvent::GetAnime(2));
+        let first = io_rx.try_recv().unwrap();
+        let top_three = io_rx.try_recv().unwrap();
+        let second = io_rx.try_recv().unwrap();
+        assert!(first.cancel.is_cancelled());
+        assert!(!top_three.cancel.is_cancelled());
+        assert!(!second.cancel.is_cancelled());
+        assert!(first.id < second.id);
+
+        // going back to the first one sends it again
+        app.dispatch(IoEvent::GetAnime(1));
+        assert_eq!(io_rx.try_recv().unwrap().event, IoEvent::GetAnime(1));
+        app.finish_request(first.id);
+        assert_eq!(app.requests.len(), 3);
+    }
+
+    #[test]
+    fn test_esc_while_loading_cancels() {
+        let (mut app, mut io_rx) = get_connected_app();
+        app.navigator.data.get_mut(&4).unwrap().title = "Before".to_string();
+        app.active_display_block = ActiveDisplayBlock::Loading;
+        app.display_block_title = "Search Results: x".to_string();
+        app.dispatch(IoEvent::GetSearchResults("x".to_string()));
+
+        app.load_previous_route();
+        assert!(io_rx.try_recv().unwrap().cancel.is_cancelled());
+        assert_eq!(app.navigator.index, 4);
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Empty);
+        assert_eq!(app.display_block_title, "Before");
+    }
+
+    #[test]
+    fn test_request_thumbnails() {
+        let (mut app, mut io_rx) = get_connected_app();
+        let url = |id| Some(format!("https://cdn/{}.jpg", id));
+        app.want_thumbnail(ImageKind::Anime, 1, url(1));
+        app.want_thumbnail(ImageKind::Anime, 2, None);
+        app.request_thumbnails();
+        let request = io_rx.try_recv().unwrap();
+        assert_eq!(
+            request.event,
+            IoEvent::GetThumbnails(vec![(ImageKind::Anime, 1, url(1).unwrap())])
+        );
+        assert!(!request.event.is_navigation());
+        assert!(app.thumbnails.contains_key(&(ImageKind::Anime, 1)));
+
+        // drawn again while loading, and a details page in between
+        app.want_thumbnail(ImageKind::Anime, 1, url(1));
+        app.request_thumbnails();
+        app.request_thumbnails();
+        assert!(io_rx.try_recv().is_err());
+
+        // the next page drops the posters of the previous one
+        app.want_thumbnail(ImageKind::Manga, 1, url(1));
+        app.request_thumbnails();
+        let request = io_rx.try_recv().unwrap();
+        assert!(!app.thumbnails.contains_key(&(ImageKind::Anime, 1)));
+        assert!(app.thumbnails.contains_key(&(ImageKind::Manga, 1)));
+
+        // what a cancelled fetch left loading is asked for again
+        request.cancel.cancel();
+        app.finish_request(request.id);
+        app.want_thumbnail(ImageKind::Manga, 1, url(1));
+        app.request_thumbnails();
+        assert!(io_rx.try_recv().is_ok());
+
+        // a failed poster waits until its page is drawn again
+        app.thumbnails
+            .insert((ImageKind::Manga, 1), Thumbnail::Failed);
+        app.want_thumbnail(ImageKind::Manga, 1, url(1));
+        app.request_thumbnails();
+        assert!(io_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_keys_on_a_filter_without_matches() {
+        use crate::event::Key;
+        let mut app = get_app();
+        let fake = crate::api::FakeMalApi::new();
+        app.anime_list = Some(vec![fake.anime(5114).unwrap()]);
+        app.anime_list_filter = "nothing matches this".parse().unwrap();
+        app.show_anime_list();
+        assert!(app.search_results.anime.as_ref().unwrap().data.is_empty());
+
+        app.active_block = ActiveBlock::DisplayBlock;
+        let next_page = app.app_config.keys.next_page;
+        for key in [
+            Key::Left,
+            Key::Right,
+            Key::Up,
+            Key::Down,
+            next_page,
+            Key::Enter,
+        ] {
+            crate::handlers::handle_app(key, &mut app);
+        }
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::UserAnimeList);
+    }
+
+    #[test]
+    fn test_dates_popup_clears_dates() {
+        use crate::event::Key;
+        let (mut app, mut io_rx) = get_connected_app();
+        let fake = crate::api::FakeMalApi::new();
+        app.anime_details = fake.anime(5114);
+        app.active_block = ActiveBlock::DisplayBlock;
+        app.active_display_block = ActiveDisplayBlock::AnimeDetails;
+        app.active_anime_detail_block = ActiveAnimeDetailBlock::Dates;
+
+        crate::handlers::handle_app(Key::Enter, &mut app);
+        assert!(app.popup);
+        crate::handlers::handle_app(Key::Backspace, &mut app);
+        assert!(app.date_picker.start_date.is_none());
+        assert!(app.date_picker.finish_date.is_some());
+        for key in [Key::Right, Key::Right, Key::Right, Key::Delete, Key::Enter] {
+            crate::handlers::handle_app(key, &mut app);
+        }
+
+        match io_rx.try_recv().unwrap().event {
+            IoEvent::UpdateAnimeListStatus(5114, query) => {
+                assert_eq!(query.start_date, Some(None));
+                assert_eq!(query.finish_date, Some(None));
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_date_picker_shift() {
+        let date = time::Date::from_calendar_date(2024, time::Month::January, 31).unwrap();
+        let mut picker = DatePicker::new(Some(DateWrapper { date }), None);
+        picker.next_field();
+        picker.shift(1);
+        assert_eq!(
+            picker.start_date.as_ref().unwrap().date.to_string(),
+            "2024-02-29"
+        );
+        picker.shift(-2);
+        assert_eq!(
+            picker.start_date.as_ref().unwrap().date.to_string(),
+            "2023-12-29"
+        );
+        picker.next_field();
+        picker.shift(3);
+        assert_eq!(
+            picker.start_date.as_ref().unwrap().date.to_string(),
+            "2024-01-01"
+        );
+
+        // an unset date starts from today
+        picker.next_field();
+        picker.shift(1);
+        assert!(picker.finish_date.is_some());
+    }
 }
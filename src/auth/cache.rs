@@ -1,48 +1,88 @@
 use super::OAuth;
 use crate::config::oauth_config::AuthConfig;
-use std::{
-    fs::OpenOptions,
-    io::{Read, Write},
-};
+use std::fs;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
 
-pub fn cache_auth(auth: &OAuth) {
-    let auth_path = AuthConfig::get_paths().unwrap().auth_cache_path;
-
-    let mut auth_file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(auth_path)
-        .unwrap();
+/// Writes the auth to the token cache of its profile, replacing the previous one in one step
+pub fn cache_auth(auth: &OAuth) -> io::Result<()> {
+    let auth_path = AuthConfig::get_paths()
+        .map_err(io::Error::other)?
+        .token_cache_path(auth.profile.as_deref());
+    write_auth(&auth_path, auth)
+}
 
-    let cached_auth = serde_json::to_string(auth).unwrap();
+/// Writes the auth to `auth_path` the way [`cache_auth`] does
+pub fn write_auth(auth_path: &Path, auth: &OAuth) -> io::Result<()> {
+    let cached_auth = serde_json::to_string(auth)?;
 
-    write!(auth_file, "{}", cached_auth).unwrap();
+    // a crash halfway through must not leave a broken cache behind
+    let tmp_path = auth_path.with_extension("tmp");
+    // a leftover keeps its mode when opened, only a new file gets 0600
+    match fs::remove_file(&tmp_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // the tokens are only for the user's eyes
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(&tmp_path)?.write_all(cached_auth.as_bytes())?;
+    fs::rename(&tmp_path, auth_path)
 }
 
-pub fn load_cached_auth() -> Option<OAuth> {
-    let auth_path = AuthConfig::get_paths().unwrap().auth_cache_path;
-    let mut auth_file = OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(auth_path)
-        .unwrap();
+/// The cached auth of the profile of `config`, with the client secret of oauth2.yml
+pub fn load_cached_auth(config: &AuthConfig) -> Option<OAuth> {
+    let auth_path = AuthConfig::get_paths()
+        .ok()?
+        .token_cache_path(config.profile.as_deref());
+    let cached_string = fs::read_to_string(auth_path).ok()?;
+    parse_cached_auth(&cached_string, config)
+}
 
-    let mut cached_string = String::new();
+fn parse_cached_auth(cached_string: &str, config: &AuthConfig) -> Option<OAuth> {
+    let mut cached_auth: OAuth = serde_json::from_str(cached_string).ok()?;
+    cached_auth.profile = config.profile.clone();
+    // never cached, so adding or rotating it in oauth2.yml takes effect right away
+    cached_auth.client_secret = config.get_client_secret();
+    Some(cached_auth)
+}
 
-    auth_file.read_to_string(&mut cached_string).unwrap();
+/// Removes the token cache of a profile, there being none is fine
+pub fn delete_cached_auth(profile: Option<&str>) -> io::Result<()> {
+    let auth_path = AuthConfig::get_paths()
+        .map_err(io::Error::other)?
+        .token_cache_path(profile);
+    match fs::remove_file(auth_path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
 
-    let cached_auth: OAuth = match serde_json::from_str(&cached_string) {
-        Ok(s) => s,
-        Err(_) => return None,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Secret;
 
-    Some(cached_auth)
-}
+    #[test]
+    fn test_client_secret_is_not_cached() {
+        let auth = OAuth::new("mal-cli", "client", Some("s3cret"), "127.0.0.1:2006");
+        let cached = serde_json::to_string(&auth).unwrap();
+        assert!(!cached.contains("s3cret"));
 
-pub fn delete_cached_auth() {
-    let auth_path = AuthConfig::get_paths().unwrap().config_file_path;
-    let _ = std::fs::remove_file(auth_path);
+        let config = AuthConfig {
+            client_id: "client".to_string(),
+            client_secret: Some(Secret::new("r0tated".to_string())),
+            ..AuthConfig::default()
+        };
+        let loaded = parse_cached_auth(&cached, &config).unwrap();
+        assert_eq!(loaded.client_secret.unwrap().expose(), "r0tated");
+        assert!(parse_cached_auth(&cached, &AuthConfig::default())
+            .unwrap()
+            .client_secret
+            .is_none());
+    }
 }
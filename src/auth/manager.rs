@@ -0,0 +1,248 @@
+use super::{cache, redirect, AuthError, LoginFlow, OAuth};
+use crate::config::oauth_config::AuthConfig;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, warn};
+use url::Url;
+
+/// Refresh this long before the access token runs out
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// Owns the [`OAuth`] of a session and keeps its access token valid.
+///
+/// The token is refreshed with the async client once it is within
+/// [`REFRESH_MARGIN`] of expiring, and every new token is written back to
+/// the token cache so the next launch starts with it. With the app's
+/// [`AuthConfig`] it can also log in again once the refresh token is gone.
+#[derive(Clone, Debug)]
+pub struct TokenManager {
+    oauth: OAuth,
+    margin: Duration,
+    persist: bool,
+    /// where tokens are saved instead of the token cache of the profile
+    cache_path: Option<PathBuf>,
+    login: Option<(AuthConfig, LoginFlow)>,
+    /// the flow started by [`begin_login`](Self::begin_login)
+    pending: Option<OAuth>,
+}
+
+impl TokenManager {
+    pub fn new(oauth: OAuth) -> Self {
+        Self {
+            oauth,
+            margin: REFRESH_MARGIN,
+            persist: true,
+            cache_path: None,
+            login: None,
+            pending: None,
+        }
+    }
+
+    /// Allows logging in again from inside the app
+    pub fn with_login(mut self, config: AuthConfig, flow: LoginFlow) -> Self {
+        self.login = Some((config, flow));
+        self
+    }
+
+    /// Whether refreshed tokens are saved to the token cache
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// Saves tokens to `path` instead of the token cache of the profile
+    pub fn cache_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache_path = Some(path.into());
+        self
+    }
+
+    pub fn oauth(&self) -> &OAuth {
+        &self.oauth
+    }
+
+    pub fn into_oauth(self) -> OAuth {
+        self.oauth
+    }
+
+    /// True once the token expired or is about to
+    pub fn needs_refresh(&self) -> bool {
+        self.oauth.token().is_none_or(|token| {
+            token
+                .expires_in_secs()
+                .is_none_or(|secs| secs <= self.margin.as_secs())
+        })
+    }
+
+    /// Refreshes the token when it is about to expire, saving the new one
+    pub async fn ensure_fresh(&mut self) -> Result<(), AuthError> {
+        if !self.needs_refresh() {
+            return Ok(());
+        }
+        self.refresh().await
+    }
+
+    /// Asks for a new token right away, saving it
+    pub async fn refresh(&mut self) -> Result<(), AuthError> {
+        debug!("refreshing the access token");
+        // work on a copy, a failed refresh keeps the old token around
+        let mut oauth = self.oauth.clone();
+        oauth.request_refresh_async().await?;
+        self.oauth = oauth;
+
+        self.save();
+        Ok(())
+    }
+
+    /// Starts a new PKCE flow and opens its url in the browser, returning it
+    /// for when there is no browser
+    pub fn begin_login(&mut self) -> Result<Url, AuthError> {
+        let (config, flow) = self.login.as_ref().ok_or(AuthError::AuthNotPresent)?;
+        if *flow == LoginFlow::Manual {
+            // there is no terminal to paste into while the app runs
+            return Err(AuthError::InvalidResponse(
+                "restart with `mal --manual-auth` to log in again".to_string(),
+            ));
+        }
+        let auth = OAuth::for_login(config, self.oauth.endpoints.clone());
+        let url = auth.get_auth_url();
+        if let Err(e) = super::open(&url) {
+            warn!("could not open the browser: {}", e);
+        }
+        self.pending = Some(auth);
+        Ok(url)
+    }
+
+    /// Waits for the browser to come back from the login url, then swaps in the new token
+    pub async fn finish_login(&mut self) -> Result<(), AuthError> {
+        let auth = self.pending.take().ok_or(AuthError::AuthNotPresent)?;
+        let app_name = auth.user_agent().clone();
+        let timeout = self
+            .login
+            .as_ref()
+            .map_or(redirect::DEFAULT_TIMEOUT, |(config, _)| {
+                config.get_login_timeout()
+            });
+        let mut auth = tokio::task::spawn_blocking(move || {
+            redirect::Server::new(app_name, auth).timeout(timeout).go()
+        })
+        .await
+        .map_err(|e| AuthError::InvalidResponse(e.to_string()))??;
+        auth.get_access_token_async().await?;
+        self.oauth = auth;
+        self.save();
+        Ok(())
+    }
+
+    /// Uses the account of another profile from now on, `None` being the default one.
+    /// Without a cached token the next refresh asks to log in.
+    pub fn switch_profile(&mut self, profile: Option<&str>) -> Result<(), AuthError> {
+        let (config, _) = self.login.as_mut().ok_or(AuthError::AuthNotPresent)?;
+        *config = config
+            .for_profile(profile)
+            .map_err(|e| AuthError::InvalidResponse(e.to_string()))?;
+        let endpoints = self.oauth.endpoints.clone();
+        self.oauth = match cache::load_cached_auth(config) {
+            Some(auth) => auth.with_endpoints(endpoints),
+            None => OAuth::for_login(config, endpoints),
+        };
+        self.pending = None;
+        Ok(())
+    }
+
+    fn save(&self) {
+        if self.persist {
+            let result = match &self.cache_path {
+                Some(path) => cache::write_auth(path, &self.oauth),
+                None => cache::cache_auth(&self.oauth),
+            };
+            if let Err(e) = result {
+                warn!("could not cache the token: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ReplayServer;
+    use crate::auth::token::{Token, TokenWrapper};
+    use crate::config::app_config::NetworkConfig;
+
+    /// An auth whose token has `secs_left` seconds to live
+    fn auth_expiring_in(secs_left: u64, token_url: &str) -> OAuth {
+        let mut auth = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        auth.endpoints.token_url = token_url.to_string();
+        auth.auth_code = Some("code".to_string());
+        let mut token = TokenWrapper::new(Token {
+            token_type: "Bearer".to_string(),
+            expires_in: 3600,
+            access_token: "old".to_string(),
+            refresh_token: "old".to_string(),
+        });
+        token.generate_time -= 3600 - secs_left;
+        auth.token = Some(token);
+        auth
+    }
+
+    #[tokio::test]
+    async fn test_valid_token_is_kept() {
+        // nothing listens there, any request would fail
+        let mut manager =
+            TokenManager::new(auth_expiring_in(3000, "http://127.0.0.1:1/token")).persist(false);
+        assert!(!manager.needs_refresh());
+        manager.ensure_fresh().await.unwrap();
+        assert_eq!(manager.oauth().token().unwrap().token.access_token, "old");
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_ahead_of_expiry() {
+        let dir = std::env::temp_dir().join(format!("mal-tokens-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = ReplayServer::bind(&dir, 0).await.unwrap();
+        let config = server.network_config(&NetworkConfig::default());
+        tokio::spawn(server.run());
+
+        let mut manager = TokenManager::new(auth_expiring_in(60, &config.token_url)).persist(false);
+        assert!(manager.needs_refresh());
+        manager.ensure_fresh().await.unwrap();
+        assert_eq!(
+            manager.oauth().token().unwrap().token.access_token,
+            "replay"
+        );
+        assert!(!manager.needs_refresh());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_refreshed_token_is_saved() {
+        let dir = std::env::temp_dir().join(format!("mal-saved-tokens-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = ReplayServer::bind(&dir, 0).await.unwrap();
+        let config = server.network_config(&NetworkConfig::default());
+        tokio::spawn(server.run());
+
+        let path = dir.join("token_cache.json");
+        let mut manager =
+            TokenManager::new(auth_expiring_in(60, &config.token_url)).cache_path(&path);
+        manager.ensure_fresh().await.unwrap();
+
+        let saved: OAuth = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.token().unwrap().token.access_token, "replay");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_token() {
+        let mut manager =
+            TokenManager::new(auth_expiring_in(0, "http://127.0.0.1:1/token")).persist(false);
+        assert!(manager.ensure_fresh().await.is_err());
+        assert_eq!(manager.oauth().token().unwrap().token.access_token, "old");
+    }
+}
@@ -7,19 +7,60 @@ pub mod token;
 /// methods for cache
 pub mod cache;
 
-use crate::config::oauth_config::AuthConfig;
+/// keeps the access token of a running session valid
+pub mod manager;
+
+use crate::config::{app_config::NetworkConfig, oauth_config::AuthConfig, Secret};
 use color_eyre::Result;
 use rand::{distr::Alphanumeric, rng, Rng};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use serde_urlencoded;
-use std::{io::Error, iter, str::FromStr}; // process::Output
+use std::{io::Error, iter, str::FromStr, time::Duration}; // process::Output
 use token::{Token, TokenWrapper};
+use tracing::warn;
 use url::Url;
 
 const USER_AGENT: &str = "mal-cli";
-const AUTHORIZE_URL: &str = "https://myanimelist.net/v1/oauth2/authorize";
-const TOKEN_URL: &str = "https://myanimelist.net/v1/oauth2/token";
+pub const AUTHORIZE_URL: &str = "https://myanimelist.net/v1/oauth2/authorize";
+pub const TOKEN_URL: &str = "https://myanimelist.net/v1/oauth2/token";
+
+/// Where the user is sent to log in and where tokens are requested
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthEndpoints {
+    pub authorize_url: String,
+    pub token_url: String,
+    /// the timeouts of the network config, a stalled refresh holds up every request
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for AuthEndpoints {
+    fn default() -> Self {
+        NetworkConfig::default().auth_endpoints()
+    }
+}
+
+impl AuthEndpoints {
+    /// The async client for token requests
+    fn client(&self) -> reqwest::Result<reqwest::Client> {
+        reqwest::ClientBuilder::new()
+            .user_agent(USER_AGENT)
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .build()
+    }
+}
+
+/// How the authorization code gets from the browser to the app
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LoginFlow {
+    /// open the browser and catch the redirect with the local server
+    #[default]
+    Browser,
+    /// print the url and read the redirect url, or only its code, from stdin
+    Manual,
+}
 
 #[derive(Clone, Debug)]
 pub enum AuthError {
@@ -28,6 +69,18 @@ pub enum AuthError {
     InvalidResponse(String),
     AuthNotPresent,
     TokenNotPresent,
+    /// MAL refused the token request, e.g. a revoked refresh token
+    Rejected(String),
+}
+
+impl AuthError {
+    /// True when only logging in again can fix it
+    pub fn needs_login(&self) -> bool {
+        matches!(
+            self,
+            AuthError::AuthNotPresent | AuthError::TokenNotPresent | AuthError::Rejected(_)
+        )
+    }
 }
 
 impl From<reqwest::Error> for AuthError {
@@ -48,6 +101,7 @@ impl std::error::Error for AuthError {
             AuthError::InvalidResponse(_) => None,
             AuthError::AuthNotPresent => None,
             AuthError::TokenNotPresent => None,
+            AuthError::Rejected(_) => None,
         }
     }
 }
@@ -60,23 +114,32 @@ impl std::fmt::Display for AuthError {
             AuthError::InvalidResponse(ref err) => err.fmt(f),
             AuthError::AuthNotPresent => write!(f, "Auth is not present"),
             AuthError::TokenNotPresent => write!(f, "Token is not present"),
+            AuthError::Rejected(ref err) => write!(f, "MyAnimeList rejected the token: {}", err),
         }
     }
 }
 
 const CODE_CHALLENGE_LENGTH: usize = 128;
+const STATE_LENGTH: usize = 32;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuth {
     pub client_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub client_secret: Option<String>,
+    /// not cached, always taken from oauth2.yml
+    #[serde(skip)]
+    pub client_secret: Option<Secret>,
     pub redirect_url: String,
     pub user_agent: String,
     pub challenge: String,
     pub state: String,
     pub auth_code: Option<String>,
     pub token: Option<TokenWrapper>,
+    /// not cached, always taken from the current config
+    #[serde(skip)]
+    pub endpoints: AuthEndpoints,
+    /// the profile whose token cache this belongs to, `None` being the default account
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 impl OAuth {
@@ -91,16 +154,27 @@ impl OAuth {
     ) -> Self {
         OAuth {
             client_id: client_id.to_string(),
-            client_secret: client_secret.map(|cs| cs.to_string()),
+            client_secret: client_secret.map(|cs| Secret::new(cs.to_string())),
             redirect_url: redirect_url.to_string(),
             user_agent: user_agent.to_string(),
             challenge: Self::new_challenge(CODE_CHALLENGE_LENGTH),
-            state: "AUTHSTART".to_string(),
+            state: Self::new_state(),
             auth_code: None,
             token: None,
+            endpoints: AuthEndpoints::default(),
+            profile: None,
         }
     }
 
+    /// A random state for one login, so a redirect of any other one is refused
+    fn new_state() -> String {
+        let mut rng = rng();
+        iter::repeat(())
+            .map(|()| rng.sample(Alphanumeric) as char)
+            .take(STATE_LENGTH)
+            .collect()
+    }
+
     /// Generates a new base64-encoded SHA-256 PKCE code
     /// # Panic
     /// `len` needs to be a value between 48 and 128
@@ -119,6 +193,26 @@ impl OAuth {
         challenge
     }
 
+    /// Start of a new oauth2 flow for the configured app and profile
+    pub fn for_login(config: &AuthConfig, endpoints: AuthEndpoints) -> Self {
+        let mut auth = OAuth::new(
+            config.get_user_agent(),
+            config.get_client_id(),
+            None,
+            config.get_redirect_uri(),
+        )
+        .with_endpoints(endpoints);
+        auth.client_secret = config.get_client_secret();
+        auth.profile = config.profile.clone();
+        auth
+    }
+
+    /// Use other authorization and token urls than MAL's
+    pub fn with_endpoints(mut self, endpoints: AuthEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
     /// Returns user agent
     pub fn user_agent(&self) -> &String {
         &self.user_agent
@@ -148,7 +242,7 @@ impl OAuth {
 
         url::Url::from_str(&format!(
             "{}?{}",
-            AUTHORIZE_URL,
+            self.endpoints.authorize_url,
             serde_urlencoded::to_string(auth_query).unwrap()
         ))
         .unwrap()
@@ -177,13 +271,48 @@ impl OAuth {
         Ok(())
     }
 
+    /// Takes what the user pasted back: the redirect url, its query or only the code
+    pub fn parse_pasted_redirect(&mut self, pasted: &str) -> Result<(), AuthError> {
+        #[derive(Deserialize, Debug)]
+        struct DeniedResponse {
+            error: String,
+            error_description: Option<String>,
+            message: Option<String>,
+        }
+
+        let pasted = pasted.trim();
+        let pasted = pasted.split('#').next().unwrap_or_default();
+        let query = match pasted.split_once('?') {
+            Some((_, query)) => Some(query),
+            None => pasted.contains('=').then_some(pasted),
+        };
+        if let Some(query) = query {
+            // e.g. `error=access_denied` when the user declined
+            if let Ok(denied) = serde_urlencoded::from_str::<DeniedResponse>(query) {
+                return Err(redirect::ServerError::Denied {
+                    error: denied.error,
+                    description: denied.error_description.or(denied.message),
+                }
+                .into());
+            }
+            self.parse_redirect_query_string(query)
+        } else if pasted.is_empty() {
+            Err(AuthError::InvalidResponse("Nothing was pasted".to_string()))
+        } else {
+            // a bare code has no state to check, it can only come from the url just printed
+            let query = serde_urlencoded::to_string([("code", pasted), ("state", &self.state)])
+                .map_err(|e| AuthError::InvalidResponse(e.to_string()))?;
+            self.parse_redirect_query_string(&query)
+        }
+    }
+
     /// Creates a new url to get the token
     pub fn get_token_query_string(&self) -> Result<String, AuthError> {
         #[derive(Serialize, Debug)]
         struct TokenRequest {
             client_id: String,
             #[serde(skip_serializing_if = "Option::is_none")]
-            client_secret: Option<String>,
+            client_secret: Option<Secret>,
             code: String,
             code_verifier: String,
             grant_type: String,
@@ -209,7 +338,7 @@ impl OAuth {
         let request = reqwest::blocking::ClientBuilder::new()
             .user_agent(USER_AGENT)
             .build()?
-            .post(TOKEN_URL)
+            .post(&self.endpoints.token_url)
             .header(reqwest::header::ACCEPT, "application/json")
             .header(
                 reqwest::header::CONTENT_TYPE,
@@ -225,10 +354,10 @@ impl OAuth {
 
     /// Refresh the token (async)
     pub async fn get_access_token_async(&mut self) -> Result<(), AuthError> {
-        let request = reqwest::ClientBuilder::new()
-            .user_agent(USER_AGENT)
-            .build()?
-            .post(TOKEN_URL)
+        let request = self
+            .endpoints
+            .client()?
+            .post(&self.endpoints.token_url)
             .header(reqwest::header::ACCEPT, "application/json")
             .header(
                 reqwest::header::CONTENT_TYPE,
@@ -253,8 +382,17 @@ impl OAuth {
                 Err(e) => Err(AuthError::InvalidResponse(e.to_string())),
             }
         } else {
-            println!("{}", body);
-            Err(AuthError::UnknownError)
+            #[derive(Deserialize)]
+            struct TokenError {
+                error: String,
+                message: Option<String>,
+            }
+
+            warn!("token request failed: {}", body);
+            Err(match serde_json::from_str::<TokenError>(body) {
+                Ok(e) => AuthError::Rejected(e.message.unwrap_or(e.error)),
+                Err(_) => AuthError::UnknownError,
+            })
         }
     }
 
@@ -268,7 +406,7 @@ impl OAuth {
         struct TokenRequest {
             client_id: String,
             #[serde(skip_serializing_if = "Option::is_none")]
-            client_secret: Option<String>,
+            client_secret: Option<Secret>,
             code: String,
             code_verifier: String,
             grant_type: String,
@@ -300,7 +438,7 @@ impl OAuth {
             let request = reqwest::blocking::ClientBuilder::new()
                 .user_agent(USER_AGENT)
                 .build()?
-                .post(TOKEN_URL)
+                .post(&self.endpoints.token_url)
                 .header(reqwest::header::ACCEPT, "application/json")
                 .header(
                     reqwest::header::CONTENT_TYPE,
@@ -319,78 +457,106 @@ impl OAuth {
 
     /// Refresh the token (async)
     pub async fn refresh_async(&mut self) -> Result<(), AuthError> {
-        if self.token().unwrap().expired() {
-            let request = reqwest::ClientBuilder::new()
-                .user_agent(USER_AGENT)
-                .build()?
-                .post(TOKEN_URL)
-                .header(reqwest::header::ACCEPT, "application/json")
-                .header(
-                    reqwest::header::CONTENT_TYPE,
-                    "application/x-www-form-urlencoded",
-                )
-                .body(self.get_token_refresh_query_string()?);
-
-            let response = request.send().await?;
-            let success = response.status().is_success();
-            let body = response.text().await?;
-            self.handle_response(success, &body)
+        if self.token().ok_or(AuthError::TokenNotPresent)?.expired() {
+            self.request_refresh_async().await
         } else {
             Ok(())
         }
     }
 
-    pub async fn get_auth_async(config: AuthConfig) -> Result<OAuth, AuthError> {
-        if let Some(mut auth) = cache::load_cached_auth() {
-            auth.refresh_async().await?;
-            Ok(auth)
-        } else {
-            let auth = OAuth::new(
-                config.get_user_agent(),
-                config.client_id.clone(),
-                None,
-                config.get_redirect_uri(),
-            );
+    /// Asks for a new token right away, whether the current one expired or not
+    pub async fn request_refresh_async(&mut self) -> Result<(), AuthError> {
+        let request = self
+            .endpoints
+            .client()?
+            .post(&self.endpoints.token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                "application/x-www-form-urlencoded",
+            )
+            .body(self.get_token_refresh_query_string()?);
 
-            let url = auth.get_auth_url();
+        let response = request.send().await?;
+        let success = response.status().is_success();
+        let body = response.text().await?;
+        self.handle_response(success, &body)
+    }
 
-            if test_oauth_url(&url).await {
-                open(&url).unwrap();
-            } else {
-                println!("==> Please verify your creds and retry.");
-                println!("==> Note: cached auth file will be deleted.");
-                // delete oauth cache file
-                cache::delete_cached_auth();
-                // If the URL cannot be opened, return an error
-                return Err(AuthError::InvalidResponse("Failed to open URL".to_string()));
+    pub async fn get_auth_async(
+        config: AuthConfig,
+        endpoints: AuthEndpoints,
+        flow: LoginFlow,
+    ) -> Result<OAuth, AuthError> {
+        if let Some(auth) = cache::load_cached_auth(&config) {
+            let mut manager = manager::TokenManager::new(auth.with_endpoints(endpoints.clone()));
+            match manager.ensure_fresh().await {
+                Ok(()) => return Ok(manager.into_oauth()),
+                Err(e) if e.needs_login() => {
+                    println!("==> Session expired ({}), logging in again", e);
+                }
+                Err(e) => return Err(e),
             }
-            let mut auth = redirect::Server::new(config.get_user_agent(), auth)
-                .go()
-                .unwrap();
+        }
+        Self::login(&config, endpoints, flow).await
+    }
 
-            auth.get_access_token_async().await.unwrap();
+    /// Runs a new PKCE flow whatever the token cache holds, caching the new token
+    pub async fn login(
+        config: &AuthConfig,
+        endpoints: AuthEndpoints,
+        flow: LoginFlow,
+    ) -> Result<OAuth, AuthError> {
+        let auth = OAuth::for_login(config, endpoints);
 
-            cache::cache_auth(&auth);
+        let url = auth.get_auth_url();
 
-            Ok(auth)
+        if !test_oauth_url(&url).await {
+            println!("==> Please verify your creds and retry.");
+            println!("==> Note: cached auth file will be deleted.");
+            // delete oauth cache file
+            if let Err(e) = cache::delete_cached_auth(config.profile.as_deref()) {
+                warn!("could not delete the cached auth: {}", e);
+            }
+            // If the URL cannot be opened, return an error
+            return Err(AuthError::InvalidResponse("Failed to open URL".to_string()));
+        }
+        let mut auth = match flow {
+            LoginFlow::Browser => {
+                open_or_print(&url);
+                redirect::Server::new(config.get_user_agent(), auth)
+                    .timeout(config.get_login_timeout())
+                    .go()?
+            }
+            LoginFlow::Manual => read_pasted_redirect(auth, &url)?,
+        };
+
+        auth.get_access_token_async().await?;
+
+        if let Err(e) = cache::cache_auth(&auth) {
+            warn!("could not cache the token: {}", e);
         }
+
+        Ok(auth)
+    }
+
+    /// The cached auth of the profile of `config` as it is, without refreshing
+    pub fn load_cached(endpoints: AuthEndpoints, config: &AuthConfig) -> Result<OAuth, AuthError> {
+        cache::load_cached_auth(config)
+            .map(|auth| auth.with_endpoints(endpoints))
+            .ok_or(AuthError::AuthNotPresent)
     }
 
     // for tests
     pub fn get_auth(config: AuthConfig) -> Result<OAuth, AuthError> {
-        if let Some(mut auth) = cache::load_cached_auth() {
+        if let Some(mut auth) = cache::load_cached_auth(&config) {
             auth.refresh()?;
             Ok(auth)
         } else {
-            let auth = OAuth::new(
-                config.get_user_agent(),
-                config.client_id.clone(),
-                None,
-                config.get_redirect_uri(),
-            );
+            let auth = OAuth::for_login(&config, AuthEndpoints::default());
 
             let url = auth.get_auth_url();
-            open(&url).unwrap();
+            open_or_print(&url);
 
             let mut auth = redirect::Server::new(config.get_user_agent(), auth)
                 .go()
@@ -398,13 +564,39 @@ impl OAuth {
 
             auth.get_access_token().unwrap();
 
-            cache::cache_auth(&auth);
+            cache::cache_auth(&auth).unwrap();
 
             Ok(auth)
         }
     }
 }
 
+/// Prints the login url and reads the redirect back from stdin until it checks out
+fn read_pasted_redirect(mut auth: OAuth, url: &Url) -> Result<OAuth, AuthError> {
+    println!(
+        "==> Open this url in any browser and allow access:\n\n{}\n",
+        url
+    );
+    println!("==> The page you land on won't load, that's fine.");
+    println!("==> Paste its url from the address bar (or only the code):");
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = std::io::stdin()
+            .read_line(&mut line)
+            .map_err(|e| AuthError::InvalidResponse(e.to_string()))?;
+        if read == 0 {
+            return Err(AuthError::AuthNotPresent);
+        }
+        match auth.parse_pasted_redirect(&line) {
+            Ok(()) => return Ok(auth),
+            // pasting again won't help, the login has to start over
+            Err(e @ AuthError::Rejected(_)) => return Err(e),
+            Err(e) => println!("==> {}, please paste it again:", e),
+        }
+    }
+}
+
 pub async fn test_oauth_url(url: &Url) -> bool {
     let res = reqwest::ClientBuilder::new()
         .user_agent(USER_AGENT)
@@ -424,6 +616,18 @@ pub fn open(url: &Url) -> Result<(), Error> {
     webbrowser::open(url.as_ref())
 }
 
+/// Opens the login url, printing it when there is no browser to open it in,
+/// e.g. over ssh; the redirect still reaches the local server
+fn open_or_print(url: &Url) {
+    if let Err(e) = open(url) {
+        warn!("could not open the browser: {}", e);
+        println!(
+            "==> Could not open a browser, open this url to log in:\n\n{}\n",
+            url
+        );
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -433,12 +637,14 @@ pub mod tests {
     }
 
     #[test]
+    #[ignore = "logs in to MyAnimeList"]
     fn test_refresh_token() {
         let mut auth = get_auth();
         auth.refresh().unwrap();
         println!("{}", serde_json::to_string(&auth).unwrap());
     }
     #[test]
+    #[ignore = "logs in to MyAnimeList in a browser"]
     fn test_get_auth() {
         // Get config from file
         let config = AuthConfig::load().unwrap();
@@ -455,7 +661,7 @@ pub mod tests {
 
         // create and open url
         let url = auth.get_auth_url();
-        open(&url).unwrap();
+        open_or_print(&url);
 
         // wait for redirect
         let mut auth = redirect::Server::new(config.get_user_agent(), auth)
@@ -470,7 +676,112 @@ pub mod tests {
         auth.refresh().unwrap();
         println!("{}", serde_json::to_string(&auth).unwrap());
 
-        cache::cache_auth(&auth);
+        cache::cache_auth(&auth).unwrap();
+    }
+
+    #[test]
+    fn test_rejected_refresh_needs_login() {
+        let mut auth = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        let body = r#"{"error":"invalid_grant","message":"The refresh token is invalid."}"#;
+        let error = auth.handle_response(false, body).unwrap_err();
+        assert!(error.needs_login());
+        assert_eq!(
+            error.to_string(),
+            "MyAnimeList rejected the token: The refresh token is invalid."
+        );
+        assert!(!AuthError::NetworkTimeout.needs_login());
+    }
+
+    #[tokio::test]
+    async fn test_stalled_refresh_times_out() {
+        // takes the request, never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let token_url = format!("http://{}/token", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            std::future::pending::<()>().await
+        });
+
+        let mut auth = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        auth.endpoints = NetworkConfig {
+            token_url,
+            request_timeout_seconds: 1,
+            ..NetworkConfig::default()
+        }
+        .auth_endpoints();
+        auth.auth_code = Some("code".to_string());
+        auth.token = Some(TokenWrapper::new(Token {
+            token_type: "Bearer".to_string(),
+            expires_in: 0,
+            access_token: "old".to_string(),
+            refresh_token: "old".to_string(),
+        }));
+        assert!(matches!(
+            auth.request_refresh_async().await,
+            Err(AuthError::NetworkTimeout)
+        ));
+    }
+
+    #[test]
+    fn test_parse_pasted_redirect() {
+        let mut auth = OAuth::new("mal-cli", "client", None, "127.0.0.1:2006");
+        let state = auth.state.clone();
+        auth.parse_pasted_redirect(&format!(" 127.0.0.1:2006/?code=abc&state={}\n", state))
+            .unwrap();
+        assert_eq!(auth.auth_code.as_deref(), Some("abc"));
+
+        auth.parse_pasted_redirect(&format!("code=def&state={}", state))
+            .unwrap();
+        assert_eq!(auth.auth_code.as_deref(), Some("def"));
+
+        auth.parse_pasted_redirect("ghi").unwrap();
+        assert_eq!(auth.auth_code.as_deref(), Some("ghi"));
+
+        let mismatch = auth.parse_pasted_redirect("http://127.0.0.1:2006/?code=x&state=other");
+        assert!(matches!(mismatch, Err(AuthError::InvalidResponse(_))));
+        assert!(matches!(
+            auth.parse_pasted_redirect(" \n"),
+            Err(AuthError::InvalidResponse(_))
+        ));
+        assert_eq!(auth.auth_code.as_deref(), Some("ghi"));
+
+        let denied = auth
+            .parse_pasted_redirect(&format!(
+                "http://127.0.0.1:2006/?error=access_denied&error_description=no&state={}",
+                state
+            ))
+            .unwrap_err();
+        assert!(matches!(denied, AuthError::Rejected(_)));
+        assert!(denied.to_string().contains("access_denied (no)"));
+    }
+
+    #[test]
+    fn test_client_secret_is_sent_but_not_logged() {
+        let mut auth = OAuth::new("mal-cli", "client", Some("s3cret"), "127.0.0.1:2006");
+        auth.auth_code = Some("abc".to_string());
+        assert!(auth
+            .get_token_query_string()
+            .unwrap()
+            .contains("client_secret=s3cret"));
+        auth.token = Some(TokenWrapper::new(Token {
+            token_type: "Bearer".to_string(),
+            expires_in: 3600,
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+        }));
+        assert!(auth
+            .get_token_refresh_query_string()
+            .unwrap()
+            .contains("client_secret=s3cret"));
+        assert!(!format!("{:?}", auth).contains("s3cret"));
+    }
+
+    #[test]
+    fn test_state_is_new_for_every_login() {
+        let first = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        let second = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        assert_eq!(first.state.len(), STATE_LENGTH);
+        assert_ne!(first.state, second.state);
     }
 
     #[test]
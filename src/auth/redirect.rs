@@ -1,9 +1,25 @@
+use std::io::prelude::*;
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// How long to wait for the browser to come back when nothing else is set
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// how often the listener checks for a connection and the deadline
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// a connection that sends nothing (e.g. a browser preconnect) is dropped after this
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// the redirect is a GET without a body, anything bigger is not ours
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
 /// HTTP server on host system
 /// ex. 127.0.0.1:7878
-/// blocks until one request is recieved (auth redirect) and parses it to get the code
+/// serves requests until the auth redirect comes in, and parses it to get the code
 pub struct Server {
     auth: super::OAuth,
     app_name: String,
+    timeout: Duration,
 }
 
 /// Error type for server methods
@@ -13,6 +29,48 @@ pub enum ServerError {
     HTTParseError(httparse::Error),
     InvalidRequestURL(String),
     AuthError(super::AuthError),
+    /// MAL sent the user back with an error, e.g. `access_denied` when they declined
+    Denied {
+        error: String,
+        description: Option<String>,
+    },
+    /// the browser did not come back in time
+    Timeout(Duration),
+}
+
+impl std::fmt::Display for ServerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ServerError::IOError(e) => write!(f, "Redirect server error: {}", e),
+            ServerError::HTTParseError(e) => write!(f, "Invalid redirect request: {}", e),
+            ServerError::InvalidRequestURL(url) => write!(f, "Invalid redirect url: {}", url),
+            ServerError::AuthError(e) => e.fmt(f),
+            ServerError::Denied {
+                error,
+                description: Some(description),
+            } => write!(
+                f,
+                "MyAnimeList refused the login: {} ({})",
+                error, description
+            ),
+            ServerError::Denied { error, .. } => {
+                write!(f, "MyAnimeList refused the login: {}", error)
+            }
+            ServerError::Timeout(timeout) => {
+                write!(f, "Nobody logged in within {} seconds", timeout.as_secs())
+            }
+        }
+    }
+}
+
+impl From<ServerError> for super::AuthError {
+    fn from(e: ServerError) -> Self {
+        match e {
+            ServerError::AuthError(e) => e,
+            e @ ServerError::Denied { .. } => super::AuthError::Rejected(e.to_string()),
+            e => super::AuthError::InvalidResponse(e.to_string()),
+        }
+    }
 }
 
 impl From<std::io::Error> for ServerError {
@@ -33,77 +91,303 @@ impl From<super::AuthError> for ServerError {
     }
 }
 
+/// What a single request turned out to be
+enum Callback {
+    /// favicon, prefetch or anything else without an answer from MAL
+    Unrelated,
+    /// a redirect that does not belong to this login, e.g. from an old tab
+    Invalid(String),
+    Denied(ServerError),
+    Authorized,
+}
+
 impl Server {
     /// Create the server
     pub fn new<A: ToString>(app_name: A, auth: super::OAuth) -> Self {
         Server {
             auth,
             app_name: app_name.to_string(),
+            timeout: DEFAULT_TIMEOUT,
         }
     }
 
-    /// Run the server.
-    /// Blocks until it recieves exactly one response.
-    pub fn go(self) -> Result<super::OAuth, ServerError> {
-        use std::io::prelude::*;
-        use std::net::TcpListener;
+    /// Gives up waiting for the browser after `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
 
+    /// Run the server.
+    /// Blocks until the redirect with the code comes in, MAL reports an error or the timeout passes.
+    pub fn go(mut self) -> Result<super::OAuth, ServerError> {
         let listener = TcpListener::bind(&self.auth.redirect_url)?;
-        let mut socket_stream = listener.incoming().next().unwrap()?;
+        // polled, so the deadline is checked while nobody connects
+        listener.set_nonblocking(true)?;
+        let deadline = Instant::now() + self.timeout;
 
-        // read all bytes of the request
-        let mut request_bytes = Vec::new();
         loop {
-            const BUF_SIZE: usize = 4096;
-            let mut buf: [u8; BUF_SIZE] = [0; BUF_SIZE];
-            match socket_stream.read(&mut buf) {
-                Ok(val) => {
-                    if val > 0 {
-                        request_bytes.append(&mut Vec::from(&buf[0..val]));
-                        if val < BUF_SIZE {
-                            break;
-                        }
-                    } else {
-                        break;
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if Instant::now() >= deadline {
+                        return Err(ServerError::Timeout(self.timeout));
                     }
+                    std::thread::sleep(POLL_INTERVAL);
+                    continue;
                 }
-                Err(e) => return Err(ServerError::IOError(e)),
+                Err(e) => return Err(e.into()),
             };
+            match self.handle(stream) {
+                Ok(true) => return Ok(self.auth),
+                Ok(false) => {}
+                Err(e @ ServerError::Denied { .. }) => return Err(e),
+                // one broken connection must not end the login
+                Err(e) => warn!("ignoring a request to the redirect server: {}", e),
+            }
         }
+    }
 
-        let mut headers = [httparse::EMPTY_HEADER; 16];
-        let mut parsed_request = httparse::Request::new(&mut headers);
+    /// Answers one connection, true once it carried the code
+    fn handle(&mut self, mut stream: TcpStream) -> Result<bool, ServerError> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let request = read_request(&mut stream)?;
+        let callback = match self.parse(&request) {
+            Ok(callback) => callback,
+            Err(e) => {
+                respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    "Bad request",
+                    &e.to_string(),
+                )?;
+                return Err(e);
+            }
+        };
+
+        match callback {
+            Callback::Unrelated => {
+                respond(&mut stream, "404 Not Found", "Not found", "")?;
+                Ok(false)
+            }
+            Callback::Invalid(reason) => {
+                warn!("ignoring a redirect: {}", reason);
+                respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    &format!("{} login failed", self.app_name),
+                    &format!("{}. Start the login from {} again.", reason, self.app_name),
+                )?;
+                Ok(false)
+            }
+            Callback::Denied(e) => {
+                respond(
+                    &mut stream,
+                    "200 OK",
+                    &format!("{} was not authorized", self.app_name),
+                    &format!("{}. You can close this tab.", e),
+                )
+                .ok();
+                Err(e)
+            }
+            Callback::Authorized => {
+                respond(
+                    &mut stream,
+                    "200 OK",
+                    &format!("{} Authorized", self.app_name),
+                    &format!("You can close this tab and go back to {}.", self.app_name),
+                )
+                // the code is what matters, even when the browser went away
+                .ok();
+                Ok(true)
+            }
+        }
+    }
 
-        parsed_request.parse(&request_bytes)?;
+    fn parse(&mut self, request: &[u8]) -> Result<Callback, ServerError> {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut parsed_request = httparse::Request::new(&mut headers);
+        parsed_request.parse(request)?;
 
-        let raw_url = if let Some(path) = parsed_request.path {
-            format!("http://{}{}", self.auth.redirect_url, path)
-        } else {
+        let Some(path) = parsed_request.path else {
             return Err(ServerError::InvalidRequestURL("".to_string()));
         };
+        debug!("redirect server got {}", path);
+        let raw_url = format!("http://{}{}", self.auth.redirect_url, path);
+        let parsed_url =
+            url::Url::parse(&raw_url).map_err(|_| ServerError::InvalidRequestURL(raw_url))?;
 
-        let parsed_url = match url::Url::parse(&raw_url) {
-            Ok(url) => url,
-            Err(_) => return Err(ServerError::InvalidRequestURL(raw_url)),
+        let param = |name: &str| {
+            parsed_url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let (Some(query), true) = (
+            parsed_url.query(),
+            param("error").is_some() || param("code").is_some(),
+        ) else {
+            return Ok(Callback::Unrelated);
         };
+        // anyone can send a request here, only MAL knows the state of this login
+        if param("state").as_deref() != Some(self.auth.state.as_str()) {
+            return Ok(Callback::Invalid(
+                "The redirect is not from this login".to_string(),
+            ));
+        }
+        if let Some(error) = param("error") {
+            return Ok(Callback::Denied(ServerError::Denied {
+                error,
+                description: param("error_description").or_else(|| param("message")),
+            }));
+        }
+        match self.auth.parse_redirect_query_string(query) {
+            Ok(()) => Ok(Callback::Authorized),
+            Err(e) => Ok(Callback::Invalid(e.to_string())),
+        }
+    }
+}
 
-        let query = if let Some(query) = parsed_url.query() {
-            query
-        } else {
+/// Reads the request head, the redirect has no body
+fn read_request(stream: &mut TcpStream) -> Result<Vec<u8>, ServerError> {
+    let mut request = Vec::new();
+    let mut buf = [0; 4096];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+        if request.len() > MAX_REQUEST_SIZE {
             return Err(ServerError::InvalidRequestURL(
-                "No query string".to_string(),
+                "Request too large".to_string(),
             ));
+        }
+    }
+    Ok(request)
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    title: &str,
+    message: &str,
+) -> std::io::Result<()> {
+    let body = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title></head>\
+         <body style=\"font-family: sans-serif; text-align: center; margin-top: 4em\">\
+         <h1>{title}</h1><p>{message}</p></body></html>",
+        title = escape_html(title),
+        message = escape_html(message),
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+/// MAL's error description ends up in the page
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::OAuth;
+
+    /// A server on a free port, with the login it waits for
+    fn server() -> (Server, String, String) {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let address = format!("127.0.0.1:{}", port);
+        let auth = OAuth::new("mal-cli", "client", None, &address);
+        let state = auth.state.clone();
+        (Server::new("mal-cli", auth), address, state)
+    }
+
+    /// Sends a GET like a browser would, returning the response
+    fn get(address: &str, path: &str) -> String {
+        let start = Instant::now();
+        let mut stream = loop {
+            match TcpStream::connect(address) {
+                Ok(stream) => break stream,
+                Err(_) if start.elapsed() < Duration::from_secs(5) => {
+                    std::thread::sleep(Duration::from_millis(10))
+                }
+                Err(e) => panic!("{}", e),
+            }
         };
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: */*\r\n\r\n",
+            path, address
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_favicon_then_callback() {
+        let (server, address, state) = server();
+        let handle = std::thread::spawn(move || server.go());
 
-        let mut ret_auth = self.auth;
+        let favicon = get(&address, "/favicon.ico");
+        assert!(favicon.starts_with("HTTP/1.1 404"));
+        // a redirect of an older login is not the one we wait for
+        let stale = get(&address, "/?code=old&state=other");
+        assert!(stale.starts_with("HTTP/1.1 400"));
+        // nor is an error without the state, it does not end the login
+        let stray = get(&address, "/?error=access_denied");
+        assert!(stray.starts_with("HTTP/1.1 400"));
+        let callback = get(&address, &format!("/?code=abc&state={}", state));
+        assert!(callback.starts_with("HTTP/1.1 200 OK"));
+        assert!(callback.contains("Content-Type: text/html"));
+        let body = callback.split("\r\n\r\n").nth(1).unwrap();
+        assert!(callback.contains(&format!("Content-Length: {}", body.len())));
 
-        ret_auth.parse_redirect_query_string(query)?;
+        let auth = handle.join().unwrap().unwrap();
+        assert_eq!(auth.auth_code.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_access_denied() {
+        let (server, address, state) = server();
+        let handle = std::thread::spawn(move || server.go());
 
-        // return a minimal http response to the browser
-        let r = format!("HTTP/1.1 200 OK\r\n\r\n<html><head><title>{} Authorized</title></head><body>{} Authorized</body></html>", self.app_name, self.app_name);
-        socket_stream.write_all(r.as_bytes())?;
-        socket_stream.flush()?;
+        let response = get(
+            &address,
+            &format!(
+                "/?error=access_denied&error_description=<b>no</b>&state={}",
+                state
+            ),
+        );
+        assert!(response.contains("&lt;b&gt;no&lt;/b&gt;"));
+        let error = handle.join().unwrap().unwrap_err();
+        assert!(matches!(
+            &error,
+            ServerError::Denied { error, .. } if error == "access_denied"
+        ));
+        assert!(super::super::AuthError::from(error).needs_login());
+    }
 
-        Ok(ret_auth)
+    #[test]
+    fn test_timeout() {
+        let (server, _, _) = server();
+        let error = server.timeout(Duration::from_millis(100)).go().unwrap_err();
+        assert!(matches!(error, ServerError::Timeout(_)));
     }
 }
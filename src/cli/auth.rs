@@ -0,0 +1,272 @@
+use crate::api::{self, ApiClient, GetUserInformationQuery};
+use crate::auth::{cache, manager::TokenManager, token::TokenWrapper, AuthError, LoginFlow, OAuth};
+use crate::config::{
+    app_config::AppConfig,
+    oauth_config::{check_profile_name, AuthConfig},
+    ConfigError,
+};
+use clap::Subcommand;
+use std::process::ExitCode;
+
+/// Everything went fine
+pub const EXIT_OK: u8 = 0;
+/// Not logged in, or MAL wants a new login
+pub const EXIT_LOGIN_NEEDED: u8 = 1;
+/// Network, config and token cache errors; 2 is taken by clap for bad usage
+pub const EXIT_ERROR: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Subcommand)]
+pub enum AuthCommand {
+    /// Show the user, when the token expires and where the config lives
+    Status,
+    /// Log in again, even when the saved token is still valid
+    Login,
+    /// Delete the token cache of the profile
+    Logout,
+    /// Get a new access token with the refresh token
+    Refresh,
+}
+
+/// Why an auth command failed, picks the exit code
+#[derive(Debug, PartialEq)]
+enum Failure {
+    LoginNeeded(String),
+    Error(String),
+}
+
+impl From<AuthError> for Failure {
+    fn from(e: AuthError) -> Self {
+        if e.needs_login() {
+            Failure::LoginNeeded(e.to_string())
+        } else {
+            Failure::Error(e.to_string())
+        }
+    }
+}
+
+impl From<api::Error> for Failure {
+    fn from(e: api::Error) -> Self {
+        if e.needs_login() {
+            Failure::LoginNeeded(e.to_string())
+        } else {
+            Failure::Error(e.to_string())
+        }
+    }
+}
+
+impl From<ConfigError> for Failure {
+    fn from(e: ConfigError) -> Self {
+        Failure::Error(e.to_string())
+    }
+}
+
+fn exit_code(result: &Result<(), Failure>) -> u8 {
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(Failure::LoginNeeded(_)) => EXIT_LOGIN_NEEDED,
+        Err(Failure::Error(_)) => EXIT_ERROR,
+    }
+}
+
+/// Runs `mal auth <command>` for a profile, `None` being the default account
+pub async fn run(command: AuthCommand, profile: Option<&str>, flow: LoginFlow) -> ExitCode {
+    let result = run_command(command, profile, flow).await;
+    match &result {
+        Ok(()) => {}
+        Err(Failure::LoginNeeded(e)) => {
+            eprintln!("{}", e);
+            eprintln!("Log in with `mal{} auth login`", profile_flag(profile));
+        }
+        Err(Failure::Error(e)) => eprintln!("error: {}", e),
+    }
+    ExitCode::from(exit_code(&result))
+}
+
+async fn run_command(
+    command: AuthCommand,
+    profile: Option<&str>,
+    flow: LoginFlow,
+) -> Result<(), Failure> {
+    if let Some(name) = profile {
+        check_profile_name(name)?;
+    }
+    match command {
+        AuthCommand::Status => status(profile).await,
+        AuthCommand::Login => login(profile, flow).await,
+        AuthCommand::Logout => logout(profile),
+        AuthCommand::Refresh => refresh(profile).await,
+    }
+}
+
+fn profile_flag(profile: Option<&str>) -> String {
+    profile.map_or(String::new(), |name| format!(" --profile {}", name))
+}
+
+async fn status(profile: Option<&str>) -> Result<(), Failure> {
+    let app_config = AppConfig::load()?;
+    let paths = AuthConfig::get_paths()?;
+    println!("Profile:      {}", profile.unwrap_or("default"));
+    println!("Config:       {}", paths.config_file_path.display());
+    println!(
+        "Token cache:  {}",
+        paths.token_cache_path(profile).display()
+    );
+
+    let auth = cached_auth(&app_config, profile)?;
+    let token = auth.token().ok_or(AuthError::TokenNotPresent)?;
+    println!("Token:        {}", describe_expiry(token));
+    let auth = refresh_expired(TokenManager::new(auth)).await?;
+
+    let client = ApiClient::new(&app_config.network).map_err(|e| Failure::Error(e.to_string()))?;
+    let query = GetUserInformationQuery { fields: None };
+    let user = api::get_my_user_information("@me", &query, &client, &auth).await?;
+    println!("User:         {} (id {})", user.name, user.id);
+    Ok(())
+}
+
+async fn login(profile: Option<&str>, flow: LoginFlow) -> Result<(), Failure> {
+    let app_config = AppConfig::load()?;
+    let config = AuthConfig::load()?.for_profile(profile)?;
+    let auth = OAuth::login(&config, app_config.network.auth_endpoints(), flow).await?;
+    println!("Logged in, {}", token_summary(&auth));
+    Ok(())
+}
+
+fn logout(profile: Option<&str>) -> Result<(), Failure> {
+    let path = AuthConfig::get_paths()?.token_cache_path(profile);
+    // logging out twice is fine, scripts don't have to check first
+    if path.exists() {
+        cache::delete_cached_auth(profile)
+            .map_err(|e| Failure::Error(format!("could not remove {}: {}", path.display(), e)))?;
+        println!("Logged out, removed {}", path.display());
+    } else {
+        println!("Not logged in");
+    }
+    Ok(())
+}
+
+async fn refresh(profile: Option<&str>) -> Result<(), Failure> {
+    let app_config = AppConfig::load()?;
+    let mut tokens = TokenManager::new(cached_auth(&app_config, profile)?);
+    tokens.refresh().await?;
+    println!("Refreshed, {}", token_summary(tokens.oauth()));
+    Ok(())
+}
+
+/// Refreshes an expired access token, only a refresh token that is gone or
+/// rejected needs a new login
+async fn refresh_expired(mut tokens: TokenManager) -> Result<OAuth, Failure> {
+    if tokens.oauth().token().is_some_and(TokenWrapper::expired) {
+        tokens.refresh().await?;
+        println!("Refreshed:    {}", token_summary(tokens.oauth()));
+    }
+    Ok(tokens.into_oauth())
+}
+
+fn cached_auth(app_config: &AppConfig, profile: Option<&str>) -> Result<OAuth, Failure> {
+    // a refresh needs the client secret of oauth2.yml, when there is one
+    let config = AuthConfig::load_existing()?
+        .unwrap_or_default()
+        .for_profile(profile)?;
+    OAuth::load_cached(app_config.network.auth_endpoints(), &config)
+        .map_err(|_| Failure::LoginNeeded("Not logged in".to_string()))
+}
+
+fn token_summary(auth: &OAuth) -> String {
+    auth.token()
+        .map_or("no token received".to_string(), describe_expiry)
+}
+
+/// e.g. `expires 2024-05-01 12:00 (in 30 days)`
+fn describe_expiry(token: &TokenWrapper) -> String {
+    match (token.expire_time(), token.expires_in_secs()) {
+        (Some(time), Some(secs)) => {
+            let time = chrono::DateTime::<chrono::Local>::from(time);
+            format!(
+                "expires {} (in {})",
+                time.format("%Y-%m-%d %H:%M"),
+                describe_duration(secs)
+            )
+        }
+        _ => "expired".to_string(),
+    }
+}
+
+fn describe_duration(secs: u64) -> String {
+    let (value, unit) = match secs {
+        s if s >= 2 * 86400 => (s / 86400, "days"),
+        s if s >= 2 * 3600 => (s / 3600, "hours"),
+        s if s >= 2 * 60 => (s / 60, "minutes"),
+        s => (s, "seconds"),
+    };
+    format!("{} {}", value, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ReplayServer;
+    use crate::auth::token::Token;
+    use crate::config::app_config::NetworkConfig;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(exit_code(&Ok(())), EXIT_OK);
+        let rejected = Failure::from(AuthError::Rejected("invalid_grant".to_string()));
+        assert_eq!(exit_code(&Err(rejected)), EXIT_LOGIN_NEEDED);
+        let timeout = Failure::from(AuthError::NetworkTimeout);
+        assert_eq!(exit_code(&Err(timeout)), EXIT_ERROR);
+        let unauthorized = Failure::from(api::Error::NoAuth);
+        assert_eq!(exit_code(&Err(unauthorized)), EXIT_LOGIN_NEEDED);
+    }
+
+    #[test]
+    fn test_describe_expiry() {
+        let mut token = TokenWrapper::new(Token {
+            token_type: "Bearer".to_string(),
+            // a minute to spare, the clock may tick during the test
+            expires_in: 3 * 86400 + 60,
+            access_token: "a".to_string(),
+            refresh_token: "r".to_string(),
+        });
+        assert!(describe_expiry(&token).ends_with("(in 3 days)"));
+        token.generate_time -= 3 * 86400 + 60;
+        assert_eq!(describe_expiry(&token), "expired");
+        assert_eq!(describe_duration(150), "2 minutes");
+        assert_eq!(describe_duration(30), "30 seconds");
+    }
+
+    fn expired_auth(token_url: &str) -> OAuth {
+        let mut auth = OAuth::new("mal-cli", "client", None, "127.0.0.1");
+        auth.endpoints.token_url = token_url.to_string();
+        auth.auth_code = Some("code".to_string());
+        let mut token = TokenWrapper::new(Token {
+            token_type: "Bearer".to_string(),
+            expires_in: 3600,
+            access_token: "old".to_string(),
+            refresh_token: "old".to_string(),
+        });
+        token.generate_time -= 3600;
+        auth.token = Some(token);
+        auth
+    }
+
+    #[tokio::test]
+    async fn test_status_refreshes_expired_token() {
+        let dir = std::env::temp_dir().join(format!("mal-status-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = ReplayServer::bind(&dir, 0).await.unwrap();
+        let config = server.network_config(&NetworkConfig::default());
+        tokio::spawn(server.run());
+
+        let tokens = TokenManager::new(expired_auth(&config.token_url)).persist(false);
+        let auth = refresh_expired(tokens).await.unwrap();
+        assert_eq!(auth.token().unwrap().token.access_token, "replay");
+        std::fs::remove_dir_all(&dir).ok();
+
+        // not being able to refresh is no reason to log in again
+        let tokens = TokenManager::new(expired_auth("http://127.0.0.1:1/token")).persist(false);
+        let result = refresh_expired(tokens).await.map(|_| ());
+        assert_eq!(exit_code(&result), EXIT_ERROR);
+    }
+}
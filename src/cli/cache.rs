@@ -0,0 +1,85 @@
+use super::auth::{EXIT_ERROR, EXIT_OK};
+use crate::api::ImageCache;
+use crate::config::app_config::AppConfig;
+use clap::Subcommand;
+use std::process::ExitCode;
+
+#[derive(Clone, Copy, Debug, PartialEq, Subcommand)]
+pub enum CacheCommand {
+    /// Delete every cached cover image
+    Clear,
+    /// Show how many images are cached and how much space they take
+    Stats,
+}
+
+/// Runs `mal cache <command>`
+pub fn run(command: CacheCommand) -> ExitCode {
+    let result = AppConfig::load()
+        .map_err(|e| e.to_string())
+        .and_then(|config| match command {
+            // only clearing opens the cache, opening it prunes files of a running app
+            CacheCommand::Clear => config
+                .image_cache()
+                .map_err(|e| e.to_string())
+                .and_then(|cache| clear(&cache)),
+            CacheCommand::Stats => {
+                stats(&config);
+                Ok(())
+            }
+        });
+    match result {
+        Ok(()) => ExitCode::from(EXIT_OK),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn clear(cache: &ImageCache) -> Result<(), String> {
+    let removed = cache.clear().map_err(|e| e.to_string())?;
+    println!("Removed {} images from {}", removed, cache.dir().display());
+    Ok(())
+}
+
+fn stats(config: &AppConfig) {
+    let stats = config.image_cache_stats();
+    println!(
+        "Directory:  {}",
+        config.paths.picture_cache_dir_path.display()
+    );
+    println!("Images:     {} of {}", stats.images, stats.max_images);
+    println!(
+        "Size:       {} of {}",
+        describe_size(stats.bytes),
+        describe_size(stats.max_bytes)
+    );
+}
+
+/// e.g. `1.5 MB`
+fn describe_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_size() {
+        assert_eq!(describe_size(512), "512 B");
+        assert_eq!(describe_size(1536), "1.5 KB");
+        assert_eq!(describe_size(50 * 1024 * 1024), "50.0 MB");
+    }
+}
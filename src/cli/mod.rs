@@ -0,0 +1,309 @@
+use crate::auth::LoginFlow;
+use crate::config::oauth_config::AuthConfig;
+use clap::{Parser, Subcommand};
+use figlet_rs::FIGfont;
+use std::path::PathBuf;
+
+/// `mal auth`
+pub mod auth;
+pub use auth::AuthCommand;
+/// `mal cache`
+pub mod cache;
+pub use cache::CacheCommand;
+
+#[derive(Debug, Parser)]
+#[command(name = "mal", version, about = "A TUI client for myanimelist.net", long_about = None)]
+struct Cli {
+    /// Show extra information about the tool
+    #[arg(short = 'i', long = "info", action = clap::ArgAction::SetTrue)]
+    info: bool,
+    /// Show configuration file structure and all available options
+    #[arg(short = 'c', long = "config", action = clap::ArgAction::SetTrue)]
+    config: bool,
+    /// Browse without a connection, using only cached responses
+    #[arg(long = "offline", action = clap::ArgAction::SetTrue)]
+    offline: bool,
+    /// Log in without a local browser: print the login url and paste the redirect url back
+    #[arg(long = "manual-auth", global = true, action = clap::ArgAction::SetTrue)]
+    manual_auth: bool,
+    /// Use the account saved under NAME instead of the default one
+    #[arg(long = "profile", value_name = "NAME", global = true)]
+    profile: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Use the app as usual while saving every api response to DIR
+    Record {
+        /// Fixtures directory, created if missing
+        dir: PathBuf,
+    },
+    /// Run the app against responses recorded with `mal record`
+    Replay {
+        /// Fixtures directory written by `mal record`
+        dir: PathBuf,
+        /// Port of the local replay server, a free one by default
+        #[arg(short, long, default_value_t = 0)]
+        port: u16,
+        /// Only run the server, e.g. to point another instance at it with MAL_API_URL
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        serve: bool,
+    },
+    /// Check, renew or drop the saved login, e.g. from scripts
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommand,
+    },
+    /// Look at or empty the cover image cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+/// How the app should run, picked from the command line
+#[derive(Debug)]
+pub enum Mode {
+    /// Talk to MyAnimeList
+    Live,
+    /// Only use cached responses
+    Offline,
+    /// Talk to MyAnimeList and save the responses
+    Record(PathBuf),
+    /// Talk to a local server replaying saved responses
+    Replay {
+        dir: PathBuf,
+        port: u16,
+        serve: bool,
+    },
+    /// Manage the login and exit, see [`AuthCommand`]
+    Auth(AuthCommand),
+    /// Manage the image cache and exit, see [`CacheCommand`]
+    Cache(CacheCommand),
+}
+
+/// Everything the command line decides about a run
+#[derive(Debug)]
+pub struct Options {
+    pub mode: Mode,
+    pub login_flow: LoginFlow,
+    /// `None` is the default account
+    pub profile: Option<String>,
+}
+
+/// Handles the informational flags, returns `None` when there is nothing left to run
+pub fn handle_args() -> Option<Options> {
+    let cli = Cli::parse();
+
+    if cli.info {
+        print_info();
+        return None;
+    } else if cli.config {
+        print_config_structure();
+        print_auth_config();
+        return None;
+    }
+    let mode = match cli.command {
+        None if cli.offline => Mode::Offline,
+        None => Mode::Live,
+        Some(Command::Record { dir }) => Mode::Record(dir),
+        Some(Command::Replay { dir, port, serve }) => Mode::Replay { dir, port, serve },
+        Some(Command::Auth { command }) => Mode::Auth(command),
+        Some(Command::Cache { command }) => Mode::Cache(command),
+    };
+    let login_flow = if cli.manual_auth {
+        LoginFlow::Manual
+    } else {
+        LoginFlow::Browser
+    };
+    Some(Options {
+        mode,
+        login_flow,
+        profile: cli.profile,
+    })
+}
+
+fn print_info() {
+    let standard_font = FIGfont::standard().unwrap();
+    let figlet = standard_font.convert("mal-cli");
+    let fig_string = figlet.unwrap().to_string();
+    println!(
+        r#"
+{fig_string}
+
+FILES:
+    - OAuth2 tokens:       $HOME/.config/mal-cli/oauth2.yml
+    - Cache data:          $HOME/.cache/mal-cli/
+    - Configuration file:  $HOME/.config/mal-cli/config.yml
+
+OFFLINE:
+    - mal --offline        Browse your lists and pages you opened before, from the cache
+
+PROFILES:
+    - mal --profile <NAME> Use another MyAnimeList account, with its own token cache
+                           and optionally its own client_id (see `profiles` in oauth2.yml)
+
+AUTH (exit codes: 0 ok, 1 login needed, 2 bad usage, 3 other errors):
+    - mal auth status      Shows the user, when the token expires and the config paths
+    - mal auth login       Logs in again, even with a valid token
+    - mal auth logout      Deletes the token cache of the profile
+    - mal auth refresh     Gets a new access token with the refresh token
+
+LOGIN OVER SSH:
+    - mal --manual-auth    Prints the login url instead of opening a browser; open it
+                           anywhere, then paste the url you land on (or its code) back
+
+CACHE:
+    - mal cache stats      Shows how many cover images are cached and their size
+    - mal cache clear      Deletes every cached cover image
+
+RECORD / REPLAY:
+    - mal record <DIR>     Use the app as usual, every api response is saved to DIR
+    - mal replay <DIR>     Run the app offline against the responses saved in DIR
+                           (add --serve to only start the server and print its urls)
+
+NOTE:
+    - Use GPU-enhanced terminals, otherwise the images won't be rendered
+    - The configuration file is optional. If it does not exist, the application will create a default one.
+    - The cache directory is used to store images
+"#
+    );
+}
+
+/// The current oauth2.yml, never showing the secrets
+fn print_auth_config() {
+    match AuthConfig::load_existing() {
+        Ok(Some(config)) => {
+            println!("CURRENT OAUTH2 SETTINGS (secrets redacted):");
+            println!("==========================================");
+            match serde_yaml::to_string(&config.redacted()) {
+                Ok(yaml) => println!("{}", yaml),
+                Err(e) => println!("could not show oauth2.yml: {}", e),
+            }
+        }
+        Ok(None) => println!("No oauth2.yml yet, it is created on the first run"),
+        Err(e) => println!("could not read oauth2.yml: {}", e),
+    }
+}
+
+fn print_config_structure() {
+    println!(
+        r#"
+
+CONFIGURATION KEYS:
+===================
+
+KEYBINDINGS:
+  keys:
+    help: '?'                    # Show help menu
+    back: 'q'                    # Go back/quit current view
+    search: '/'                  # Open search
+    toggle: 's'                  # Toggle between anime/manga or switch states
+    next_state: Ctrl+p           # Navigate to next state/page
+    open_popup: 'r'              # Open rating/status popup
+    next_page: 'n'               # Next page of results (loads more when needed)
+    previous_page: 'p'           # Previous page of results
+    previous_season: '['         # Seasonal: go to the previous season
+    next_season: ']'             # Seasonal: go to the next season
+    current_season: 'c'          # Seasonal: jump back to the current season
+    filter: 'f'                  # User lists: filter by type, score, genre, year or tag
+
+THEME COLORS:
+  theme:
+    mal_color: '#2E51A2'         # MyAnimeList brand color (blue)
+    active: Cyan                 # Color for active/focused elements
+    banner: LightCyan            # Color for banners and titles
+    hovered: Magenta             # Color for hovered elements
+    text: White                  # Default text color
+    selected: LightCyan          # Color for selected items
+    error_border: Red            # Border color for error dialogs
+    error_text: LightRed         # Text color for error messages
+    inactive: Gray               # Color for inactive/unfocused elements
+    status_completed: Green      # Color for completed anime/manga
+    status_dropped: Gray         # Color for dropped items
+    status_on_hold: Yellow       # Color for on-hold items
+    status_watching: Blue        # Color for currently watching/reading
+    status_plan_to_watch: Cyan   # Color for plan-to-watch/read items
+    status_other: White          # Color for other status types
+
+APPLICATION BEHAVIOR:
+  behavior:
+    tick_rate_milliseconds: 500  # UI refresh rate (lower = more responsive)
+    show_logger: false           # Show debug logger window
+
+CONTENT SETTINGS:
+  nsfw: false                    # Show NSFW (18+) content
+  title_language: English       # Preferred title language (English/Japanese)
+  manga_display_type: Both      # Show volumes, chapters, or both (Vol/Ch/Both)
+
+RANKING TYPES:
+  top_three_anime_types:         # Anime ranking types to show in top 3
+    - airing                     # Currently airing anime
+    - all                        # All-time rankings
+    - upcoming                   # Upcoming anime
+    - movie                      # Movie rankings
+    - special                    # Special episodes
+    - ova                        # Original Video Animation
+    - tv                         # TV series
+    - popularity                 # Most popular
+    - favorite                   # Most favorited
+
+  top_three_manga_types:         # Manga ranking types to show in top 3
+    - all                        # All-time rankings
+    - manga                      # Manga only
+    - novels                     # Light novels
+    - oneshots                   # One-shot manga
+    - doujinshi                  # Self-published works
+    - manhwa                     # Korean comics
+    - manhua                     # Chinese comics
+    - bypopularity               # Most popular
+    - favorite                   # Most favorited
+
+PERFORMANCE SETTINGS:
+  navigation_stack_limit: 15     # Max number of pages to keep in history
+  search_limit: 30               # Max search results per page
+  max_cached_images: 200         # Max images to cache for faster loading
+  max_image_cache_mb: 50         # Max size of the image cache, least recently used go first
+  show_thumbnails: true          # Posters inside result and list cards, when the terminal can draw them
+
+NETWORK SETTINGS:
+  network:
+    connect_timeout_seconds: 10        # Give up connecting after this long
+    request_timeout_seconds: 30        # Give up on a whole request after this long
+    max_retries: 3                     # Retries on rate limits, server errors and timeouts
+    retry_base_delay_milliseconds: 500 # First retry delay, doubled on every retry
+    retry_max_delay_milliseconds: 8000 # Upper bound for the retry delay
+    rate_limit_burst: 5                # Requests sent at once before throttling
+    rate_limit_per_second: 2.0         # Sustained requests per second
+    api_url: https://api.myanimelist.net/v2                    # or MAL_API_URL
+    authorize_url: https://myanimelist.net/v1/oauth2/authorize # or MAL_AUTHORIZE_URL
+    token_url: https://myanimelist.net/v1/oauth2/token         # or MAL_TOKEN_URL
+
+RESPONSE CACHE:
+  response_cache:
+    enabled: true                # Keep api responses in $HOME/.cache/mal-cli/responses
+    rankings_ttl_minutes: 360    # Rankings, seasonal and suggestions
+    details_ttl_minutes: 1440    # Anime, manga and forum topic pages
+    other_ttl_minutes: 60        # Searches; your lists are always refreshed
+    max_age_days: 30             # Drop responses older than this, even offline
+    max_size_mb: 50              # Drop the oldest responses past this size
+
+OAUTH2 SETTINGS ($HOME/.config/mal-cli/oauth2.yml):
+  client_id: <32 hex chars>      # From https://myanimelist.net/apiconfig
+  client_secret: <secret>        # Only for "web" apps, leave it out otherwise
+  user_agent: mal-cli
+  port: 2006                     # Port of the redirect uri http://127.0.0.1:<port>
+  login_timeout_seconds: 300     # How long the login waits for the browser
+  profiles:                      # Other accounts, used with --profile NAME
+    work:
+      client_id: <32 hex chars>  # Optional, with its own client_secret if needed
+
+EXAMPLE CONFIG FILE:
+====================
+Copy the example configuration from: config.example.yml
+Location: $HOME/.config/mal-cli/config.yml
+"#
+    );
+}
@@ -1,11 +1,16 @@
 use std::{
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use super::*;
 use crate::{
-    api::model::{AnimeRankingType, MangaRankingType},
+    api::{
+        model::{AnimeRankingType, MangaRankingType},
+        ImageCache, ImageCacheStats, API_URL,
+    },
+    auth::{AuthEndpoints, AUTHORIZE_URL, TOKEN_URL},
     event::key::Key,
 };
 use log::LevelFilter;
@@ -28,7 +33,18 @@ pub struct AppConfig {
     pub navigation_stack_limit: u32,
     pub search_limit: u64,
     pub log_level: LevelFilter,
+    #[serde(default = "default_max_cached_images")]
     pub max_cached_images: u16,
+    /// size budget of the image cache, next to `max_cached_images`
+    #[serde(default = "default_max_image_cache_mb")]
+    pub max_image_cache_mb: u64,
+    /// posters inside the result and list cards, off when pictures can't be drawn
+    #[serde(default = "default_show_thumbnails")]
+    pub show_thumbnails: bool,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -86,6 +102,61 @@ pub struct KeyBindings {
     pub toggle: Key,
     pub next_state: Key,
     pub open_popup: Key,
+    #[serde(default = "default_next_page_key")]
+    pub next_page: Key,
+    #[serde(default = "default_previous_page_key")]
+    pub previous_page: Key,
+    #[serde(default = "default_previous_season_key")]
+    pub previous_season: Key,
+    #[serde(default = "default_next_season_key")]
+    pub next_season: Key,
+    #[serde(default = "default_current_season_key")]
+    pub current_season: Key,
+    #[serde(default = "default_filter_key")]
+    pub filter: Key,
+}
+
+fn default_next_page_key() -> Key {
+    Key::Char('n')
+}
+
+fn default_previous_page_key() -> Key {
+    Key::Char('p')
+}
+
+fn default_previous_season_key() -> Key {
+    Key::Char('[')
+}
+
+fn default_next_season_key() -> Key {
+    Key::Char(']')
+}
+
+fn default_current_season_key() -> Key {
+    Key::Char('c')
+}
+
+fn default_filter_key() -> Key {
+    Key::Char('f')
+}
+
+/// a page of thumbnails is 15 images already
+const DEFAULT_MAX_CACHED_IMAGES: u16 = 200;
+/// the default before the image cache got `max_image_cache_mb`, when it only held large covers
+const OLD_MAX_CACHED_IMAGES: u16 = 15;
+/// keys that came with thumbnails, no file with the old default has them
+const IMAGE_SETTINGS: [&str; 2] = ["max_image_cache_mb", "show_thumbnails"];
+
+fn default_max_cached_images() -> u16 {
+    DEFAULT_MAX_CACHED_IMAGES
+}
+
+fn default_max_image_cache_mb() -> u64 {
+    50
+}
+
+fn default_show_thumbnails() -> bool {
+    true
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -96,6 +167,99 @@ pub struct BehaviorConfig {
     pub show_logger: bool,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub connect_timeout_seconds: u64,
+    pub request_timeout_seconds: u64,
+    /// how many times a failed request is retried (429, 5xx, timeouts)
+    pub max_retries: u32,
+    pub retry_base_delay_milliseconds: u64,
+    pub retry_max_delay_milliseconds: u64,
+    /// requests that can be sent at once before the rate limit kicks in
+    pub rate_limit_burst: u32,
+    /// sustained requests per second
+    pub rate_limit_per_second: f64,
+    /// base of every api endpoint, overridden by `MAL_API_URL`
+    pub api_url: String,
+    /// overridden by `MAL_AUTHORIZE_URL`
+    pub authorize_url: String,
+    /// overridden by `MAL_TOKEN_URL`
+    pub token_url: String,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_seconds: 10,
+            request_timeout_seconds: 30,
+            max_retries: 3,
+            retry_base_delay_milliseconds: 500,
+            retry_max_delay_milliseconds: 8000,
+            rate_limit_burst: 5,
+            rate_limit_per_second: 2.0,
+            api_url: API_URL.to_string(),
+            authorize_url: AUTHORIZE_URL.to_string(),
+            token_url: TOKEN_URL.to_string(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Lets the `MAL_*_URL` environment variables take precedence over the config file
+    pub fn apply_env(&mut self) {
+        let urls = [
+            ("MAL_API_URL", &mut self.api_url),
+            ("MAL_AUTHORIZE_URL", &mut self.authorize_url),
+            ("MAL_TOKEN_URL", &mut self.token_url),
+        ];
+        for (name, url) in urls {
+            if let Some(value) = std::env::var(name).ok().filter(|v| !v.trim().is_empty()) {
+                *url = value.trim().to_string();
+            }
+        }
+    }
+
+    pub fn auth_endpoints(&self) -> AuthEndpoints {
+        AuthEndpoints {
+            authorize_url: self.authorize_url.clone(),
+            token_url: self.token_url.clone(),
+            connect_timeout: Duration::from_secs(self.connect_timeout_seconds),
+            request_timeout: Duration::from_secs(self.request_timeout_seconds),
+        }
+    }
+}
+
+/// How long cached api responses are used before asking MAL again
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    /// rankings, seasonal and suggestions
+    pub rankings_ttl_minutes: u64,
+    /// anime, manga and forum topic pages
+    pub details_ttl_minutes: u64,
+    /// searches and forum boards; user lists are always revalidated
+    pub other_ttl_minutes: u64,
+    /// entries are dropped this long after they were stored, even for offline use
+    pub max_age_days: u64,
+    /// size budget of each profile's responses, the oldest go first
+    pub max_size_mb: u64,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rankings_ttl_minutes: 360,
+            details_ttl_minutes: 1440,
+            other_ttl_minutes: 60,
+            max_age_days: 30,
+            max_size_mb: 50,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum MangaDisplayType {
     Vol,
@@ -117,6 +281,12 @@ impl AppConfig {
                 toggle: Key::Char('s'),
                 open_popup: Key::Char('r'),
                 next_state: Key::Ctrl('p'),
+                next_page: default_next_page_key(),
+                previous_page: default_previous_page_key(),
+                previous_season: default_previous_season_key(),
+                next_season: default_next_season_key(),
+                current_season: default_current_season_key(),
+                filter: default_filter_key(),
             },
             behavior: BehaviorConfig {
                 tick_rate_milliseconds: 500,
@@ -149,8 +319,12 @@ impl AppConfig {
             ],
             navigation_stack_limit: 15,
             search_limit: 30,
-            max_cached_images: 15,
+            max_cached_images: default_max_cached_images(),
+            max_image_cache_mb: default_max_image_cache_mb(),
+            show_thumbnails: default_show_thumbnails(),
             log_level: LevelFilter::Debug,
+            network: NetworkConfig::default(),
+            response_cache: ResponseCacheConfig::default(),
         })
     }
 
@@ -165,18 +339,61 @@ impl AppConfig {
         if !config_file.exists() {
             // if config file doesn't exist, create default config
             fs::create_dir_all(config_file.parent().unwrap())?;
-            let default_config = Self::new()?;
+            let mut default_config = Self::new()?;
 
             fs::write(&config_file, serde_yaml::to_string(&default_config)?)?;
+            default_config.network.apply_env();
             Ok(default_config)
         } else {
             // if config file exists, read it
             let content = fs::read_to_string(&config_file).map_err(|_| ConfigError::ReadError)?;
-            let config: Self = serde_yaml::from_str(&content).map_err(ConfigError::ParseError)?;
+            let mut config = Self::parse(&content)?;
+            config.network.apply_env();
 
             Ok(config)
         }
     }
+
+    /// Reads a config file, bringing the settings of older versions up to date
+    fn parse(content: &str) -> Result<Self, ConfigError> {
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(content).map_err(ConfigError::ParseError)?;
+        let mut config: Self =
+            serde_yaml::from_value(value.clone()).map_err(ConfigError::ParseError)?;
+        // files written before thumbnails still carry the old default, which
+        // would evict the posters of a page while they are drawn. A missing
+        // limit gets the new default from serde, one next to any of the newer
+        // image settings was picked by the user
+        let newer_image_settings = IMAGE_SETTINGS.iter().any(|key| value.get(key).is_some());
+        if !newer_image_settings && config.max_cached_images == OLD_MAX_CACHED_IMAGES {
+            tracing::info!(
+                "raising max_cached_images from {} to {}, set max_image_cache_mb in the config to keep {}",
+                OLD_MAX_CACHED_IMAGES,
+                DEFAULT_MAX_CACHED_IMAGES,
+                OLD_MAX_CACHED_IMAGES
+            );
+            config.max_cached_images = DEFAULT_MAX_CACHED_IMAGES;
+        }
+        Ok(config)
+    }
+
+    /// What the poster cache holds, without opening it
+    pub fn image_cache_stats(&self) -> ImageCacheStats {
+        ImageCache::read_stats(
+            &self.paths.picture_cache_dir_path,
+            self.max_cached_images as usize,
+            self.max_image_cache_mb * 1024 * 1024,
+        )
+    }
+
+    /// The poster cache, with the limits of the config
+    pub fn image_cache(&self) -> std::io::Result<ImageCache> {
+        ImageCache::open(
+            &self.paths.picture_cache_dir_path,
+            self.max_cached_images as usize,
+            self.max_image_cache_mb * 1024 * 1024,
+        )
+    }
 }
 
 fn get_cache_dir() -> Result<CachePaths, ConfigError> {
@@ -193,6 +410,8 @@ fn get_cache_dir() -> Result<CachePaths, ConfigError> {
 
             let data_file_path = cache_dir.join(DATA_FILE);
 
+            let response_cache_dir = cache_dir.join(RESPONSE_CACHE_DIR);
+
             if !home_cache_dir.exists() {
                 fs::create_dir(&home_cache_dir)?;
             }
@@ -204,9 +423,14 @@ fn get_cache_dir() -> Result<CachePaths, ConfigError> {
                 fs::create_dir(&picture_cache_dir)?;
             }
 
+            if !response_cache_dir.exists() {
+                fs::create_dir(&response_cache_dir)?;
+            }
+
             let paths = CachePaths {
                 picture_cache_dir_path: picture_cache_dir.to_path_buf(),
                 data_file_path,
+                response_cache_dir_path: response_cache_dir,
             };
 
             Ok(paths)
@@ -219,9 +443,59 @@ fn get_cache_dir() -> Result<CachePaths, ConfigError> {
 pub struct CachePaths {
     pub picture_cache_dir_path: PathBuf,
     pub data_file_path: PathBuf,
+    pub response_cache_dir_path: PathBuf,
 }
 impl Default for CachePaths {
     fn default() -> Self {
         get_cache_dir().ok().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_old_image_limit_is_raised() {
+        let config = AppConfig::new().unwrap();
+        let mut old = serde_yaml::to_value(&config).unwrap();
+        let fields = old.as_mapping_mut().unwrap();
+        fields.remove("max_image_cache_mb");
+        fields.insert("max_cached_images".into(), OLD_MAX_CACHED_IMAGES.into());
+        // set on purpose, the file has one of the newer image settings
+        let picked = serde_yaml::to_string(&old).unwrap();
+        assert_eq!(
+            AppConfig::parse(&picked).unwrap().max_cached_images,
+            OLD_MAX_CACHED_IMAGES
+        );
+
+        old.as_mapping_mut().unwrap().remove("show_thumbnails");
+        let old = serde_yaml::to_string(&old).unwrap();
+        assert_eq!(
+            AppConfig::parse(&old).unwrap().max_cached_images,
+            DEFAULT_MAX_CACHED_IMAGES
+        );
+
+        // a limit set next to the size budget is the user's own
+        let mut current = serde_yaml::to_value(&config).unwrap();
+        current["max_cached_images"] = OLD_MAX_CACHED_IMAGES.into();
+        let current = serde_yaml::to_string(&current).unwrap();
+        assert_eq!(
+            AppConfig::parse(&current).unwrap().max_cached_images,
+            OLD_MAX_CACHED_IMAGES
+        );
+    }
+
+    #[test]
+    fn test_missing_image_limit_is_default() {
+        let mut config = serde_yaml::to_value(AppConfig::new().unwrap()).unwrap();
+        let fields = config.as_mapping_mut().unwrap();
+        fields.remove("max_cached_images");
+        fields.remove("max_image_cache_mb");
+        let config = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            AppConfig::parse(&config).unwrap().max_cached_images,
+            DEFAULT_MAX_CACHED_IMAGES
+        );
+    }
+}
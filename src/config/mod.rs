@@ -14,6 +14,7 @@ const APP_CONFIG_DIR: &str = "mal-cli";
 const CACHE_DIR: &str = ".cache";
 const APP_CACHE_DIR: &str = "mal-cli";
 const PICTURE_CACHE_DIR: &str = "images";
+const RESPONSE_CACHE_DIR: &str = "responses";
 const DATA_FILE: &str = "mal_data.json";
 
 const DEFAULT_PORT: u16 = 2006;
@@ -37,6 +38,8 @@ pub enum ConfigError {
     IOError(std::io::Error),
 
     InvalidClientIdError,
+    /// Profile names end up in file names, so only letters, digits, `-` and `_` are allowed
+    InvalidProfileName(String),
 }
 
 impl std::error::Error for ConfigError {
@@ -48,6 +51,7 @@ impl std::error::Error for ConfigError {
             ConfigError::ParseError(_) => None,
             ConfigError::IOError(_) => None,
             ConfigError::InvalidClientIdError => None,
+            ConfigError::InvalidProfileName(_) => None,
         }
     }
 }
@@ -61,6 +65,11 @@ impl std::fmt::Display for ConfigError {
             ConfigError::ParseError(ref err) => err.fmt(f),
             ConfigError::IOError(ref err) => err.fmt(f),
             ConfigError::InvalidClientIdError => write!(f, "Invalid client ID provided"),
+            ConfigError::InvalidProfileName(ref name) => write!(
+                f,
+                "Invalid profile name `{}`, use letters, digits, `-` and `_`",
+                name
+            ),
         }
     }
 }
@@ -77,7 +86,49 @@ impl From<serde_yaml::Error> for ConfigError {
     }
 }
 
+/// A value that must never show up in logs or printed config, like a client secret
+#[derive(Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub const REDACTED: &'static str = "<redacted>";
+
+    pub fn new(value: impl Into<String>) -> Self {
+        Secret(value.into())
+    }
+
+    /// The actual value, only for the requests that need it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// A stand-in to print instead of the value
+    pub fn redacted() -> Self {
+        Secret(Self::REDACTED.to_string())
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(Self::REDACTED)
+    }
+}
+
 pub struct ConfigPaths {
     pub config_file_path: PathBuf,
     pub auth_cache_path: PathBuf,
 }
+
+impl ConfigPaths {
+    /// The token cache of a profile, `None` being the default account
+    pub fn token_cache_path(&self, profile: Option<&str>) -> PathBuf {
+        match profile {
+            // .mal_token_cache.<name>.json next to the default one
+            Some(name) => self
+                .auth_cache_path
+                .with_extension(format!("{}.json", name)),
+            None => self.auth_cache_path.clone(),
+        }
+    }
+}
@@ -3,6 +3,7 @@ use figlet_rs::FIGfont;
 use serde::{Deserialize, Serialize};
 use serde_yaml;
 use std::{
+    collections::BTreeMap,
     fs,
     io::{stdin, Write},
     path::Path,
@@ -10,20 +11,118 @@ use std::{
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 pub struct AuthConfig {
     pub client_id: String,
+    /// Only "web" apps on MAL have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret>,
     pub user_agent: Option<String>,
     pub port: Option<u16>,
+    /// How long to wait for the browser during login, 5 minutes when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_timeout_seconds: Option<u64>,
+    /// Other MAL accounts, each with its own token cache
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile in use, `None` being the default account
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+/// A named account in oauth2.yml
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct Profile {
+    /// Uses the top level client id when missing
+    pub client_id: Option<String>,
+    /// Secret of the profile's own client id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<Secret>,
+}
+
+/// Profile names end up in file names, only letters, digits, `-` and `_` are allowed
+pub fn check_profile_name(name: &str) -> Result<(), ConfigError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidProfileName(name.to_string()))
+    }
 }
 
 impl AuthConfig {
-    // TODO: Strip whitespace from user_agent as it can cause code to panic
-    pub fn load() -> Result<Self, ConfigError> {
+    /// This config for the profile `name`, `None` being the default account.
+    /// Profiles missing from oauth2.yml are fine, they use the top level client id.
+    pub fn for_profile(&self, name: Option<&str>) -> Result<Self, ConfigError> {
+        if let Some(name) = name {
+            check_profile_name(name)?;
+        }
+        Ok(Self {
+            profile: name.map(str::to_string),
+            ..self.clone()
+        })
+    }
+
+    /// The profiles of oauth2.yml plus the one in use
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        if let Some(profile) = &self.profile {
+            if !names.contains(profile) {
+                names.push(profile.clone());
+            }
+        }
+        names
+    }
+
+    /// The client id of the profile in use
+    pub fn get_client_id(&self) -> String {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .and_then(|profile| profile.client_id.clone())
+            .unwrap_or_else(|| self.client_id.clone())
+    }
+
+    /// The client secret going with [`get_client_id`](Self::get_client_id)
+    pub fn get_client_secret(&self) -> Option<Secret> {
+        match self
+            .profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+        {
+            // a secret only works with the client id it was issued for
+            Some(profile) if profile.client_id.is_some() => profile.client_secret.clone(),
+            _ => self.client_secret.clone(),
+        }
+    }
+
+    /// This config with every secret replaced, safe to print
+    pub fn redacted(&self) -> Self {
+        let redact = |secret: &Option<Secret>| secret.as_ref().map(|_| Secret::redacted());
+        let mut config = self.clone();
+        config.client_secret = redact(&self.client_secret);
+        for profile in config.profiles.values_mut() {
+            profile.client_secret = redact(&profile.client_secret);
+        }
+        config
+    }
+
+    /// oauth2.yml as it is, `None` before the first run
+    pub fn load_existing() -> Result<Option<Self>, ConfigError> {
         let paths = Self::get_paths()?;
-        if paths.config_file_path.exists() {
-            let config_string = fs::read_to_string(&paths.config_file_path)?;
-            let config_yml: AuthConfig = serde_yaml::from_str(&config_string)?;
+        if !paths.config_file_path.exists() {
+            return Ok(None);
+        }
+        let config_string = fs::read_to_string(&paths.config_file_path)?;
+        Ok(Some(serde_yaml::from_str(&config_string)?))
+    }
 
+    // TODO: Strip whitespace from user_agent as it can cause code to panic
+    pub fn load() -> Result<Self, ConfigError> {
+        if let Some(config_yml) = Self::load_existing()? {
             Ok(config_yml)
         } else {
+            let paths = Self::get_paths()?;
             let standard_font = FIGfont::standard().unwrap();
             let figlet = standard_font.convert("MAL-CLI");
             let banner = figlet.unwrap().to_string();
@@ -45,10 +144,8 @@ impl AuthConfig {
                 "You are now ready to authenticate with myanimelist!",
             ];
 
-            let mut number = 1;
-            for item in instructions.iter() {
-                println!("   {}. {}", number, item);
-                number += 1;
+            for (number, item) in instructions.iter().enumerate() {
+                println!("   {}. {}", number + 1, item);
             }
 
             let mut client_id = String::new();
@@ -66,6 +163,15 @@ impl AuthConfig {
                     client_id.clear();
                 }
             }
+            let mut client_secret = String::new();
+            println!(
+                "\nEnter your client secret (only \"web\" apps have one, leave empty otherwise): "
+            );
+            stdin().read_line(&mut client_secret)?;
+            let client_secret = Some(client_secret.trim())
+                .filter(|secret| !secret.is_empty())
+                .map(Secret::new);
+
             let mut user_agent = String::new();
             println!("\nEnter User Agent (default {}): ", DEFAULT_USER_AGENT);
             stdin().read_line(&mut user_agent)?;
@@ -82,13 +188,20 @@ impl AuthConfig {
 
             let config_yml = AuthConfig {
                 client_id,
+                client_secret,
                 user_agent: Some(user_agent),
                 port: Some(port),
+                ..Default::default()
             };
 
             let content_yml = serde_yaml::to_string(&config_yml)?;
 
-            let mut new_config = fs::File::create(&paths.config_file_path)?;
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            // it may hold the client secret
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut new_config = options.open(&paths.config_file_path)?;
             write!(new_config, "{}", content_yml)?;
 
             Ok(config_yml)
@@ -103,6 +216,13 @@ impl AuthConfig {
         self.port.unwrap_or(DEFAULT_PORT)
     }
 
+    pub fn get_login_timeout(&self) -> std::time::Duration {
+        self.login_timeout_seconds.map_or(
+            crate::auth::redirect::DEFAULT_TIMEOUT,
+            std::time::Duration::from_secs,
+        )
+    }
+
     pub fn get_user_agent(&self) -> String {
         match &self.user_agent {
             Some(s) => s.clone(),
@@ -139,3 +259,65 @@ impl AuthConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_profiles() {
+        let config: AuthConfig = serde_yaml::from_str(
+            "client_id: main\nprofiles:\n  work:\n    client_id: other\n  alt: {}\n",
+        )
+        .unwrap();
+        assert_eq!(config.get_client_id(), "main");
+        assert_eq!(
+            config.for_profile(Some("work")).unwrap().get_client_id(),
+            "other"
+        );
+        // missing profiles and ones without a client id use the top level one
+        let new = config.for_profile(Some("new")).unwrap();
+        assert_eq!(new.get_client_id(), "main");
+        assert_eq!(new.profile_names(), ["alt", "work", "new"]);
+        assert!(matches!(
+            config.for_profile(Some("../work")),
+            Err(ConfigError::InvalidProfileName(_))
+        ));
+    }
+
+    #[test]
+    fn test_client_secret() {
+        let config: AuthConfig = serde_yaml::from_str(
+            "client_id: main\nclient_secret: s3cret\nprofiles:\n  work:\n    client_id: other\n    client_secret: w0rk\n  alt: {}\n",
+        )
+        .unwrap();
+        assert_eq!(config.get_client_secret().unwrap().expose(), "s3cret");
+        let work = config.for_profile(Some("work")).unwrap();
+        assert_eq!(work.get_client_secret().unwrap().expose(), "w0rk");
+        // same client id, same secret
+        let alt = config.for_profile(Some("alt")).unwrap();
+        assert_eq!(alt.get_client_secret().unwrap().expose(), "s3cret");
+
+        let printed = serde_yaml::to_string(&config.redacted()).unwrap();
+        assert!(!printed.contains("s3cret") && !printed.contains("w0rk"));
+        assert!(printed.contains(Secret::REDACTED));
+        assert!(!format!("{:?}", config).contains("s3cret"));
+    }
+
+    #[test]
+    fn test_token_cache_path() {
+        let paths = ConfigPaths {
+            config_file_path: PathBuf::from("/mal/oauth2.yml"),
+            auth_cache_path: PathBuf::from("/mal/.mal_token_cache.json"),
+        };
+        assert_eq!(
+            paths.token_cache_path(None),
+            PathBuf::from("/mal/.mal_token_cache.json")
+        );
+        assert_eq!(
+            paths.token_cache_path(Some("work")),
+            PathBuf::from("/mal/.mal_token_cache.work.json")
+        );
+    }
+}
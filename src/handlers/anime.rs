@@ -10,19 +10,13 @@ pub fn handler(key: Key, app: &mut App) {
         k if common::down_event(k) => {
             // calculate the next index in the list
             let next_index = ANIME_OPTIONS_RANGE.start
-                + common::on_down_press(
-                    &ANIME_OPTIONS,
-                    Some(app.library.selected_index % (ANIME_OPTIONS.len())),
-                );
+                + common::on_down_press(&ANIME_OPTIONS, app.library.index_in(ANIME_OPTIONS_RANGE));
             app.library.selected_index = next_index;
         }
         k if common::up_event(k) => {
             // calculate the next index in the list
             let next_index = ANIME_OPTIONS_RANGE.start
-                + common::on_up_press(
-                    &ANIME_OPTIONS,
-                    Some(app.library.selected_index % (ANIME_OPTIONS.len())),
-                );
+                + common::on_up_press(&ANIME_OPTIONS, app.library.index_in(ANIME_OPTIONS_RANGE));
             app.library.selected_index = next_index;
         }
 
@@ -37,7 +31,7 @@ pub fn handler(key: Key, app: &mut App) {
                 // This is required because Rust can't tell if this pattern in exhaustive
                 _ => {}
             };
-            app.library.selected_index = 9;
+            app.library.selected_index = 10;
         }
 
         _ => (),
@@ -1,9 +1,14 @@
 use crate::{
     api::{
-        model::{UserAnimeListStatus, UserMangaListStatus, UserReadStatus, UserWatchStatus},
+        model::{
+            DateWrapper, UserAnimeListStatus, UserMangaListStatus, UserReadStatus, UserWatchStatus,
+        },
         UpdateUserAnimeListStatusQuery, UpdateUserMangaStatus,
     },
-    app::{ActiveAnimeDetailBlock, ActiveDisplayBlock, ActiveMangaDetailBlock, App, DetailPopup},
+    app::{
+        ActiveAnimeDetailBlock, ActiveDisplayBlock, ActiveMangaDetailBlock, App, DatePicker,
+        DetailPopup,
+    },
     event::Key,
     handlers::common,
     network::IoEvent,
@@ -43,6 +48,12 @@ pub fn handler(key: Key, app: &mut App) {
                     app.selected_popup_rate = (app.selected_popup_rate + 1) % 11;
                 }
             }
+            ActiveAnimeDetailBlock::Dates => {
+                if app.popup {
+                    app.date_picker.shift(-1);
+                }
+            }
+            ActiveAnimeDetailBlock::Discussion => {}
         },
         k if common::up_event(k) => match app.active_anime_detail_block {
             ActiveAnimeDetailBlock::SideInfo => {
@@ -74,21 +85,48 @@ pub fn handler(key: Key, app: &mut App) {
                     app.selected_popup_rate = (app.selected_popup_rate + 9) % 11;
                 }
             }
+            ActiveAnimeDetailBlock::Dates => {
+                if app.popup {
+                    app.date_picker.shift(1);
+                }
+            }
+            ActiveAnimeDetailBlock::Discussion => {}
         },
+        k if common::right_event(k)
+            && app.popup
+            && app.active_anime_detail_block == ActiveAnimeDetailBlock::Dates =>
+        {
+            app.date_picker.next_field()
+        }
+        k if common::left_event(k)
+            && app.popup
+            && app.active_anime_detail_block == ActiveAnimeDetailBlock::Dates =>
+        {
+            app.date_picker.previous_field()
+        }
+        Key::Backspace | Key::Delete
+            if app.popup && app.active_anime_detail_block == ActiveAnimeDetailBlock::Dates =>
+        {
+            app.date_picker.clear()
+        }
         k if common::right_event(k) && !app.popup => {
             app.active_anime_detail_block = match app.active_anime_detail_block {
                 ActiveAnimeDetailBlock::AddToList => ActiveAnimeDetailBlock::Rate,
                 ActiveAnimeDetailBlock::Rate => ActiveAnimeDetailBlock::Episodes,
-                ActiveAnimeDetailBlock::Episodes => ActiveAnimeDetailBlock::AddToList,
+                ActiveAnimeDetailBlock::Episodes => ActiveAnimeDetailBlock::Dates,
+                ActiveAnimeDetailBlock::Dates => ActiveAnimeDetailBlock::Discussion,
+                ActiveAnimeDetailBlock::Discussion => ActiveAnimeDetailBlock::AddToList,
                 ActiveAnimeDetailBlock::SideInfo => ActiveAnimeDetailBlock::Episodes,
                 ActiveAnimeDetailBlock::Synopsis => ActiveAnimeDetailBlock::SideInfo,
             }
         }
         k if common::left_event(k) && !app.popup => {
             app.active_anime_detail_block = match app.active_anime_detail_block {
-                ActiveAnimeDetailBlock::AddToList => ActiveAnimeDetailBlock::Episodes,
+                ActiveAnimeDetailBlock::AddToList => ActiveAnimeDetailBlock::Discussion,
                 ActiveAnimeDetailBlock::Rate => ActiveAnimeDetailBlock::AddToList,
                 ActiveAnimeDetailBlock::Episodes => ActiveAnimeDetailBlock::Rate,
+                ActiveAnimeDetailBlock::Dates => ActiveAnimeDetailBlock::Episodes,
+                ActiveAnimeDetailBlock::Discussion => ActiveAnimeDetailBlock::Dates,
                 ActiveAnimeDetailBlock::SideInfo => ActiveAnimeDetailBlock::Synopsis,
                 ActiveAnimeDetailBlock::Synopsis => ActiveAnimeDetailBlock::AddToList,
             }
@@ -104,7 +142,9 @@ fn change_tab(app: &mut App) {
     app.active_anime_detail_block = match app.active_anime_detail_block {
         ActiveAnimeDetailBlock::AddToList => ActiveAnimeDetailBlock::Rate,
         ActiveAnimeDetailBlock::Rate => ActiveAnimeDetailBlock::Episodes,
-        ActiveAnimeDetailBlock::Episodes => ActiveAnimeDetailBlock::SideInfo,
+        ActiveAnimeDetailBlock::Episodes => ActiveAnimeDetailBlock::Dates,
+        ActiveAnimeDetailBlock::Dates => ActiveAnimeDetailBlock::Discussion,
+        ActiveAnimeDetailBlock::Discussion => ActiveAnimeDetailBlock::SideInfo,
         ActiveAnimeDetailBlock::SideInfo => ActiveAnimeDetailBlock::Synopsis,
         ActiveAnimeDetailBlock::Synopsis => ActiveAnimeDetailBlock::AddToList,
     }
@@ -148,6 +188,19 @@ fn open_popup(app: &mut App) {
                 .map_or(0, |list| list.num_episodes_watched as u16);
             app.popup = true;
         }
+        ActiveAnimeDetailBlock::Dates => {
+            app.active_detail_popup = DetailPopup::Dates;
+            let my_list = &app.anime_details.as_ref().unwrap().my_list_status;
+            app.date_picker = my_list.as_ref().map_or_else(DatePicker::default, |list| {
+                DatePicker::new(list.start_date.clone(), list.finish_date.clone())
+            });
+            app.popup = true;
+        }
+        ActiveAnimeDetailBlock::Discussion => {
+            let anime_id = app.anime_details.as_ref().unwrap().id;
+            app.active_display_block = ActiveDisplayBlock::Loading;
+            app.dispatch(IoEvent::GetAnimeDiscussion(anime_id));
+        }
         _ => {}
     }
 }
@@ -231,16 +284,32 @@ pub fn handle_edit(app: &mut App) {
                         }
                     }
 
+                    ActiveAnimeDetailBlock::Dates => {
+                        let picker = &app.date_picker;
+                        let mut query = match my_list {
+                            Some(list) => user_list_to_anime_query(list, None, None, None),
+                            None => anime_query_with_one_field(None, None, None),
+                        };
+                        if same_date(&query.start_date, &picker.start_date)
+                            && same_date(&query.finish_date, &picker.finish_date)
+                        {
+                            app.popup = false;
+                            None
+                        } else {
+                            query.start_date = Some(picker.start_date.clone());
+                            query.finish_date = Some(picker.finish_date.clone());
+                            Some(query)
+                        }
+                    }
+
                     _ => Some(anime_query_with_one_field(None, None, None)),
                 };
-            if anime_update_query.is_none() {
+            let Some(mut anime_update_query) = anime_update_query else {
                 return;
-            }
+            };
+            fill_anime_dates(&mut anime_update_query, my_list.as_ref());
             let anime_id = app.anime_details.as_ref().unwrap().id;
-            app.dispatch(IoEvent::UpdateAnimeListStatus(
-                anime_id,
-                anime_update_query.unwrap(),
-            ));
+            app.dispatch(IoEvent::UpdateAnimeListStatus(anime_id, anime_update_query));
             app.popup_is_loading = true;
             app.result_popup = true;
         }
@@ -346,17 +415,33 @@ pub fn handle_edit(app: &mut App) {
                             ))
                         }
                     }
+
+                    ActiveMangaDetailBlock::Dates => {
+                        let picker = &app.date_picker;
+                        let mut query = match my_list {
+                            Some(list) => user_list_to_manga_query(list, None, None, None, None),
+                            None => manga_query_with_one_field(None, None, None, None),
+                        };
+                        if same_date(&query.start_date, &picker.start_date)
+                            && same_date(&query.finish_date, &picker.finish_date)
+                        {
+                            app.popup = false;
+                            None
+                        } else {
+                            query.start_date = Some(picker.start_date.clone());
+                            query.finish_date = Some(picker.finish_date.clone());
+                            Some(query)
+                        }
+                    }
                     _ => None,
                 };
-            if manga_update_query.is_none() {
+            let Some(mut manga_update_query) = manga_update_query else {
                 return;
-            }
+            };
+            fill_manga_dates(&mut manga_update_query, my_list.as_ref());
 
             let manga_id = app.manga_details.as_ref().unwrap().id;
-            app.dispatch(IoEvent::UpdateMangaListStatus(
-                manga_id,
-                manga_update_query.unwrap(),
-            ));
+            app.dispatch(IoEvent::UpdateMangaListStatus(manga_id, manga_update_query));
             app.result_popup = true;
             app.popup_is_loading = true;
         }
@@ -408,6 +493,8 @@ pub fn user_list_to_anime_query(
         priority: my_list.priority,
         rewatch_value: my_list.rewatch_value,
         tags: my_list.tags.clone().map(|v| v.join(",")),
+        start_date: my_list.start_date.clone().map(Some),
+        finish_date: my_list.finish_date.clone().map(Some),
     }
 }
 
@@ -429,6 +516,8 @@ pub fn user_list_to_manga_query(
         comments: my_list.comments.clone(),
         is_rereading: Some(my_list.is_rereading),
         num_times_reread: my_list.num_times_reread,
+        start_date: my_list.start_date.clone().map(Some),
+        finish_date: my_list.finish_date.clone().map(Some),
     }
 }
 
@@ -449,6 +538,8 @@ pub fn manga_query_with_one_field(
         is_rereading: None,
         priority: None,
         tags: None,
+        start_date: None,
+        finish_date: None,
     }
 }
 
@@ -467,5 +558,45 @@ pub fn anime_query_with_one_field(
         rewatch_value: None,
         tags: None,
         comments: None,
+        start_date: None,
+        finish_date: None,
+    }
+}
+
+/// Whether the query leaves the date as it was picked
+fn same_date(sent: &Option<Option<DateWrapper>>, picked: &Option<DateWrapper>) -> bool {
+    sent.as_ref().and_then(Option::as_ref).map(|d| d.date) == picked.as_ref().map(|d| d.date)
+}
+
+/// Sets the start date when the progress leaves 0 and the finish date
+/// when the anime becomes completed, unless they are already set
+fn fill_anime_dates(
+    query: &mut UpdateUserAnimeListStatusQuery,
+    my_list: Option<&UserAnimeListStatus>,
+) {
+    let watched = my_list.map_or(0, |list| list.num_episodes_watched);
+    if watched == 0 && query.num_watched_episodes.unwrap_or(0) > 0 && query.start_date.is_none() {
+        query.start_date = Some(Some(DateWrapper::today()));
+    }
+
+    let completed = my_list.is_some_and(|list| list.status == UserWatchStatus::Completed);
+    if !completed && query.status == Some(UserWatchStatus::Completed) && query.finish_date.is_none()
+    {
+        query.finish_date = Some(Some(DateWrapper::today()));
+    }
+}
+
+/// Same as [`fill_anime_dates`], where progress is either chapters or volumes
+fn fill_manga_dates(query: &mut UpdateUserMangaStatus, my_list: Option<&UserMangaListStatus>) {
+    let read = my_list.map_or(0, |list| list.num_chapters_read + list.num_volumes_read);
+    let progress = query.num_chapters_read.unwrap_or(0) + query.num_volumes_read.unwrap_or(0);
+    if read == 0 && progress > 0 && query.start_date.is_none() {
+        query.start_date = Some(Some(DateWrapper::today()));
+    }
+
+    let completed = my_list.is_some_and(|list| list.status == UserReadStatus::Completed);
+    if !completed && query.status == Some(UserReadStatus::Completed) && query.finish_date.is_none()
+    {
+        query.finish_date = Some(Some(DateWrapper::today()));
     }
 }
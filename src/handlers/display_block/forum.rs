@@ -0,0 +1,98 @@
+use crate::{
+    app::{ActiveDisplayBlock, App, FORUM_POSTS_PER_PAGE},
+    event::Key,
+    handlers::common,
+    network::IoEvent,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    match app.active_display_block {
+        ActiveDisplayBlock::ForumTopics => handle_topics(key, app),
+        ActiveDisplayBlock::ForumTopic => handle_thread(key, app),
+        _ => {}
+    }
+}
+
+fn handle_topics(key: Key, app: &mut App) {
+    let Some(forum) = &app.forum_topics else {
+        return;
+    };
+    let count = forum.topics.data.len();
+    let index = forum.selected_index;
+    let has_next_page = forum.topics.has_next_page();
+    if count == 0 {
+        return;
+    }
+
+    match key {
+        k if common::down_event(k) => {
+            if index + 1 < count {
+                select_topic(app, index + 1);
+            } else if has_next_page && !app.is_loading {
+                app.dispatch(IoEvent::GetNextPage);
+            }
+        }
+
+        k if common::up_event(k) => select_topic(app, index.saturating_sub(1)),
+
+        k if k == app.app_config.keys.next_page && has_next_page && !app.is_loading => {
+            app.dispatch(IoEvent::GetNextPage);
+        }
+
+        Key::Enter => {
+            let topic_id = app.forum_topics.as_ref().unwrap().topics.data[index].id;
+            // keep the selection when coming back to the list
+            app.update_current_route_data();
+            app.active_display_block = ActiveDisplayBlock::Loading;
+            app.dispatch(IoEvent::GetForumTopic(topic_id, 0));
+        }
+
+        _ => {}
+    }
+}
+
+fn select_topic(app: &mut App, index: usize) {
+    if let Some(forum) = app.forum_topics.as_mut() {
+        forum.selected_index = index;
+    }
+}
+
+fn handle_thread(key: Key, app: &mut App) {
+    let Some(thread) = &app.forum_thread else {
+        return;
+    };
+    let topic_id = thread.topic_id;
+    let offset = thread.offset;
+    let has_next_page = thread.page.paging.next.is_some();
+
+    match key {
+        // the reader clamps the scroll to the end of the page when drawing
+        k if common::down_event(k) => {
+            app.forum_thread_scroll = app.forum_thread_scroll.saturating_add(1)
+        }
+
+        k if common::up_event(k) => {
+            app.forum_thread_scroll = app.forum_thread_scroll.saturating_sub(1)
+        }
+
+        Key::PageDown => app.forum_thread_scroll = app.forum_thread_scroll.saturating_add(10),
+
+        Key::PageUp => app.forum_thread_scroll = app.forum_thread_scroll.saturating_sub(10),
+
+        k if k == app.app_config.keys.next_page && has_next_page && !app.is_loading => {
+            app.dispatch(IoEvent::GetForumTopic(
+                topic_id,
+                offset + FORUM_POSTS_PER_PAGE,
+            ));
+        }
+
+        k if k == app.app_config.keys.previous_page && offset > 0 && !app.is_loading => {
+            app.dispatch(IoEvent::GetForumTopic(
+                topic_id,
+                offset.saturating_sub(FORUM_POSTS_PER_PAGE),
+            ));
+        }
+
+        _ => {}
+    }
+}
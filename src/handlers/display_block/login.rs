@@ -0,0 +1,15 @@
+use crate::{
+    app::{App, LoginState},
+    event::Key,
+    network::IoEvent,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+    // the network is already waiting for the browser
+    if matches!(app.login, Some(LoginState::Waiting(_))) {
+        return;
+    }
+    if key == Key::Enter {
+        app.dispatch(IoEvent::Login);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    app::{ActiveMangaDetailBlock, App, DetailPopup},
+    app::{ActiveDisplayBlock, ActiveMangaDetailBlock, App, DatePicker, DetailPopup},
     event::Key,
     handlers::common,
+    network::IoEvent,
 };
 
 use super::anime_details::{get_user_status_index, handle_edit};
@@ -51,6 +52,12 @@ pub fn handler(key: Key, app: &mut App) {
                     app.temp_popup_num -= 1;
                 }
             }
+            ActiveMangaDetailBlock::Dates => {
+                if app.popup {
+                    app.date_picker.shift(-1);
+                }
+            }
+            ActiveMangaDetailBlock::Discussion => {}
         },
         k if common::up_event(k) => match app.active_manga_detail_block {
             ActiveMangaDetailBlock::SideInfo => {
@@ -103,9 +110,18 @@ pub fn handler(key: Key, app: &mut App) {
                     }
                 }
             }
+            ActiveMangaDetailBlock::Dates => {
+                if app.popup {
+                    app.date_picker.shift(1);
+                }
+            }
+            ActiveMangaDetailBlock::Discussion => {}
         },
         k if common::right_event(k) => {
             if app.popup {
+                if app.active_manga_detail_block == ActiveMangaDetailBlock::Dates {
+                    app.date_picker.next_field();
+                }
                 return;
             }
             match app.active_manga_detail_block {
@@ -119,6 +135,12 @@ pub fn handler(key: Key, app: &mut App) {
                     app.active_manga_detail_block = ActiveMangaDetailBlock::Volumes;
                 }
                 ActiveMangaDetailBlock::Volumes => {
+                    app.active_manga_detail_block = ActiveMangaDetailBlock::Dates;
+                }
+                ActiveMangaDetailBlock::Dates => {
+                    app.active_manga_detail_block = ActiveMangaDetailBlock::Discussion;
+                }
+                ActiveMangaDetailBlock::Discussion => {
                     app.active_manga_detail_block = ActiveMangaDetailBlock::AddToList;
                 }
                 _ => {}
@@ -126,10 +148,19 @@ pub fn handler(key: Key, app: &mut App) {
         }
         k if common::left_event(k) => {
             if app.popup {
+                if app.active_manga_detail_block == ActiveMangaDetailBlock::Dates {
+                    app.date_picker.previous_field();
+                }
                 return;
             }
             match app.active_manga_detail_block {
                 ActiveMangaDetailBlock::AddToList => {
+                    app.active_manga_detail_block = ActiveMangaDetailBlock::Discussion;
+                }
+                ActiveMangaDetailBlock::Discussion => {
+                    app.active_manga_detail_block = ActiveMangaDetailBlock::Dates;
+                }
+                ActiveMangaDetailBlock::Dates => {
                     app.active_manga_detail_block = ActiveMangaDetailBlock::Volumes;
                 }
                 ActiveMangaDetailBlock::Volumes => {
@@ -151,6 +182,11 @@ pub fn handler(key: Key, app: &mut App) {
                 open_popup(app)
             }
         }
+        Key::Backspace | Key::Delete
+            if app.popup && app.active_manga_detail_block == ActiveMangaDetailBlock::Dates =>
+        {
+            app.date_picker.clear()
+        }
         _ => {}
     }
 }
@@ -170,6 +206,12 @@ fn change_tab(app: &mut App) {
             app.active_manga_detail_block = ActiveMangaDetailBlock::Volumes;
         }
         ActiveMangaDetailBlock::Volumes => {
+            app.active_manga_detail_block = ActiveMangaDetailBlock::Dates;
+        }
+        ActiveMangaDetailBlock::Dates => {
+            app.active_manga_detail_block = ActiveMangaDetailBlock::Discussion;
+        }
+        ActiveMangaDetailBlock::Discussion => {
             app.active_manga_detail_block = ActiveMangaDetailBlock::SideInfo;
         }
         ActiveMangaDetailBlock::SideInfo => {
@@ -229,6 +271,19 @@ fn open_popup(app: &mut App) {
                 .map_or(0, |list| list.num_volumes_read as u16);
             app.popup = true;
         }
+        ActiveMangaDetailBlock::Dates => {
+            app.active_detail_popup = DetailPopup::Dates;
+            let my_list = &app.manga_details.as_ref().unwrap().my_list_status;
+            app.date_picker = my_list.as_ref().map_or_else(DatePicker::default, |list| {
+                DatePicker::new(list.start_date.clone(), list.finish_date.clone())
+            });
+            app.popup = true;
+        }
+        ActiveMangaDetailBlock::Discussion => {
+            let manga_id = app.manga_details.as_ref().unwrap().id;
+            app.active_display_block = ActiveDisplayBlock::Loading;
+            app.dispatch(IoEvent::GetMangaDiscussion(manga_id));
+        }
         _ => {}
     }
 }
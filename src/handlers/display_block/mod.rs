@@ -3,7 +3,10 @@ use crate::{
     event::Key,
 };
 mod anime_details;
+mod forum;
+mod login;
 mod manga_details;
+mod profiles;
 mod ranking;
 mod result;
 mod seasonal;
@@ -24,8 +27,12 @@ pub fn handle_display_block(key: Key, app: &mut App) {
         ActiveDisplayBlock::MangaRanking => ranking::handler(key, app),
         ActiveDisplayBlock::AnimeDetails => anime_details::handler(key, app),
         ActiveDisplayBlock::MangaDetails => manga_details::handler(key, app),
+        ActiveDisplayBlock::ForumTopics => forum::handler(key, app),
+        ActiveDisplayBlock::ForumTopic => forum::handler(key, app),
         ActiveDisplayBlock::Loading => {}
         ActiveDisplayBlock::Error => {}
+        ActiveDisplayBlock::Login => login::handler(key, app),
+        ActiveDisplayBlock::Profiles => profiles::handler(key, app),
         ActiveDisplayBlock::Empty => {
             //? add toggle color for fun
             //? hard one: add playing the banner and moving it around
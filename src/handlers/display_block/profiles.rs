@@ -0,0 +1,24 @@
+use crate::{app::App, event::Key, handlers::common, network::IoEvent};
+
+pub fn handler(key: Key, app: &mut App) {
+    let choices = app.profile_choices();
+    match key {
+        k if common::down_event(k) => {
+            app.profile_index = common::on_down_press(&choices, Some(app.profile_index));
+        }
+        k if common::up_event(k) => {
+            app.profile_index = common::on_up_press(&choices, Some(app.profile_index));
+        }
+        Key::Enter => {
+            let Some(profile) = choices.get(app.profile_index) else {
+                return;
+            };
+            if *profile == app.active_profile {
+                app.load_previous_route();
+                return;
+            }
+            app.dispatch(IoEvent::SwitchProfile(profile.clone()));
+        }
+        _ => (),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::app::{ActiveDisplayBlock, App, DISPLAY_COLUMN_NUMBER, DISPLAY_RAWS_NUMBER};
 use crate::handlers::{common, get_media_detail_page};
+use crate::network::IoEvent;
 use crate::ui::get_end_card_index;
 use crate::{app::SelectedSearchTab, event::Key};
 pub fn handler(key: Key, app: &mut App) {
@@ -20,13 +21,17 @@ pub fn handler(key: Key, app: &mut App) {
 }
 
 pub fn handle_result_block(key: Key, app: &mut App) {
+    // e.g. a list filter that matches nothing, there is no card to move to
+    if get_data_length(app) == 0 {
+        return;
+    }
     //? max is the last index of the current card list
     let max = get_end_card_index(app) - app.start_card_list_index as usize;
     match key {
         k if common::left_event(k) => {
             let mut index = app.search_results.selected_display_card_index.unwrap_or(0);
             let mut edges = Vec::new();
-            for i in (0..=max - 2).step_by(DISPLAY_COLUMN_NUMBER) {
+            for i in (0..=max.saturating_sub(2)).step_by(DISPLAY_COLUMN_NUMBER) {
                 edges.push(i);
             }
             if !edges.contains(&index) {
@@ -74,11 +79,40 @@ pub fn handle_result_block(key: Key, app: &mut App) {
             }
         }
 
+        k if k == app.app_config.keys.next_page => next_page(app),
+
+        k if k == app.app_config.keys.previous_page => previous_page(app),
+
         Key::Enter => get_media_detail_page(app),
         _ => {}
     }
 }
 
+fn next_page(app: &mut App) {
+    let page_size = DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER;
+    // keep the grid full: the last screen starts page_size cards before the end
+    let last_start = (get_data_length(app) as usize).saturating_sub(page_size);
+    let start = app.start_card_list_index as usize;
+    if start >= last_start {
+        load_more(app);
+    } else {
+        app.start_card_list_index = (start + page_size).min(last_start) as u16;
+    }
+}
+
+fn previous_page(app: &mut App) {
+    app.start_card_list_index = app
+        .start_card_list_index
+        .saturating_sub((DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER) as u16);
+}
+
+/// Ask for the next page of results, they get appended to the current list
+fn load_more(app: &mut App) {
+    if !app.is_loading && has_next_page(app) {
+        app.dispatch(IoEvent::GetNextPage);
+    }
+}
+
 fn scroll_results_up(app: &mut App) {
     app.start_card_list_index = app
         .start_card_list_index
@@ -90,9 +124,12 @@ fn scroll_results_down(app: &mut App) {
     // Ensure that the end index does not exceed the data length
     // If it does, reset the the index to the start
 
-    if get_end_card_index(app) + DISPLAY_COLUMN_NUMBER > data_length - 1 {
+    if get_end_card_index(app) == data_length - 1 && has_next_page(app) {
+        // we reached the last loaded card, the next rows come from the next page
+        load_more(app);
+    } else if get_end_card_index(app) + DISPLAY_COLUMN_NUMBER > data_length - 1 {
         app.start_card_list_index =
-            (data_length - DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER) as u16;
+            data_length.saturating_sub(DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER) as u16;
     } else if get_end_card_index(app) > data_length - 1 {
         let index_positoin = app
             .search_results
@@ -110,14 +147,48 @@ fn scroll_results_down(app: &mut App) {
 }
 
 pub fn get_data_length(app: &App) -> u16 {
-    let data_length = match app.active_display_block {
+    let data = match app.active_display_block {
         ActiveDisplayBlock::SearchResultBlock => match app.search_results.selected_tab {
-            SelectedSearchTab::Manga => app.search_results.manga.as_ref().unwrap().data.len(),
-            SelectedSearchTab::Anime => app.search_results.anime.as_ref().unwrap().data.len(),
+            SelectedSearchTab::Manga => app.search_results.manga.as_ref().map(|d| d.data.len()),
+            SelectedSearchTab::Anime => app.search_results.anime.as_ref().map(|d| d.data.len()),
         },
-        ActiveDisplayBlock::MangaRanking => app.manga_ranking_data.as_ref().unwrap().data.len(),
-        ActiveDisplayBlock::AnimeRanking => app.anime_ranking_data.as_ref().unwrap().data.len(),
-        _ => app.search_results.anime.as_ref().unwrap().data.len(),
+        ActiveDisplayBlock::MangaRanking => app.manga_ranking_data.as_ref().map(|d| d.data.len()),
+        ActiveDisplayBlock::AnimeRanking => app.anime_ranking_data.as_ref().map(|d| d.data.len()),
+        ActiveDisplayBlock::UserMangaList => {
+            app.search_results.manga.as_ref().map(|d| d.data.len())
+        }
+        _ => app.search_results.anime.as_ref().map(|d| d.data.len()),
     };
-    data_length as u16
+    data.unwrap_or(0) as u16
+}
+
+fn has_next_page(app: &App) -> bool {
+    match app.active_display_block {
+        ActiveDisplayBlock::SearchResultBlock
+            if app.search_results.selected_tab == SelectedSearchTab::Manga =>
+        {
+            app.search_results
+                .manga
+                .as_ref()
+                .is_some_and(|d| d.has_next_page())
+        }
+        ActiveDisplayBlock::MangaRanking => app
+            .manga_ranking_data
+            .as_ref()
+            .is_some_and(|d| d.has_next_page()),
+        ActiveDisplayBlock::AnimeRanking => app
+            .anime_ranking_data
+            .as_ref()
+            .is_some_and(|d| d.has_next_page()),
+        ActiveDisplayBlock::UserMangaList => app
+            .search_results
+            .manga
+            .as_ref()
+            .is_some_and(|d| d.has_next_page()),
+        _ => app
+            .search_results
+            .anime
+            .as_ref()
+            .is_some_and(|d| d.has_next_page()),
+    }
 }
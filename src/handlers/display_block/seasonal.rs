@@ -1,8 +1,6 @@
-use chrono::Datelike;
-
 use super::result::handle_result_block;
 use crate::{
-    api::model::Season,
+    api::model::{AnimeSeason, Season},
     app::{ActiveDisplayBlock, App},
     event::Key,
     network::IoEvent,
@@ -16,7 +14,19 @@ pub fn handler(key: Key, app: &mut App) {
     } else {
         match key {
             // Key::Enter => open anime detail),
-            k if k == app.app_config.keys.toggle => app.popup = true,
+            k if k == app.app_config.keys.toggle => {
+                app.anime_season.popup_season = app.anime_season.anime_season.clone();
+                app.popup = true;
+            }
+            k if k == app.app_config.keys.previous_season => {
+                load_season(app, app.anime_season.anime_season.prev())
+            }
+            k if k == app.app_config.keys.next_season => {
+                load_season(app, app.anime_season.anime_season.next())
+            }
+            k if k == app.app_config.keys.current_season => {
+                load_season(app, AnimeSeason::current())
+            }
 
             // Key::Char('s') => app.active_display_block = ActiveDisplayBlock::,
             _ => handle_result_block(key, app),
@@ -24,45 +34,53 @@ pub fn handler(key: Key, app: &mut App) {
     }
 }
 
+/// Newest season that can be picked, MAL already lists the upcoming one
+fn latest_season() -> AnimeSeason {
+    AnimeSeason::current().next()
+}
+
+fn load_season(app: &mut App, season: AnimeSeason) {
+    let season = season.clamp_to_available(&latest_season());
+    if season == app.anime_season.anime_season {
+        return;
+    }
+    app.anime_season.popup_season = season;
+    reload_seasonal(app);
+}
+
 fn reload_seasonal(app: &mut App) {
     app.reset_result_index();
     app.active_display_block = ActiveDisplayBlock::Loading;
     app.popup = false;
-    app.anime_season.anime_season.season = get_season(app);
-    app.anime_season.anime_season.year = app.anime_season.selected_year as u64;
+    app.anime_season.anime_season = app.anime_season.popup_season.clone();
     app.dispatch(IoEvent::GetSeasonalAnime);
 }
 
 fn handle_popup(key: Key, app: &mut App) {
     let is_season_selected = app.anime_season.popup_season_highlight;
+    let selected = &app.anime_season.popup_season;
+    let season_index = selected.season.index().unwrap_or(0);
     match key {
         k if k == app.app_config.keys.toggle => {
             app.anime_season.popup_season_highlight = !is_season_selected;
         }
 
         k if common::down_event(k) => {
-            if is_season_selected {
-                app.anime_season.selected_season = (app.anime_season.selected_season + 1) % 4;
-            } else if app.anime_season.selected_year > 1917 {
-                // Ensure the selected year does not go below 1917, which is the last year available
-                app.anime_season.selected_year -= 1;
+            let season = if is_season_selected {
+                AnimeSeason::new(selected.year, Season::from_index(season_index + 1))
             } else {
-                app.anime_season.selected_year = 1917;
-            }
+                AnimeSeason::new(selected.year.saturating_sub(1), selected.season.clone())
+            };
+            app.anime_season.popup_season = season.clamp_to_available(&latest_season());
         }
 
         k if common::up_event(k) => {
-            if is_season_selected {
-                if app.anime_season.selected_season == 0 {
-                    app.anime_season.selected_season = 3;
-                } else {
-                    app.anime_season.selected_season = (app.anime_season.selected_season - 1) % 4;
-                }
-            } else if app.anime_season.selected_year < chrono::Utc::now().year_ce().1 as u16 {
-                app.anime_season.selected_year += 1;
+            let season = if is_season_selected {
+                AnimeSeason::new(selected.year, Season::from_index(season_index + 3))
             } else {
-                app.anime_season.selected_year = chrono::Utc::now().year_ce().1 as u16;
-            }
+                AnimeSeason::new(selected.year + 1, selected.season.clone())
+            };
+            app.anime_season.popup_season = season.clamp_to_available(&latest_season());
         }
 
         Key::Enter => {
@@ -73,14 +91,3 @@ fn handle_popup(key: Key, app: &mut App) {
         _ => {}
     }
 }
-
-fn get_season(app: &App) -> Season {
-    let season = app.anime_season.selected_season;
-    match season {
-        0 => Season::Winter,
-        1 => Season::Spring,
-        2 => Season::Summer,
-        3 => Season::Fall,
-        _ => panic!("Invalid season"),
-    }
-}
@@ -1,37 +1,46 @@
-use crate::{
-    app::{ActiveDisplayBlock, App},
-    event::Key,
-    handlers::user::is_user_manga_list_data_available,
-    network::IoEvent,
-};
+use crate::{app::App, event::Key, handlers::common, user_list::ListSort};
 
 use super::result;
 
 pub fn handler(key: Key, app: &mut App) {
+    if app.popup {
+        handle_sort_popup(key, app);
+        return;
+    }
     match key {
         k if k == app.app_config.keys.toggle => change_tab(app),
-        k if k == app.app_config.keys.open_popup => {} // i don't remember what is the popup for
+        k if k == app.app_config.keys.open_popup => open_sort_popup(app),
+        k if k == app.app_config.keys.filter => {
+            app.list_filter_input = Some(app.manga_list_filter.to_string());
+        }
         _ => result::handler(key, app),
     }
 }
 
-// fn open_popup(app: &mut App) {}
-
+/// The synced list is only filtered again, no request is sent
 fn change_tab(app: &mut App) {
-    let next_status = app.next_anime_list_status();
-    app.anime_list_status = next_status.clone();
-    let (is_data_available, is_next, index) = is_user_manga_list_data_available(app);
+    app.manga_list_status = app.next_manga_list_status();
+    app.show_manga_list();
+}
 
-    app.reset_result_index();
+fn open_sort_popup(app: &mut App) {
+    app.list_sort_index = ListSort::ALL
+        .iter()
+        .position(|s| *s == app.manga_list_sort)
+        .unwrap_or(0);
+    app.popup = true;
+}
 
-    if is_next {
-        app.load_next_route();
-        return;
-    }
-    if is_data_available {
-        app.load_route(index.unwrap());
-    } else {
-        app.active_display_block = ActiveDisplayBlock::Loading;
-        app.dispatch(IoEvent::GetAnimeList(next_status));
+fn handle_sort_popup(key: Key, app: &mut App) {
+    let len = ListSort::ALL.len();
+    match key {
+        k if common::up_event(k) => app.list_sort_index = (app.list_sort_index + len - 1) % len,
+        k if common::down_event(k) => app.list_sort_index = (app.list_sort_index + 1) % len,
+        Key::Enter => {
+            app.popup = false;
+            app.manga_list_sort = ListSort::ALL[app.list_sort_index];
+            app.show_manga_list();
+        }
+        _ => {}
     }
 }
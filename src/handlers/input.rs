@@ -60,21 +60,17 @@ pub fn handler(key: Key, app: &mut App) {
         }
 
         // Move cursor to left
-        Key::Left | Key::Ctrl('b') => {
-            if !app.input.is_empty() && app.input_idx > 0 {
-                let last_c = app.input[app.input_idx - 1];
-                app.input_idx -= 1;
-                app.input_cursor_position -= compute_character_width(last_c);
-            }
+        Key::Left | Key::Ctrl('b') if !app.input.is_empty() && app.input_idx > 0 => {
+            let last_c = app.input[app.input_idx - 1];
+            app.input_idx -= 1;
+            app.input_cursor_position -= compute_character_width(last_c);
         }
 
         // Move cursor to right
-        Key::Right | Key::Ctrl('f') => {
-            if app.input_idx < app.input.len() {
-                let next_c = app.input[app.input_idx];
-                app.input_idx += 1;
-                app.input_cursor_position += compute_character_width(next_c);
-            }
+        Key::Right | Key::Ctrl('f') if app.input_idx < app.input.len() => {
+            let next_c = app.input[app.input_idx];
+            app.input_idx += 1;
+            app.input_cursor_position += compute_character_width(next_c);
         }
 
         // end input mode
@@ -108,19 +104,17 @@ pub fn handler(key: Key, app: &mut App) {
         }
 
         // delete character before cursor
-        Key::Backspace | Key::Ctrl('h') => {
-            if !app.input.is_empty() && app.input_idx > 0 {
-                let last_c = app.input.remove(app.input_idx - 1);
-                app.input_idx -= 1;
-                app.input_cursor_position -= compute_character_width(last_c);
-            }
+        Key::Backspace | Key::Ctrl('h') if !app.input.is_empty() && app.input_idx > 0 => {
+            let last_c = app.input.remove(app.input_idx - 1);
+            app.input_idx -= 1;
+            app.input_cursor_position -= compute_character_width(last_c);
         }
 
         // ! not working ??
-        Key::Delete | Key::Ctrl('d') => {
-            if !app.input.is_empty() && app.input_idx < app.input.len() {
-                app.input.remove(app.input_idx);
-            }
+        Key::Delete | Key::Ctrl('d')
+            if !app.input.is_empty() && app.input_idx < app.input.len() =>
+        {
+            app.input.remove(app.input_idx);
         }
 
         _ => {}
@@ -0,0 +1,52 @@
+use crate::app::{ActiveDisplayBlock, App};
+use crate::event::Key;
+use crate::user_list::ListFilter;
+
+/// Typing in the filter prompt of a user list, enter applies the filter
+pub fn handler(key: Key, app: &mut App) {
+    let Some(input) = app.list_filter_input.as_mut() else {
+        return;
+    };
+    match key {
+        Key::Char(c) => input.push(c),
+        Key::Backspace => {
+            input.pop();
+        }
+        // Deletes everything in input
+        Key::Ctrl('u') | Key::Ctrl('l') => input.clear(),
+        Key::Enter => {
+            apply_filter(app);
+            return;
+        }
+        Key::Esc => {
+            app.list_filter_input = None;
+        }
+        _ => return,
+    }
+    app.list_filter_error = None;
+}
+
+fn apply_filter(app: &mut App) {
+    let input = app.list_filter_input.clone().unwrap_or_default();
+    let filter = match input.parse::<ListFilter>() {
+        Ok(filter) => filter,
+        Err(e) => {
+            // the prompt stays open to fix it
+            app.list_filter_error = Some(e);
+            return;
+        }
+    };
+    app.list_filter_input = None;
+    app.list_filter_error = None;
+    match app.active_display_block {
+        ActiveDisplayBlock::UserAnimeList => {
+            app.anime_list_filter = filter;
+            app.show_anime_list();
+        }
+        ActiveDisplayBlock::UserMangaList => {
+            app.manga_list_filter = filter;
+            app.show_manga_list();
+        }
+        _ => {}
+    }
+}
@@ -3,6 +3,7 @@ pub mod common;
 mod display_block;
 mod help;
 mod input;
+mod list_filter;
 mod option;
 mod user;
 use crate::api::model::{AnimeRankingType, MangaRankingType, Media};
@@ -15,6 +16,7 @@ use crate::network::IoEvent;
 
 use common::get_lowercase_key;
 pub use input::handler as input_handler;
+pub use list_filter::handler as list_filter_handler;
 use log::warn;
 
 pub fn handle_app(key: Key, app: &mut App) {
@@ -95,10 +97,8 @@ pub fn handle_tab(app: &mut App) {
             app.active_block = ActiveBlock::DisplayBlock;
         }
 
-        ActiveBlock::DisplayBlock => {
-            if !app.popup {
-                app.active_block = ActiveBlock::Input;
-            }
+        ActiveBlock::DisplayBlock if !app.popup => {
+            app.active_block = ActiveBlock::Input;
         }
         _ => {}
     }
@@ -111,24 +111,22 @@ pub fn handle_back_tab(app: &mut App) {
             app.active_block = ActiveBlock::DisplayBlock;
         }
 
-        ActiveBlock::DisplayBlock => {
-            if !app.popup {
-                app.active_block = ActiveBlock::TopThree;
-            }
+        ActiveBlock::DisplayBlock if !app.popup => {
+            app.active_block = ActiveBlock::TopThree;
         }
 
         ActiveBlock::TopThree => {
-            app.library.selected_index = GENERAL_OPTIONS_RANGE.end;
+            app.library.selected_index = 10; // out of range to not display anything
             app.active_block = ActiveBlock::Option;
         }
 
         ActiveBlock::Option => {
-            app.library.selected_index = USER_OPTIONS_RANGE.end;
+            app.library.selected_index = 10; // out of range to not display anything
             app.active_block = ActiveBlock::User;
         }
 
         ActiveBlock::User => {
-            app.library.selected_index = ANIME_OPTIONS_RANGE.end;
+            app.library.selected_index = 10; // out of range to not display anything
             app.active_block = ActiveBlock::Anime;
         }
 
@@ -9,7 +9,7 @@ pub fn handler(key: Key, app: &mut App) {
             let next_index = GENERAL_OPTIONS_RANGE.start
                 + common::on_down_press(
                     &GENERAL_OPTIONS,
-                    Some(app.library.selected_index % GENERAL_OPTIONS_RANGE.len()),
+                    app.library.index_in(GENERAL_OPTIONS_RANGE),
                 );
             app.library.selected_index = next_index;
         }
@@ -19,7 +19,7 @@ pub fn handler(key: Key, app: &mut App) {
             let next_index = GENERAL_OPTIONS_RANGE.start
                 + common::on_up_press(
                     &GENERAL_OPTIONS,
-                    Some(app.library.selected_index % GENERAL_OPTIONS_RANGE.len()),
+                    app.library.index_in(GENERAL_OPTIONS_RANGE),
                 );
 
             app.library.selected_index = next_index;
@@ -28,15 +28,15 @@ pub fn handler(key: Key, app: &mut App) {
         Key::Enter => {
             match app.library.selected_index {
                 // Help
-                6 => {}
-                // About
                 7 => {}
-                // Quit
+                // About
                 8 => {}
+                // Quit
+                9 => {}
 
                 _ => {}
             };
-            app.library.selected_index = 9;
+            app.library.selected_index = 10;
         }
         _ => (),
     };
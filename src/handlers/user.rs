@@ -1,5 +1,5 @@
 use super::common;
-use crate::app::{ActiveDisplayBlock, App, Data, USER_OPTIONS, USER_OPTIONS_RANGE};
+use crate::app::{ActiveBlock, ActiveDisplayBlock, App, USER_OPTIONS, USER_OPTIONS_RANGE};
 
 use crate::event::Key;
 use crate::network::IoEvent;
@@ -8,18 +8,12 @@ pub fn handler(key: Key, app: &mut App) {
     match key {
         k if common::down_event(k) => {
             let next_index = USER_OPTIONS_RANGE.start
-                + common::on_down_press(
-                    &USER_OPTIONS,
-                    Some(app.library.selected_index % USER_OPTIONS_RANGE.len()),
-                );
+                + common::on_down_press(&USER_OPTIONS, app.library.index_in(USER_OPTIONS_RANGE));
             app.library.selected_index = next_index;
         }
         k if common::up_event(k) => {
             let next_index = USER_OPTIONS_RANGE.start
-                + common::on_up_press(
-                    &USER_OPTIONS,
-                    Some(app.library.selected_index % USER_OPTIONS_RANGE.len()),
-                );
+                + common::on_up_press(&USER_OPTIONS, app.library.index_in(USER_OPTIONS_RANGE));
             app.library.selected_index = next_index;
         }
 
@@ -31,41 +25,57 @@ pub fn handler(key: Key, app: &mut App) {
                 4 => get_user_anime_list(app),
                 // mangaList
                 5 => get_user_manga_list(app),
+                // profiles
+                6 => get_profiles(app),
                 // This is required because Rust can't tell if this pattern in exhaustive
                 _ => {}
             };
-            app.library.selected_index = 9;
+            app.library.selected_index = 10;
         }
         _ => (),
     };
 }
 
 fn get_user_anime_list(app: &mut App) {
+    if app.anime_list.is_none() {
+        app.active_display_block = ActiveDisplayBlock::Loading;
+        app.dispatch(IoEvent::GetAnimeList);
+        return;
+    }
+    // reuse the list page, it is shown again from the synced list
     let (is_data_available, is_next, index) = is_user_anime_list_data_available(app);
     if is_next {
         app.load_next_route();
-        return;
-    }
-    if is_data_available {
+    } else if is_data_available {
         app.load_route(index.unwrap());
-    } else {
-        app.active_display_block = ActiveDisplayBlock::Loading;
-        app.dispatch(IoEvent::GetAnimeList(app.anime_list_status.clone()));
     }
+    app.show_anime_list();
 }
 
 fn get_user_manga_list(app: &mut App) {
+    if app.manga_list.is_none() {
+        app.active_display_block = ActiveDisplayBlock::Loading;
+        app.dispatch(IoEvent::GetMangaList);
+        return;
+    }
+    // reuse the list page, it is shown again from the synced list
     let (is_data_available, is_next, index) = is_user_manga_list_data_available(app);
     if is_next {
         app.load_next_route();
-        return;
-    }
-    if is_data_available {
+    } else if is_data_available {
         app.load_route(index.unwrap());
-    } else {
-        app.active_display_block = ActiveDisplayBlock::Loading;
-        app.dispatch(IoEvent::GetMangaList(app.manga_list_status.clone()));
     }
+    app.show_manga_list();
+}
+
+fn get_profiles(app: &mut App) {
+    app.profile_index = app
+        .profile_choices()
+        .iter()
+        .position(|p| *p == app.active_profile)
+        .unwrap_or(0);
+    app.active_display_block = ActiveDisplayBlock::Profiles;
+    app.active_block = ActiveBlock::DisplayBlock;
 }
 
 fn get_user_profile(app: &mut App) {
@@ -82,35 +92,27 @@ fn get_user_profile(app: &mut App) {
     }
 }
 
-pub fn is_user_anime_list_data_available(app: &App) -> (bool, bool, Option<u16>) {
+fn is_user_anime_list_data_available(app: &App) -> (bool, bool, Option<u16>) {
     for i in 0..(app.navigator.history.len()) {
         let id: u16 = app.navigator.history[i];
         if app.navigator.data[&id].block == ActiveDisplayBlock::UserAnimeList
             && app.navigator.data[&id].data.is_some()
         {
-            if let Data::UserAnimeList(d) = app.navigator.data[&id].data.as_ref().unwrap() {
-                if d.status == app.anime_list_status {
-                    let is_next = app.navigator.index + 1 == i;
-                    return (true, is_next, Some(id));
-                }
-            }
+            let is_next = app.navigator.index + 1 == i;
+            return (true, is_next, Some(id));
         }
     }
     (false, false, None)
 }
 
-pub fn is_user_manga_list_data_available(app: &App) -> (bool, bool, Option<u16>) {
+fn is_user_manga_list_data_available(app: &App) -> (bool, bool, Option<u16>) {
     for i in 0..(app.navigator.history.len()) {
         let id = app.navigator.history[i];
         if app.navigator.data[&id].block == ActiveDisplayBlock::UserMangaList
             && app.navigator.data[&id].data.is_some()
         {
-            if let Data::UserMangaList(d) = app.navigator.data[&id].data.as_ref().unwrap() {
-                if d.status == app.manga_list_status {
-                    let is_next = app.navigator.index + 1 == i;
-                    return (true, is_next, Some(id));
-                }
-            }
+            let is_next = app.navigator.index + 1 == i;
+            return (true, is_next, Some(id));
         }
     }
     (false, false, None)
@@ -41,6 +41,9 @@ pub mod network;
 /// Events
 pub mod event;
 
+/// Sorting and filtering of the synced user lists
+pub mod user_list;
+
 /// Handlers
 pub mod handlers;
 
@@ -4,26 +4,30 @@ use crossterm::execute;
 use crossterm::terminal;
 use crossterm::{cursor::MoveTo, ExecutableCommand};
 use mal::api::model::RankingType;
+use mal::api::{ApiClient, HttpMalApi, Recorder, ReplayServer, ResponseCache};
+use mal::cli::{Mode, Options};
 use mal::handlers::common;
 use mal::logging::initialize_logging;
 use ratatui::prelude::CrosstermBackend;
 use ratatui::Terminal;
 
+use std::process::ExitCode;
 use std::sync::Arc;
 use std::{
     io::{self}, //Write
     panic,
 };
+use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::Mutex;
 
 use mal::app::*;
-use mal::auth::OAuth;
+use mal::auth::{manager::TokenManager, OAuth};
 // use mal::cli::{Opt, StructOpt};
 use mal::config::{app_config::AppConfig, oauth_config::AuthConfig};
 use mal::event;
 use mal::event::key::Key;
 use mal::handlers;
-use mal::network::{IoEvent, Network};
+use mal::network::{IoEvent, Network, Request};
 use mal::ui;
 
 fn setup_terminal() -> Result<()> {
@@ -64,44 +68,135 @@ fn setup_panic_hook() {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     better_panic::install();
     setup_panic_hook();
-    let exit = mal::cli::handle_args();
-    if exit {
-        return Ok(());
-    }
+    let Some(Options {
+        mode,
+        login_flow,
+        profile,
+    }) = mal::cli::handle_args()
+    else {
+        return Ok(ExitCode::SUCCESS);
+    };
 
     // initialize logging
     initialize_logging();
+    if let Mode::Auth(command) = mode {
+        return Ok(mal::cli::auth::run(command, profile.as_deref(), login_flow).await);
+    }
+    if let Mode::Cache(command) = mode {
+        return Ok(mal::cli::cache::run(command));
+    }
     // Get config
     println!("==> Loading Configiration");
-    let app_config = AppConfig::load()?;
-    println!("==> Auth Configuration Loading");
-    let auth_config = AuthConfig::load()?;
-    println!("==> Refreshing Token");
-    let oauth = OAuth::get_auth_async(auth_config).await?;
-    let (sync_io_tx, sync_io_rx) = std::sync::mpsc::channel::<IoEvent>();
+    let mut app_config = AppConfig::load()?;
+
+    let (tokens, client, auth_config) = match mode {
+        Mode::Replay { dir, port, serve } => {
+            let server = ReplayServer::bind(&dir, port).await?;
+            println!(
+                "==> Replaying {} responses from {} on {}",
+                server.len(),
+                dir.display(),
+                server.base_url()
+            );
+            if serve {
+                let network = server.network_config(&app_config.network);
+                println!("MAL_API_URL={}", network.api_url);
+                println!("MAL_AUTHORIZE_URL={}", network.authorize_url);
+                println!("MAL_TOKEN_URL={}", network.token_url);
+                server.run().await;
+                return Ok(ExitCode::SUCCESS);
+            }
+            app_config.network = server.network_config(&app_config.network);
+            tokio::spawn(server.run());
+            let oauth = ReplayServer::login(&app_config.network).await?;
+            (
+                TokenManager::new(oauth).persist(false),
+                ApiClient::new(&app_config.network)?,
+                None,
+            )
+        }
+        Mode::Offline => {
+            println!("==> Offline, using cached responses only");
+            // nothing is read from oauth2.yml, the profile only picks the caches
+            let auth_config = AuthConfig::default().for_profile(profile.as_deref())?;
+            let oauth = OAuth::load_cached(app_config.network.auth_endpoints(), &auth_config)?;
+            let cache = ResponseCache::new(
+                &app_config.paths.response_cache_dir_path,
+                app_config.response_cache.clone(),
+            )?
+            .for_profile(auth_config.profile.as_deref())?
+            .offline(true);
+            (
+                TokenManager::new(oauth).persist(false),
+                ApiClient::new(&app_config.network)?.with_cache(cache),
+                None,
+            )
+        }
+        mode => {
+            println!("==> Auth Configuration Loading");
+            let auth_config = AuthConfig::load()?.for_profile(profile.as_deref())?;
+            println!("==> Refreshing Token");
+            let endpoints = app_config.network.auth_endpoints();
+            let oauth = OAuth::get_auth_async(auth_config.clone(), endpoints, login_flow).await?;
+            let mut client = ApiClient::new(&app_config.network)?;
+            if app_config.response_cache.enabled {
+                let cache = ResponseCache::new(
+                    &app_config.paths.response_cache_dir_path,
+                    app_config.response_cache.clone(),
+                )?
+                .for_profile(auth_config.profile.as_deref())?;
+                client = client.with_cache(cache);
+            }
+            if let Mode::Record(dir) = mode {
+                println!("==> Recording responses to {}", dir.display());
+                client = client.with_recorder(Recorder::new(dir)?);
+            }
+            let tokens = TokenManager::new(oauth).with_login(auth_config.clone(), login_flow);
+            (tokens, client, Some(auth_config))
+        }
+    };
+    let (io_tx, io_rx) = tokio::sync::mpsc::unbounded_channel::<Request>();
 
     // initialize app state
-    let app = Arc::new(Mutex::new(App::new(sync_io_tx, app_config.clone())));
+    let mut app = App::new(io_tx, app_config.clone());
+    app.rate_limiter = Some(client.rate_limiter().clone());
+    app.response_cache = client.response_cache().cloned();
+    // profiles can only be switched when logging in is possible
+    if let Some(auth_config) = auth_config {
+        app.profiles = auth_config.profile_names();
+    }
+    app.active_profile = profile;
+    let app = Arc::new(Mutex::new(app));
+
+    let images = app_config
+        .image_cache()
+        .inspect_err(|e| tracing::warn!("posters are off, no image cache: {}", e))
+        .ok();
 
     let cloned_app = Arc::clone(&app);
     std::thread::spawn(move || {
-        let mut network = Network::new(oauth, &app, app_config.search_limit);
-        start_network(sync_io_rx, &mut network);
+        let api = HttpMalApi::new(client, tokens);
+        let mut network = Network::new(api, &app, app_config.search_limit);
+        if let Some(images) = images {
+            network = network.with_image_cache(images);
+        }
+        start_network(io_rx, network);
     });
 
     // run ui
     start_ui(app_config, &cloned_app).await?;
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 #[tokio::main]
-async fn start_network(io_rx: std::sync::mpsc::Receiver<IoEvent>, network: &mut Network) {
-    while let Ok(io_event) = io_rx.recv() {
-        network.handle_network_event(io_event).await;
+async fn start_network(mut io_rx: UnboundedReceiver<Request>, mut network: Network) {
+    while let Some(request) = io_rx.recv().await {
+        // a slow request must not hold up the ones after it
+        network.spawn_network_event(request).await;
     }
 }
 
@@ -127,32 +222,15 @@ async fn start_ui(app_config: AppConfig, app: &Arc<Mutex<App>>) -> Result<()> {
     }
 
     loop {
-        let mut app = app.lock().await;
-        if app.exit_flag {
-            // if exit_flag is set, we exit the app
-            break;
-        }
-
-        let current_block = app.active_block;
-        terminal.draw(|f| ui::draw_main_layout(f, &mut app))?;
-
-        if current_block == ActiveBlock::Input {
-            terminal.show_cursor()?;
-        } else {
-            terminal.hide_cursor()?;
+        {
+            let mut app = app.lock().await;
+            if app.exit_flag {
+                // if exit_flag is set, we exit the app
+                break;
+            }
+            draw(&mut terminal, &mut app)?;
         }
 
-        let cursor_offset = if app.size.height > ui::util::SMALL_TERMINAL_HEIGHT {
-            2
-        } else {
-            1
-        };
-
-        terminal.backend_mut().execute(MoveTo(
-            cursor_offset + app.input_cursor_position,
-            cursor_offset,
-        ))?;
-
         /*
         there are five blocks:
             1.Input
@@ -178,12 +256,17 @@ async fn start_ui(app_config: AppConfig, app: &Arc<Mutex<App>>) -> Result<()> {
         we switch between blocks by pressing Tab and between display by input and navigation
         we will implement a stack for display block to allow going back and forth
                 */
+        // the network locks the app to show its results, it stays unlocked while waiting on keys
         if let event::Event::Input(key) = events.next()? {
+            let mut app = app.lock().await;
             let key = common::get_lowercase_key(key);
 
             let active_block = app.active_block;
             // change the default of menu selecting to None when leaving the block
-            if key == Key::Tab {
+            if app.list_filter_input.is_some() {
+                // every key is typed into the filter prompt, `q` included
+                handlers::list_filter_handler(key, &mut app);
+            } else if key == Key::Tab {
                 // handle navigation between block
                 handlers::handle_tab(&mut app);
             } else if key == Key::BackTab {
@@ -208,3 +291,29 @@ async fn start_ui(app_config: AppConfig, app: &Arc<Mutex<App>>) -> Result<()> {
     cleanup_terminal()?;
     Ok(())
 }
+
+/// Draws a frame and places the cursor of the search input
+fn draw(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
+    let current_block = app.active_block;
+    terminal.draw(|f| ui::draw_main_layout(f, app))?;
+    // the cards just drawn may be missing their posters
+    app.request_thumbnails();
+
+    if current_block == ActiveBlock::Input {
+        terminal.show_cursor()?;
+    } else {
+        terminal.hide_cursor()?;
+    }
+
+    let cursor_offset = if app.size.height > ui::util::SMALL_TERMINAL_HEIGHT {
+        2
+    } else {
+        1
+    };
+
+    terminal.backend_mut().execute(MoveTo(
+        cursor_offset + app.input_cursor_position,
+        cursor_offset,
+    ))?;
+    Ok(())
+}
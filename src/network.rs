@@ -1,22 +1,27 @@
 use crate::{
     api::{
-        self, model::*, GetAnimeDetailQuery, GetAnimeRankingQuery, GetMangaDetailQuery,
-        GetMangaRankingQuery, GetSeasonalAnimeQuery, GetSuggestedAnimeQuery,
-        GetUserInformationQuery, UpdateUserAnimeListStatusQuery, UpdateUserMangaStatus,
+        self, model::*, pagination, GetAnimeDetailQuery, GetAnimeRankingQuery,
+        GetForumTopicDetailQuery, GetForumTopicsQuery, GetMangaDetailQuery, GetMangaRankingQuery,
+        GetSeasonalAnimeQuery, GetSuggestedAnimeQuery, GetUserInformationQuery, HttpMalApi,
+        ImageCache, ImageKey, ImageKind, ImageSize, MalApi, UpdateUserAnimeListStatusQuery,
+        UpdateUserMangaStatus,
     },
     app::{
-        ActiveBlock, ActiveDisplayBlock, App, Data, Route, SelectedSearchTab, TopThreeBlock,
-        UserAnimeList, UserMangaList,
+        ActiveBlock, ActiveDisplayBlock, App, Data, ForumThread, ForumTopics, LoginState, Route,
+        SelectedSearchTab, Thumbnail, TopThreeBlock, FORUM_POSTS_PER_PAGE,
     },
-    auth::OAuth,
+    user_list::USER_LIST_PAGE_LIMIT,
 };
-use bytes::Bytes;
-use std::path::{Path, PathBuf};
+use futures::{future, TryStreamExt};
+use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::warn;
+use tokio::sync::{Mutex, MutexGuard, Notify};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum IoEvent {
     GetSearchResults(String),
     GetAnimeSearchResults(String),
@@ -29,33 +34,354 @@ pub enum IoEvent {
     GetSuggestedAnime,
     UpdateAnimeListStatus(u64, UpdateUserAnimeListStatusQuery),
     DeleteAnimeListStatus(String),
-    GetAnimeList(Option<UserWatchStatus>),
-    GetMangaList(Option<UserReadStatus>),
+    /// Syncs the whole anime list of the user
+    GetAnimeList,
+    /// Syncs the whole manga list of the user
+    GetMangaList,
     UpdateMangaListStatus(u64, UpdateUserMangaStatus),
     DeleteMangaListStatus(String),
     GetUserInfo,
     GetTopThree(TopThreeBlock),
+    GetNextPage,
+    GetAnimeDiscussion(u64),
+    GetMangaDiscussion(u64),
+    GetForumTopic(u64, u64),
+    /// Log in again after the session expired
+    Login,
+    /// Use the account of another profile, `None` being the default one
+    SwitchProfile(Option<String>),
+    /// Card posters of the page on screen, by the url of their medium picture
+    GetThumbnails(Vec<(ImageKind, u64, String)>),
+}
+
+impl IoEvent {
+    /// Whether the event replaces the page on screen, making the ones before it stale
+    pub fn is_navigation(&self) -> bool {
+        matches!(
+            self,
+            IoEvent::GetSearchResults(_)
+                | IoEvent::GetAnimeSearchResults(_)
+                | IoEvent::GetMangaSearchResults(_)
+                | IoEvent::GetAnime(_)
+                | IoEvent::GetManga(_)
+                | IoEvent::GetAnimeRanking(_)
+                | IoEvent::GetMangaRanking(_)
+                | IoEvent::GetSeasonalAnime
+                | IoEvent::GetSuggestedAnime
+                | IoEvent::GetAnimeList
+                | IoEvent::GetMangaList
+                | IoEvent::GetUserInfo
+                | IoEvent::GetAnimeDiscussion(_)
+                | IoEvent::GetMangaDiscussion(_)
+                | IoEvent::GetForumTopic(_, _)
+        )
+    }
+
+    /// Whether the event is fetched quietly, without showing loading or needing a session
+    pub fn is_background(&self) -> bool {
+        matches!(self, IoEvent::GetThumbnails(_))
+    }
+
+    /// Whether a copy of the event still in flight can answer for it, list changes
+    /// and logins are always sent
+    pub fn can_merge(&self) -> bool {
+        !matches!(
+            self,
+            IoEvent::UpdateAnimeListStatus(_, _)
+                | IoEvent::DeleteAnimeListStatus(_)
+                | IoEvent::UpdateMangaListStatus(_, _)
+                | IoEvent::DeleteMangaListStatus(_)
+                | IoEvent::Login
+                | IoEvent::SwitchProfile(_)
+        )
+    }
+}
+
+/// Tells an in-flight request that its result is not wanted anymore
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<CancelState>);
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the request is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            // created before the check, so a cancel in between still wakes it
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// An event sent by [`App::dispatch`], with its id and the token to cancel it
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub id: u64,
+    pub event: IoEvent,
+    pub cancel: CancelToken,
+}
+
+/// A request nobody tracks, e.g. sent by a test
+impl From<IoEvent> for Request {
+    fn from(event: IoEvent) -> Self {
+        Self {
+            id: 0,
+            event,
+            cancel: CancelToken::default(),
+        }
+    }
 }
 
 #[derive(Clone)]
-pub struct Network<'a> {
-    oauth: OAuth,
+pub struct Network<A: MalApi = HttpMalApi> {
+    api: A,
     large_search_limit: u64,
     // small_search_limit: u64,
-    app: &'a Arc<Mutex<App>>,
+    app: Arc<Mutex<App>>,
+    /// the event that found the session expired, sent again after logging in
+    retry: Arc<std::sync::Mutex<Option<IoEvent>>>,
+    /// events still being fetched, the app shows loading until the last one is done
+    in_flight: Arc<AtomicUsize>,
+    /// the request being fetched, its result is dropped once this is cancelled
+    cancel: CancelToken,
+    /// where posters and card thumbnails are kept, none are shown without it
+    images: Option<ImageCache>,
+    /// settings the request reads, copied in `prepare` so fetching does not lock the app
+    settings: RequestSettings,
+}
+
+/// What a request needs to know of the app before it has a result to show
+#[derive(Clone, Copy, Default)]
+struct RequestSettings {
+    nsfw: bool,
+    picker: Option<Picker>,
 }
 
-impl<'a> Network<'a> {
-    pub fn new(oauth: OAuth, app: &'a Arc<Mutex<App>>, search_limit: u64) -> Self {
+impl RequestSettings {
+    fn of(app: &App) -> Self {
         Self {
-            oauth,
+            nsfw: app.app_config.nsfw,
+            picker: app.picker,
+        }
+    }
+}
+
+/// Finishes a request whose fetch never got to, because it panicked or was dropped.
+/// Otherwise the app would show loading forever and merge every later copy of it away.
+struct FinishGuard<A: MalApi + Clone + Send + Sync + 'static> {
+    network: Option<Network<A>>,
+    request: Request,
+}
+
+impl<A> Drop for FinishGuard<A>
+where
+    A: MalApi + Clone + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        let Some(network) = self.network.take() else {
+            return;
+        };
+        warn!("request {} ended without finishing", self.request.id);
+        // the app can't be locked from here, and the runtime may be shutting down
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let request = self.request.clone();
+            runtime.spawn(async move { network.finish(&request, false).await });
+        }
+    }
+}
+
+impl<A> Network<A>
+where
+    A: MalApi + Clone + Send + Sync + 'static,
+{
+    pub fn new(api: A, app: &Arc<Mutex<App>>, search_limit: u64) -> Self {
+        Self {
+            api,
             large_search_limit: search_limit,
             // small_search_limit: 3,
-            app,
+            app: Arc::clone(app),
+            retry: Arc::default(),
+            in_flight: Arc::default(),
+            cancel: CancelToken::default(),
+            images: None,
+            settings: RequestSettings::default(),
         }
     }
 
-    pub async fn handle_network_event(&mut self, io_event: IoEvent) {
+    /// Keeps the posters of the details pages and cards in `images`
+    pub fn with_image_cache(mut self, images: ImageCache) -> Self {
+        self.images = Some(images);
+        self
+    }
+
+    /// Handles an event to the end before returning
+    pub async fn handle_network_event(&mut self, request: impl Into<Request>) {
+        if let Some(request) = self.prepare(request.into()).await {
+            self.clone().fetch(request).await;
+        }
+    }
+
+    /// Fetches an event in the background, so a slow request does not hold up the next ones.
+    /// Logging in, switching profiles and refreshing the token still happen one at a time.
+    pub async fn spawn_network_event(
+        &mut self,
+        request: impl Into<Request>,
+    ) -> Option<JoinHandle<()>> {
+        let request = self.prepare(request.into()).await?;
+        Some(tokio::spawn(self.clone().fetch(request)))
+    }
+
+    /// Gets the session ready for a request, returning the request to fetch
+    async fn prepare(&mut self, mut request: Request) -> Option<Request> {
+        if request.event.is_background() {
+            if request.cancel.is_cancelled() {
+                self.finish(&request, false).await;
+                return None;
+            }
+            self.settings = RequestSettings::of(&*self.app.lock().await);
+            return Some(request);
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        if request.cancel.is_cancelled() {
+            self.finish(&request, false).await;
+            return None;
+        }
+        if let IoEvent::Login = request.event {
+            match self.login().await {
+                Some(retry) => request.event = retry,
+                None => {
+                    self.finish(&request, false).await;
+                    return None;
+                }
+            }
+        }
+        if let IoEvent::SwitchProfile(profile) = &request.event {
+            match self.switch_profile(profile.clone()).await {
+                Some(next) => request.event = next,
+                None => {
+                    self.finish(&request, false).await;
+                    return None;
+                }
+            }
+        }
+        {
+            let mut app = self.app.lock().await;
+            // an expired session the user walked away from is found again if it still is
+            app.login = None;
+            // another event may have finished since this one was dispatched
+            app.is_loading = true;
+            self.settings = RequestSettings::of(&app);
+        }
+
+        match self.api.refresh_auth().await {
+            Ok(()) => Some(request),
+            Err(e) => {
+                {
+                    let mut app = self.app.lock().await;
+                    app.write_error(e.into());
+                    app.active_display_block = ActiveDisplayBlock::Error;
+                }
+                self.finish(&request, true).await;
+                None
+            }
+        }
+    }
+
+    async fn fetch(mut self, request: Request) {
+        self.cancel = request.cancel.clone();
+        let mut guard = FinishGuard {
+            network: Some(self.clone()),
+            request: request.clone(),
+        };
+        tokio::select! {
+            _ = api::cache::track_stale(self.dispatch_event(request.event.clone())) => {}
+            _ = request.cancel.cancelled() => debug!("request {} cancelled", request.id),
+        }
+        guard.network = None;
+        self.finish(&request, true).await;
+    }
+
+    /// Marks a request as done, keeping it for later when it found the session expired
+    async fn finish(&self, request: &Request, retry: bool) {
+        let mut app = self.app.lock().await;
+        app.finish_request(request.id);
+        if request.event.is_background() {
+            return;
+        }
+        if self.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            app.is_loading = false;
+        }
+        if retry && !request.cancel.is_cancelled() && app.login == Some(LoginState::Expired) {
+            app.active_display_block = ActiveDisplayBlock::Login;
+            *self.retry.lock().unwrap_or_else(|e| e.into_inner()) = Some(request.event.clone());
+        }
+    }
+
+    /// Logs in again, returning the event to send again once it worked
+    async fn login(&mut self) -> Option<IoEvent> {
+        let url = match self.api.begin_login() {
+            Ok(url) => url,
+            Err(e) => {
+                self.app.lock().await.login = Some(LoginState::Failed(e.to_string()));
+                return None;
+            }
+        };
+        self.app.lock().await.login = Some(LoginState::Waiting(url.to_string()));
+
+        // the app stays unlocked while waiting, so the url keeps being drawn
+        let result = self.api.finish_login().await;
+        let mut app = self.app.lock().await;
+        match result {
+            Ok(()) => {
+                app.login = None;
+                app.active_display_block = app.navigator.get_current_block();
+                self.retry.lock().unwrap_or_else(|e| e.into_inner()).take()
+            }
+            Err(e) => {
+                app.login = Some(LoginState::Failed(e.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Swaps the account, returning the event that loads the new user
+    async fn switch_profile(&mut self, profile: Option<String>) -> Option<IoEvent> {
+        let result = self.api.switch_profile(profile.as_deref());
+        let mut app = self.app.lock().await;
+        match result {
+            Ok(()) => {
+                // nothing of the previous user may be shown anymore
+                app.clear_user_data();
+                app.active_profile = profile;
+                *self.retry.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                Some(IoEvent::GetUserInfo)
+            }
+            Err(e) => {
+                app.write_error(e.into());
+                app.active_display_block = ActiveDisplayBlock::Error;
+                None
+            }
+        }
+    }
+
+    async fn dispatch_event(&self, io_event: IoEvent) {
         match io_event {
             IoEvent::GetSearchResults(q) => self.get_search_results(q).await,
 
@@ -67,9 +393,9 @@ impl<'a> Network<'a> {
 
             IoEvent::GetSuggestedAnime => self.get_suggested().await,
 
-            IoEvent::GetAnimeList(s) => self.get_user_anime_list(s).await,
+            IoEvent::GetAnimeList => self.get_user_anime_list().await,
 
-            IoEvent::GetMangaList(s) => self.get_user_manga_list(s).await,
+            IoEvent::GetMangaList => self.get_user_manga_list().await,
 
             IoEvent::GetAnime(id) => self.get_anime_details(id).await,
 
@@ -85,6 +411,11 @@ impl<'a> Network<'a> {
             // IoEvent::DeleteMangaListStatus(String) => {}
             IoEvent::GetUserInfo => self.get_user_info().await,
             IoEvent::GetTopThree(r) => self.get_top_three(r).await,
+            IoEvent::GetNextPage => self.get_next_page().await,
+            IoEvent::GetAnimeDiscussion(id) => self.get_anime_discussion(id).await,
+            IoEvent::GetMangaDiscussion(id) => self.get_manga_discussion(id).await,
+            IoEvent::GetForumTopic(id, offset) => self.get_forum_topic(id, offset).await,
+            IoEvent::GetThumbnails(covers) => self.get_thumbnails(covers).await,
 
             IoEvent::UpdateAnimeListStatus(anime_id, query) => {
                 self.update_anime_list_status(anime_id, query).await
@@ -94,23 +425,127 @@ impl<'a> Network<'a> {
             }
             _ => (),
         }
+    }
 
-        let mut app = self.app.lock().await;
-        app.is_loading = false
+    /// Locks the app to show a result, unless the request was cancelled meanwhile
+    async fn lock_for_result(&self) -> Option<MutexGuard<'_, App>> {
+        let app = self.app.lock().await;
+        // checked under the lock, the app cancels while holding it
+        (!self.cancel.is_cancelled()).then_some(app)
     }
 
-    async fn get_anime_details(&mut self, id: u64) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    /// Appends the next page of the list on screen to its results and route
+    async fn get_next_page(&self) {
+        let (block, tab) = {
+            let app = self.app.lock().await;
+            (app.active_display_block, app.search_results.selected_tab)
+        };
 
-        let query = GetAnimeDetailQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-            nsfw: app.app_config.nsfw,
+        match block {
+            ActiveDisplayBlock::AnimeRanking => {
+                self.append_next_page(|app| app.anime_ranking_data.as_mut())
+                    .await
+            }
+            ActiveDisplayBlock::MangaRanking => {
+                self.append_next_page(|app| app.manga_ranking_data.as_mut())
+                    .await
+            }
+            ActiveDisplayBlock::SearchResultBlock if tab == SelectedSearchTab::Manga => {
+                self.append_next_page(|app| app.search_results.manga.as_mut())
+                    .await
+            }
+            ActiveDisplayBlock::SearchResultBlock
+            | ActiveDisplayBlock::Suggestions
+            | ActiveDisplayBlock::Seasonal => {
+                self.append_next_page(|app| app.search_results.anime.as_mut())
+                    .await
+            }
+            ActiveDisplayBlock::ForumTopics => {
+                self.append_next_page(|app| app.forum_topics.as_mut().map(|f| &mut f.topics))
+                    .await
+            }
+            _ => (),
+        }
+    }
+
+    /// Fetches the page after the list `results` picks, with the app unlocked meanwhile
+    async fn append_next_page<T, F>(&self, results: F)
+    where
+        T: serde::de::DeserializeOwned + Clone + std::fmt::Debug + Send,
+        F: Fn(&mut App) -> Option<&mut PageableData<Vec<T>>> + Send + Sync,
+    {
+        let paging = match results(&mut *self.app.lock().await) {
+            Some(list) => list.paging.clone(),
+            None => return,
         };
 
-        match api::get_anime_details(id, &query, &self.oauth).await {
-            Ok(result) => {
-                app.anime_details = Some(result.clone());
+        let next = self.api.get_next_page(&paging).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match next {
+            Ok(next) => {
+                // the list may have been replaced while the page loaded
+                let list = results(&mut app).filter(|list| list.paging.next == paging.next);
+                if let (Some(list), Some(page)) = (list, next) {
+                    list.append(page);
+                }
+                app.update_current_route_data();
+            }
+            Err(e) => {
+                app.write_error(e);
+                app.active_display_block = ActiveDisplayBlock::Error;
+            }
+        }
+    }
+
+    /// Lists the episode discussion threads of an anime
+    async fn get_anime_discussion(&self, anime_id: u64) {
+        let title = self
+            .app
+            .lock()
+            .await
+            .anime_details
+            .as_ref()
+            .map_or(String::new(), |a| a.title.clone());
+        let query = GetForumTopicsQuery {
+            q: Some(format!("{} Episode", title)),
+            anime_id: Some(anime_id),
+            limit: self.large_search_limit,
+            ..Default::default()
+        };
+        self.get_forum_topics(&query, title).await;
+    }
+
+    /// Lists the chapter discussion threads of a manga
+    async fn get_manga_discussion(&self, manga_id: u64) {
+        let title = self
+            .app
+            .lock()
+            .await
+            .manga_details
+            .as_ref()
+            .map_or(String::new(), |m| m.title.clone());
+        let query = GetForumTopicsQuery {
+            q: Some(format!("{} Chapter", title)),
+            manga_id: Some(manga_id),
+            limit: self.large_search_limit,
+            ..Default::default()
+        };
+        self.get_forum_topics(&query, title).await;
+    }
+
+    async fn get_forum_topics(&self, query: &GetForumTopicsQuery, title: String) {
+        let result = self.api.get_forum_topics(query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
+            Ok(topics) => {
+                app.forum_topics = Some(ForumTopics {
+                    topics,
+                    selected_index: 0,
+                });
             }
             Err(e) => {
                 app.write_error(e);
@@ -119,99 +554,219 @@ impl<'a> Network<'a> {
             }
         }
 
-        let mut image = None;
-        if app.picker.is_some() {
-            image = get_picture(
-                app.app_config.paths.picture_cache_dir_path.clone(),
-                app.anime_details.as_ref().unwrap().id,
-                &app.anime_details.as_ref().unwrap().main_picture,
-                app.app_config.max_cached_images,
-            )
+        let route = Route {
+            data: Some(Data::ForumTopics(app.forum_topics.clone().unwrap())),
+            block: ActiveDisplayBlock::ForumTopics,
+            title: format!("Discussion: {}", title),
+            image: None,
+        };
+        app.active_block = ActiveBlock::DisplayBlock;
+        app.active_display_block = ActiveDisplayBlock::ForumTopics;
+        app.display_block_title = route.title.clone();
+        app.push_navigation_stack(route);
+    }
+
+    /// Opens a page of posts, in place when the reader already shows that topic
+    async fn get_forum_topic(&self, topic_id: u64, offset: u64) {
+        let query = GetForumTopicDetailQuery {
+            limit: FORUM_POSTS_PER_PAGE,
+            offset,
+        };
+
+        let result = self.api.get_forum_topic_detail(topic_id, &query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        let thread = match result {
+            Ok(page) => ForumThread {
+                topic_id,
+                offset,
+                page,
+            },
+            Err(e) => {
+                app.write_error(e);
+                app.active_display_block = ActiveDisplayBlock::Error;
+                return;
+            }
+        };
+        app.forum_thread_scroll = 0;
+
+        if app.active_display_block == ActiveDisplayBlock::ForumTopic {
+            app.forum_thread = Some(thread);
+            app.update_current_route_data();
+            return;
+        }
+
+        let route = Route {
+            data: Some(Data::ForumThread(thread.clone())),
+            block: ActiveDisplayBlock::ForumTopic,
+            title: thread.page.data.title.clone(),
+            image: None,
+        };
+        app.forum_thread = Some(thread);
+        app.active_block = ActiveBlock::DisplayBlock;
+        app.active_display_block = ActiveDisplayBlock::ForumTopic;
+        app.display_block_title = route.title.clone();
+        app.push_navigation_stack(route);
+    }
+
+    /// Fetches the poster and gets it ready for drawing, when the terminal can show pictures
+    async fn get_poster(
+        &self,
+        kind: ImageKind,
+        id: u64,
+        pictures: &Option<Picture>,
+    ) -> Option<Poster> {
+        let picker = self.settings.picker?;
+        let images = self.images.as_ref()?;
+        let path = get_picture(&self.api, images, kind, id, pictures).await?;
+        let decoded = image::open(&path).ok()?;
+        let image = (
+            path.to_string_lossy().to_string(),
+            decoded.width(),
+            decoded.height(),
+        );
+        Some((image, picker.new_resize_protocol(decoded)))
+    }
+
+    /// Fetches the posters of the cards together, then shows them all at once
+    async fn get_thumbnails(&self, covers: Vec<(ImageKind, u64, String)>) {
+        let thumbnails = future::join_all(covers.into_iter().map(|(kind, id, url)| async move {
+            ((kind, id), self.get_thumbnail(kind, id, &url).await)
+        }))
+        .await;
+
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        for (key, thumbnail) in thumbnails {
+            // left out when its page is not on screen anymore
+            if let Some(slot) = app.thumbnails.get_mut(&key) {
+                *slot = thumbnail;
+            }
+        }
+    }
+
+    /// The poster of a card ready for drawing, from its medium picture
+    async fn get_thumbnail(&self, kind: ImageKind, id: u64, url: &str) -> Thumbnail {
+        let (Some(picker), Some(images)) = (self.settings.picker, self.images.as_ref()) else {
+            return Thumbnail::Failed;
+        };
+        let key = ImageKey::new(kind, id, ImageSize::Medium);
+        let Some(path) = cached_picture(&self.api, images, &key, url).await else {
+            return Thumbnail::Failed;
+        };
+        match image::open(&path) {
+            Ok(decoded) => Thumbnail::Ready(picker.new_resize_protocol(decoded)),
+            Err(e) => {
+                warn!("could not decode the image {}: {}", path.display(), e);
+                Thumbnail::Failed
+            }
+        }
+    }
+
+    async fn get_anime_details(&self, id: u64) {
+        let query = GetAnimeDetailQuery {
+            fields: Some(FieldSet::detail().into()),
+            nsfw: self.settings.nsfw,
+        };
+
+        let anime = match self.api.get_anime_details(id, &query).await {
+            Ok(result) => result,
+            Err(e) => {
+                let Some(mut app) = self.lock_for_result().await else {
+                    return;
+                };
+                app.write_error(e);
+                app.active_display_block = ActiveDisplayBlock::Error;
+                return;
+            }
+        };
+        let poster = self
+            .get_poster(ImageKind::Anime, anime.id, &anime.main_picture)
             .await;
+
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        let image = poster.as_ref().map(|(image, _)| image.clone());
+        if app.picker.is_some() {
             app.media_image = image.clone();
-            app.image_state = Some(
-                app.picker
-                    .as_ref()
-                    .unwrap()
-                    .new_resize_protocol(app.get_picture_from_cache().unwrap()),
-            );
+            app.image_state = poster.map(|(_, state)| state);
         }
 
         let route = Route {
-            data: Some(Data::Anime(app.anime_details.as_ref().unwrap().clone())),
+            data: Some(Data::Anime(anime.clone())),
             block: ActiveDisplayBlock::AnimeDetails,
-            title: app.anime_details.as_ref().unwrap().title.clone(),
+            title: anime.title.clone(),
             image,
         };
+        app.display_block_title = anime.title.clone();
+        app.anime_details = Some(anime);
         app.push_navigation_stack(route);
         app.active_block = ActiveBlock::DisplayBlock;
         app.active_display_block = ActiveDisplayBlock::AnimeDetails;
-        app.display_block_title = app.anime_details.as_ref().unwrap().title.clone();
     }
 
-    async fn get_manga_details(&mut self, id: u64) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
-
+    async fn get_manga_details(&self, id: u64) {
         let query = GetMangaDetailQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-            nsfw: app.app_config.nsfw,
+            fields: Some(FieldSet::detail().into()),
+            nsfw: self.settings.nsfw,
         };
 
-        match api::get_manga_details(id, &query, &self.oauth).await {
-            Ok(result) => {
-                app.manga_details = Some(result.clone());
-            }
+        let manga = match self.api.get_manga_details(id, &query).await {
+            Ok(result) => result,
             Err(e) => {
+                let Some(mut app) = self.lock_for_result().await else {
+                    return;
+                };
                 app.write_error(e);
                 app.active_display_block = ActiveDisplayBlock::Error;
                 return;
             }
-        }
+        };
+        let poster = self
+            .get_poster(ImageKind::Manga, manga.id, &manga.main_picture)
+            .await;
 
-        let mut image = None;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        let image = poster.as_ref().map(|(image, _)| image.clone());
         if app.picker.is_some() {
-            image = get_picture(
-                app.app_config.paths.picture_cache_dir_path.clone(),
-                app.manga_details.as_ref().unwrap().id,
-                &app.manga_details.as_ref().unwrap().main_picture,
-                app.app_config.max_cached_images,
-            )
-            .await;
             app.media_image = image.clone();
-            app.image_state = Some(
-                app.picker
-                    .as_ref()
-                    .unwrap()
-                    .new_resize_protocol(app.get_picture_from_cache().unwrap()),
-            );
+            app.image_state = poster.map(|(_, state)| state);
         }
+
         let route = Route {
-            data: Some(Data::Manga(app.manga_details.as_ref().unwrap().clone())),
+            data: Some(Data::Manga(manga.clone())),
             block: ActiveDisplayBlock::MangaDetails,
-            title: app.manga_details.as_ref().unwrap().title.clone(),
+            title: manga.title.clone(),
             image,
         };
+        app.display_block_title = manga.title.clone();
+        app.manga_details = Some(manga);
         app.push_navigation_stack(route);
         app.active_block = ActiveBlock::DisplayBlock;
         app.active_display_block = ActiveDisplayBlock::MangaDetails;
-        app.display_block_title = app.manga_details.as_ref().unwrap().title.clone();
     }
 
-    async fn get_anime_ranking(&mut self, ranking_type: AnimeRankingType) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_anime_ranking(&self, ranking_type: AnimeRankingType) {
         let query = GetAnimeRankingQuery {
             ranking_type: ranking_type.clone(),
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
             limit: self.large_search_limit,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
             offset: 0,
         };
         let title = format!("Top Anime by {}", ranking_type);
-        match api::get_anime_ranking(&query, &self.oauth).await {
+        let result = self.api.get_anime_ranking(&query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
-                app.anime_ranking_data = Some(result.clone());
+                app.anime_ranking_data = Some(result);
             }
             Err(e) => {
                 app.write_error(e);
@@ -234,14 +789,12 @@ impl<'a> Network<'a> {
         app.display_block_title = title;
     }
 
-    async fn get_manga_ranking(&mut self, ranking_type: MangaRankingType) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_manga_ranking(&self, ranking_type: MangaRankingType) {
         let query = GetMangaRankingQuery {
             ranking_type: ranking_type.clone(),
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
             limit: self.large_search_limit,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
             offset: 0,
         };
         // better title:
@@ -250,9 +803,13 @@ impl<'a> Network<'a> {
             rank = "Popular Manga".to_string();
         }
         let title = format!("Top {}", rank);
-        match api::get_manga_ranking(&query, &self.oauth).await {
+        let result = self.api.get_manga_ranking(&query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
-                app.manga_ranking_data = Some(result.clone());
+                app.manga_ranking_data = Some(result);
             }
             Err(e) => {
                 app.write_error(e);
@@ -276,7 +833,7 @@ impl<'a> Network<'a> {
         app.display_block_title = title;
     }
 
-    async fn get_top_three(&mut self, ranking_type: TopThreeBlock) {
+    async fn get_top_three(&self, ranking_type: TopThreeBlock) {
         match ranking_type {
             TopThreeBlock::Anime(r) => self.get_anime_top_three(r).await,
             TopThreeBlock::Manga(r) => self.get_manga_top_three(r).await,
@@ -284,80 +841,50 @@ impl<'a> Network<'a> {
         }
     }
 
-    async fn get_anime_top_three(&mut self, rank_type: AnimeRankingType) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_anime_top_three(&self, rank_type: AnimeRankingType) {
         let query = GetAnimeRankingQuery {
             ranking_type: rank_type.clone(),
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::top_three().into()),
             limit: 3,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
             offset: 0,
         };
-        match api::get_anime_ranking(&query, &self.oauth).await {
-            Ok(result) => match &rank_type {
+        let result = self
+            .api
+            .get_anime_ranking(&query)
+            .await
+            .and_then(|ranking| top_three(ranking.data, |pair| pair.node));
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
+            Ok(top) => match &rank_type {
                 AnimeRankingType::Airing => {
-                    app.top_three_anime.airing = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.airing = Some(top);
                 }
                 AnimeRankingType::All => {
-                    app.top_three_anime.all = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ])
+                    app.top_three_anime.all = Some(top);
                 }
                 AnimeRankingType::Upcoming => {
-                    app.top_three_anime.upcoming = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.upcoming = Some(top);
                 }
                 AnimeRankingType::ByPopularity => {
-                    app.top_three_anime.popular = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.popular = Some(top);
                 }
                 AnimeRankingType::Favorite => {
-                    app.top_three_anime.favourite = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.favourite = Some(top);
                 }
                 AnimeRankingType::Movie => {
-                    app.top_three_anime.movie = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.movie = Some(top);
                 }
                 AnimeRankingType::OVA => {
-                    app.top_three_anime.ova = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.ova = Some(top);
                 }
                 AnimeRankingType::TV => {
-                    app.top_three_anime.tv = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.tv = Some(top);
                 }
                 AnimeRankingType::Special => {
-                    app.top_three_anime.special = Some([
-                        result.data[0].node.clone(),
-                        result.data[1].node.clone(),
-                        result.data[2].node.clone(),
-                    ]);
+                    app.top_three_anime.special = Some(top);
                 }
                 AnimeRankingType::Other(_s) => {}
             },
@@ -380,81 +907,51 @@ impl<'a> Network<'a> {
         );
     }
 
-    async fn get_manga_top_three(&mut self, rank_type: MangaRankingType) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_manga_top_three(&self, rank_type: MangaRankingType) {
         let query = GetMangaRankingQuery {
             ranking_type: rank_type.clone(),
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::top_three().into()),
             limit: 3,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
             offset: 0,
         };
 
-        match api::get_manga_ranking(&query, &self.oauth).await {
-            Ok(results) => match &rank_type {
+        let result = self
+            .api
+            .get_manga_ranking(&query)
+            .await
+            .and_then(|ranking| top_three(ranking.data, |pair| pair.node));
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
+            Ok(top) => match &rank_type {
                 MangaRankingType::All => {
-                    app.top_three_manga.all = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.all = Some(top);
                 }
                 MangaRankingType::Manga => {
-                    app.top_three_manga.manga = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.manga = Some(top);
                 }
                 MangaRankingType::Novels => {
-                    app.top_three_manga.novels = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.novels = Some(top);
                 }
                 MangaRankingType::OneShots => {
-                    app.top_three_manga.oneshots = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.oneshots = Some(top);
                 }
                 MangaRankingType::Favorite => {
-                    app.top_three_manga.favourite = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.favourite = Some(top);
                 }
                 MangaRankingType::Doujinshi => {
-                    app.top_three_manga.doujin = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.doujin = Some(top);
                 }
                 MangaRankingType::Manhwa => {
-                    app.top_three_manga.manhwa = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.manhwa = Some(top);
                 }
                 MangaRankingType::Manhua => {
-                    app.top_three_manga.manhua = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.manhua = Some(top);
                 }
                 MangaRankingType::ByPopularity => {
-                    app.top_three_manga.popular = Some([
-                        results.data[0].node.clone(),
-                        results.data[1].node.clone(),
-                        results.data[2].node.clone(),
-                    ]);
+                    app.top_three_manga.popular = Some(top);
                 }
                 MangaRankingType::Other(_) => {}
             },
@@ -478,14 +975,16 @@ impl<'a> Network<'a> {
         );
     }
 
-    async fn get_user_info(&mut self) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_user_info(&self) {
         let query = GetUserInformationQuery {
             fields: Some(ALL_USER_FIELDS.to_string()),
         };
         //? we can only use @me for the user in the current api version
-        match api::get_my_user_information("@me".to_string(), &query, &self.oauth).await {
+        let result = self.api.get_my_user_information("@me", &query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
                 app.user_profile = Some(result.clone());
             }
@@ -507,16 +1006,18 @@ impl<'a> Network<'a> {
         app.display_block_title = "Profile".to_string();
     }
 
-    async fn get_suggested(&mut self) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    async fn get_suggested(&self) {
         let query = GetSuggestedAnimeQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            fields: Some(FieldSet::card().into()),
             limit: self.large_search_limit,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
             offset: 0,
         };
-        match api::get_suggested_anime(&query, &self.oauth).await {
+        let result = self.api.get_suggested_anime(&query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
                 app.search_results.anime = Some(result.clone());
             }
@@ -539,17 +1040,23 @@ impl<'a> Network<'a> {
         app.display_block_title = "Suggested Anime".to_string();
     }
 
-    async fn get_seasonal(&mut self) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
-        let query = GetSeasonalAnimeQuery {
-            sort: Some(app.anime_season.anime_sort.clone()),
-            offset: 0,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-            limit: self.large_search_limit,
-            nsfw: app.app_config.nsfw,
+    async fn get_seasonal(&self) {
+        let (season, query) = {
+            let app = self.app.lock().await;
+            let query = GetSeasonalAnimeQuery {
+                sort: Some(app.anime_season.anime_sort.clone()),
+                offset: 0,
+                fields: Some(FieldSet::card().into()),
+                limit: self.large_search_limit,
+                nsfw: app.app_config.nsfw,
+            };
+            (app.anime_season.anime_season.clone(), query)
+        };
+        let result = self.api.get_seasonal_anime(&season, &query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
         };
-        match api::get_seasonal_anime(&app.anime_season.anime_season, &query, &self.oauth).await {
+        match result {
             Ok(result) => app.search_results.anime = Some(result),
             Err(e) => {
                 app.write_error(e);
@@ -558,10 +1065,7 @@ impl<'a> Network<'a> {
             }
         }
 
-        let title = format!(
-            "Seasonal Anime: {} {}",
-            app.anime_season.anime_season.season, app.anime_season.anime_season.year,
-        );
+        let title = format!("Seasonal Anime: {}", season);
 
         let route = Route {
             data: Some(Data::SearchResult(app.search_results.clone())),
@@ -575,104 +1079,98 @@ impl<'a> Network<'a> {
         app.display_block_title = title;
     }
 
-    async fn get_user_anime_list(&mut self, status: Option<UserWatchStatus>) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    /// Pages through the whole anime list, the tabs, sorting and filtering
+    /// are done on it locally
+    async fn get_user_anime_list(&self) {
         let query = api::GetUserAnimeListQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-            status: status.clone(),
-            sort: Some(SortStyle::ListScore),
-            limit: self.large_search_limit,
+            fields: Some(FieldSet::list().into()),
+            status: None,
+            sort: Some(SortStyle::ListUpdatedAt),
+            limit: USER_LIST_PAGE_LIMIT,
             offset: 0,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
         };
-        match api::get_user_anime_list("@me".to_string(), &query, &self.oauth).await {
-            Ok(result) => {
-                app.search_results.anime = Some(result.clone());
+        let result = pagination::items(query, None, |q| async move {
+            self.api.get_user_anime_list("@me", &q).await
+        })
+        .map_ok(|n| n.node)
+        .try_collect::<Vec<Anime>>()
+        .await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
+            Ok(list) => {
+                debug!("synced {} anime of the user list", list.len());
+                app.anime_list = Some(list);
+                app.show_anime_list();
             }
             Err(e) => {
                 app.write_error(e);
                 app.active_display_block = ActiveDisplayBlock::Error;
-                return;
             }
         }
-
-        let data = UserAnimeList {
-            anime_list: app.search_results.anime.as_ref().unwrap().clone(),
-            status: status.clone(),
-        };
-        let route = Route {
-            block: ActiveDisplayBlock::UserAnimeList,
-            data: Some(Data::UserAnimeList(data)),
-            title: format!("My Anime List: {}", get_status_string(status)),
-            image: None,
-        };
-        app.active_block = ActiveBlock::DisplayBlock;
-        app.active_display_block = ActiveDisplayBlock::UserAnimeList;
-        app.display_block_title = route.title.clone();
-        app.push_navigation_stack(route);
     }
 
-    async fn get_user_manga_list(&mut self, status: Option<UserReadStatus>) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
+    /// See [`get_user_anime_list`](Self::get_user_anime_list)
+    async fn get_user_manga_list(&self) {
         let query = api::GetUserMangaListQuery {
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
-            status: status.clone(),
-            sort: Some(SortStyle::ListScore),
-            limit: self.large_search_limit,
+            fields: Some(FieldSet::list().into()),
+            status: None,
+            sort: Some(MangaSortStyle::ListUpdatedAt),
+            limit: USER_LIST_PAGE_LIMIT,
             offset: 0,
-            nsfw: app.app_config.nsfw,
+            nsfw: self.settings.nsfw,
         };
-        match api::get_user_manga_list("@me".to_string(), &query, &self.oauth).await {
-            Ok(result) => {
-                app.search_results.manga = Some(result.clone());
+        let result = pagination::items(query, None, |q| async move {
+            self.api.get_user_manga_list("@me", &q).await
+        })
+        .map_ok(|n| n.node)
+        .try_collect::<Vec<Manga>>()
+        .await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
+            Ok(list) => {
+                debug!("synced {} manga of the user list", list.len());
+                app.manga_list = Some(list);
+                app.show_manga_list();
             }
             Err(e) => {
                 app.write_error(e);
                 app.active_display_block = ActiveDisplayBlock::Error;
-                return;
             }
         }
-
-        let data = UserMangaList {
-            manga_list: app.search_results.manga.as_ref().unwrap().clone(),
-            status: status.clone(),
-        };
-        let route = Route {
-            block: ActiveDisplayBlock::UserMangaList,
-            data: Some(Data::UserMangaList(data)),
-            title: format!("My Manga List: {}", get_manga_status_string(status)),
-            image: None,
-        };
-
-        app.active_block = ActiveBlock::DisplayBlock;
-        app.active_display_block = ActiveDisplayBlock::UserMangaList;
-        app.display_block_title = route.title.clone();
-        app.push_navigation_stack(route);
     }
 
-    async fn get_search_results(&mut self, q: String) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
-
+    async fn get_search_results(&self, q: String) {
+        let nsfw = self.settings.nsfw;
         let anime_query = api::GetAnimeListQuery {
             q: q.clone(),
             limit: self.large_search_limit,
             offset: 0,
-            nsfw: app.app_config.nsfw,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            nsfw,
+            fields: Some(FieldSet::card().into()),
         };
 
         let manga_query = api::GetMangaListQuery {
             q: q.clone(),
             limit: self.large_search_limit,
             offset: 0,
-            nsfw: app.app_config.nsfw,
-            fields: Some(ALL_ANIME_AND_MANGA_FIELDS.to_string()),
+            nsfw,
+            fields: Some(FieldSet::card().into()),
         };
 
-        match api::get_anime_list(&anime_query, &self.oauth).await {
+        // both searches run at once
+        let (anime, manga) = tokio::join!(
+            self.api.get_anime_list(&anime_query),
+            self.api.get_manga_list(&manga_query)
+        );
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match anime {
             Ok(results) => {
                 app.search_results.anime = Some(results);
             }
@@ -683,7 +1181,7 @@ impl<'a> Network<'a> {
             }
         };
 
-        match api::get_manga_list(&manga_query, &self.oauth).await {
+        match manga {
             Ok(results) => {
                 app.search_results.manga = Some(results);
             }
@@ -707,17 +1205,18 @@ impl<'a> Network<'a> {
         app.display_block_title = format!("Search Results: {}", q).to_string()
     }
 
-    async fn update_anime_list_status(
-        &mut self,
-        anime_id: u64,
-        query: UpdateUserAnimeListStatusQuery,
-    ) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
-
-        match api::update_anime_list_status(anime_id, &query, &self.oauth).await {
+    async fn update_anime_list_status(&self, anime_id: u64, query: UpdateUserAnimeListStatusQuery) {
+        let result = self.api.update_anime_list_status(anime_id, &query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
-                app.anime_details.as_mut().unwrap().my_list_status = Some(result);
+                // the details may have been left while the update was sent
+                if let Some(anime) = app.anime_details.as_mut().filter(|a| a.id == anime_id) {
+                    anime.my_list_status = Some(result.clone());
+                }
+                app.update_synced_anime(anime_id, result);
                 app.popup_post_req_success_message = Some("updated Successfully".to_string());
                 app.popup_post_req_success = true;
             }
@@ -729,13 +1228,17 @@ impl<'a> Network<'a> {
         app.popup_is_loading = false;
     }
 
-    async fn update_manga_list_status(&mut self, manga_id: u64, query: UpdateUserMangaStatus) {
-        self.oauth.refresh().unwrap();
-        let mut app = self.app.lock().await;
-        match api::update_manga_list_status(manga_id, &query, &self.oauth).await {
+    async fn update_manga_list_status(&self, manga_id: u64, query: UpdateUserMangaStatus) {
+        let result = self.api.update_manga_list_status(manga_id, &query).await;
+        let Some(mut app) = self.lock_for_result().await else {
+            return;
+        };
+        match result {
             Ok(result) => {
-                //
-                app.manga_details.as_mut().unwrap().my_list_status = Some(result);
+                if let Some(manga) = app.manga_details.as_mut().filter(|m| m.id == manga_id) {
+                    manga.my_list_status = Some(result.clone());
+                }
+                app.update_synced_manga(manga_id, result);
                 app.popup_post_req_success = true;
                 app.popup_post_req_success_message = Some("updated Successfully".to_string());
             }
@@ -748,156 +1251,565 @@ impl<'a> Network<'a> {
     }
 }
 
-fn get_status_string(status: Option<UserWatchStatus>) -> String {
-    match status {
-        Some(s) => match s {
-            UserWatchStatus::Completed => "completed".to_string(),
-            UserWatchStatus::Watching => "watching".to_string(),
-            UserWatchStatus::OnHold => "on_hold".to_string(),
-            UserWatchStatus::Dropped => "dropped".to_string(),
-            UserWatchStatus::PlanToWatch => "plan_to_watch".to_string(),
-            UserWatchStatus::Other(_) => "All".to_string(),
-        },
-        None => "All".to_string(),
+/// The first three titles of a ranking, MAL may rank fewer for small categories
+fn top_three<P, T>(ranking: Vec<P>, node: impl Fn(P) -> T) -> Result<[T; 3], api::Error> {
+    let count = ranking.len();
+    let top: Vec<T> = ranking.into_iter().take(3).map(node).collect();
+    top.try_into()
+        .map_err(|_| api::Error::Decode(format!("only {} ranked titles, expected 3", count)))
+}
+
+/// The cached picture with its size, and its protocol state for drawing
+type Poster = ((String, u32, u32), StatefulProtocol);
+
+/// The cached cover, or the first one that downloads; the large one first
+async fn get_picture(
+    api: &impl MalApi,
+    images: &ImageCache,
+    kind: ImageKind,
+    id: u64,
+    pictures: &Option<Picture>,
+) -> Option<PathBuf> {
+    let pictures = pictures.as_ref()?;
+    let urls = [
+        (ImageSize::Large, &pictures.large),
+        (ImageSize::Medium, &pictures.medium),
+    ];
+    for (size, url) in urls {
+        let Some(url) = url else {
+            continue;
+        };
+        let key = ImageKey::new(kind, id, size);
+        if let Some(path) = cached_picture(api, images, &key, url).await {
+            return Some(path);
+        }
     }
+    None
 }
 
-fn get_manga_status_string(status: Option<UserReadStatus>) -> String {
-    match status {
-        Some(s) => match s {
-            UserReadStatus::Completed => "completed".to_string(),
-            UserReadStatus::Reading => "reading".to_string(),
-            UserReadStatus::OnHold => "on_hold".to_string(),
-            UserReadStatus::Dropped => "dropped".to_string(),
-            UserReadStatus::PlanToRead => "plan_to_read".to_string(),
-            UserReadStatus::Other(_) => "All".to_string(),
+/// The image of `key`, downloaded from `url` when it is not cached yet
+async fn cached_picture(
+    api: &impl MalApi,
+    images: &ImageCache,
+    key: &ImageKey,
+    url: &str,
+) -> Option<PathBuf> {
+    if let Some(path) = images.get(key) {
+        return Some(path);
+    }
+    match api.get_bytes(url).await {
+        Ok(bytes) => match images.put(key, &bytes) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("could not cache the image {}: {}", url, e);
+                None
+            }
         },
-        None => "All".to_string(),
+        Err(e) => {
+            warn!("Error fetching image: {}", e);
+            None
+        }
     }
 }
 
-async fn fetch_image(url: &str) -> Result<Bytes, Box<dyn std::error::Error>> {
-    let response = reqwest::get(url).await?;
-    let bytes = response.bytes().await?;
-    Ok(bytes)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeMalApi, Mutation};
+    use crate::app::test::get_app;
+    use crate::auth::AuthError;
+    use crate::user_list::ListSort;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    fn setup() -> (FakeMalApi, Arc<Mutex<App>>) {
+        let mut app = get_app();
+        app.picker = None;
+        (FakeMalApi::new(), Arc::new(Mutex::new(app)))
+    }
 
-async fn get_picture(
-    image_dir_path: PathBuf,
-    id: u64,
-    pictures: &Option<Picture>,
-    max_limit: u16,
-) -> Option<(String, u32, u32)> {
-    // check if the image is already in the cache, if not we fetch it and save it
-    // look for it in the cache first:
-    let file_path = image_dir_path.join(format!("{}.png", id));
-    if file_path.exists() {
-        let image = image::open(&file_path).ok()?;
-        return Some((
-            file_path.to_string_lossy().to_string(),
-            image.width(),
-            image.height(),
-        ));
+    #[tokio::test]
+    async fn test_anime_details_pushes_route() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetAnime(5114)).await;
+
+        let app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::AnimeDetails);
+        assert_eq!(app.display_block_title, "Fullmetal Alchemist: Brotherhood");
+        assert_eq!(app.anime_details.as_ref().unwrap().id, 5114);
+        let route = &app.navigator.data[&app.navigator.history[app.navigator.index]];
+        assert_eq!(route.block, ActiveDisplayBlock::AnimeDetails);
+        assert!(!app.is_loading);
     }
 
-    // fetch the image and save it
-    // after fetch we need to look into the total count of files and if it exceeds the limit we delete the last one used
-    //? make the image number limit a conf var
-    //? we ll implement a FIFO mechanisme
-    // so the flow is:
-    // when an image is fetchead we enter its name  in the array (just use array for poping and pushing)
-
-    // checking the json file
-    let cache_index_file = image_dir_path.join("cache_index.json");
-    if !cache_index_file.exists() {
-        // create an empty  file with emtpy array
-        let cache_index = Vec::<String>::new();
-        // Read all existing images in the folder and create an array
-        let mut existing_images = Vec::<String>::new();
-        if let Ok(entries) = std::fs::read_dir(&image_dir_path) {
-            for entry in entries.flatten() {
-                if let Some(file_name) = entry.file_name().to_str() {
-                    if file_name != "cache_index.json" {
-                        existing_images.push(file_name.to_string());
-                    }
-                }
-            }
+    #[tokio::test]
+    async fn test_slow_request_leaves_app_unlocked() {
+        let (fake, app) = setup();
+        fake.delay(Some(Duration::from_millis(200)));
+        let mut network = Network::new(fake, &app, 10);
+        let handle = network
+            .spawn_network_event(IoEvent::GetAnime(5114))
+            .await
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // the ui can draw and take keys while the details load
+        assert!(app.try_lock().unwrap().is_loading);
+        handle.await.unwrap();
+        let app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::AnimeDetails);
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_events_run_concurrently() {
+        let (fake, app) = setup();
+        let delay = Duration::from_millis(300);
+        fake.delay(Some(delay));
+        let mut network = Network::new(fake, &app, 10);
+        let start = std::time::Instant::now();
+        let details = network.spawn_network_event(IoEvent::GetAnime(5114)).await;
+        let top_three = network
+            .spawn_network_event(IoEvent::GetTopThree(TopThreeBlock::Anime(
+                AnimeRankingType::All,
+            )))
+            .await;
+        details.unwrap().await.unwrap();
+        top_three.unwrap().await.unwrap();
+
+        assert!(start.elapsed() < delay * 2);
+        let app = app.lock().await;
+        assert!(app.top_three_anime.all.is_some());
+        assert_eq!(app.anime_details.as_ref().unwrap().id, 5114);
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_is_discarded() {
+        let (fake, app) = setup();
+        fake.delay(Some(Duration::from_millis(200)));
+        let mut network = Network::new(fake, &app, 10);
+        let request = Request::from(IoEvent::GetAnime(5114));
+        let cancel = request.cancel.clone();
+        let handle = network.spawn_network_event(request).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let start = std::time::Instant::now();
+        cancel.cancel();
+        handle.await.unwrap();
+        // dropped right away instead of waiting for the response
+        assert!(start.elapsed() < Duration::from_millis(100));
+        let app = app.lock().await;
+        assert!(app.anime_details.is_none());
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Empty);
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_thumbnails_load_quietly() {
+        let (_, app) = setup();
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(4, 6)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let fake = FakeMalApi::new().with_image("https://cdn/1.png", png.into_inner());
+        // posters need no session
+        fake.fail_auth_with(Some(AuthError::NetworkTimeout));
+        let dir = std::env::temp_dir().join(format!("mal-thumbnails-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let images = ImageCache::open(dir, 10, u64::MAX).unwrap();
+        {
+            let mut app = app.lock().await;
+            app.picker = Some(ratatui_image::picker::Picker::from_fontsize((8, 16)));
+            app.thumbnails
+                .insert((ImageKind::Anime, 1), Thumbnail::Loading);
+            app.thumbnails
+                .insert((ImageKind::Anime, 2), Thumbnail::Loading);
         }
-        let cache_index_json =
-            serde_json::to_string(&cache_index).unwrap_or_else(|_| "[]".to_string());
-        std::fs::write(&cache_index_file, cache_index_json).ok();
-    }
-
-    if let Some(p) = pictures {
-        let urls = vec![&p.large, &p.medium];
-        // loop all image urls and return the first fetched one
-        for url in urls.into_iter().flatten() {
-            // save the image in the .cache/mal-tui/media-images folder
-            let image = fetch_image(url).await;
-            match image {
-                Ok(bytes) => {
-                    let file_name = format!("{}.png", id);
-                    let file_path = image_dir_path.join(file_name.clone());
-                    let image = image::load_from_memory(&bytes).ok();
-                    if let Some(image) = image {
-                        image
-                            .save_with_format(&file_path, image::ImageFormat::Png)
-                            .ok();
-                        // after saving the image we need to update the index file
-                        // first push to array then check if it reached the max size if yes then we remove the first element(image then array element)
-                        let res = update_image_cache(
-                            &cache_index_file,
-                            &image_dir_path,
-                            &file_name,
-                            max_limit,
-                        )
-                        .await;
-                        if let Err(e) = res {
-                            warn!("error updating the cache index file: {}", e)
-                        }
-
-                        return Some((
-                            file_path.to_string_lossy().to_string(),
-                            image.width(),
-                            image.height(),
-                        ));
-                    }
-                }
-                Err(e) => {
-                    warn!("Error fetching image: {}", e);
-                }
-            }
+        let mut network = Network::new(fake, &app, 10).with_image_cache(images.clone());
+        network
+            .handle_network_event(IoEvent::GetThumbnails(vec![
+                (ImageKind::Anime, 1, "https://cdn/1.png".to_string()),
+                (ImageKind::Anime, 2, "https://cdn/2.png".to_string()),
+                // its page was left meanwhile
+                (ImageKind::Anime, 3, "https://cdn/1.png".to_string()),
+            ]))
+            .await;
+
+        let app = app.lock().await;
+        assert!(matches!(
+            app.thumbnails[&(ImageKind::Anime, 1)],
+            Thumbnail::Ready(_)
+        ));
+        // asked for again when its page is drawn again
+        assert!(matches!(
+            app.thumbnails[&(ImageKind::Anime, 2)],
+            Thumbnail::Failed
+        ));
+        assert!(!app.thumbnails.contains_key(&(ImageKind::Anime, 3)));
+        assert!(images
+            .get(&ImageKey::new(ImageKind::Anime, 1, ImageSize::Medium))
+            .is_some());
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Empty);
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_shows_error() {
+        let (fake, app) = setup();
+        fake.fail_with(Some(StatusCode::SERVICE_UNAVAILABLE));
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetUserInfo).await;
+
+        let app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Error);
+        assert!(app.api_error.contains("503"));
+        assert!(app.user_profile.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_shows_error() {
+        let (fake, app) = setup();
+        fake.fail_auth_with(Some(AuthError::NetworkTimeout));
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetUserInfo).await;
+
+        let app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Error);
+        assert!(app.api_error.contains("Network Timeout"));
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_login_resumes_after_expired_session() {
+        let (fake, app) = setup();
+        fake.fail_auth_with(Some(AuthError::Rejected("invalid_grant".to_string())));
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetUserInfo).await;
+        {
+            let app = app.lock().await;
+            assert_eq!(app.active_display_block, ActiveDisplayBlock::Login);
+            assert_eq!(app.login, Some(LoginState::Expired));
         }
+
+        network.handle_network_event(IoEvent::Login).await;
+        let app = app.lock().await;
+        assert_eq!(app.login, None);
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::UserInfo);
+        assert!(app.user_profile.is_some());
+        assert!(!app.is_loading);
     }
-    None
-}
 
-async fn update_image_cache(
-    cache_index_file: &PathBuf,
-    image_dir_path: &Path,
-    file_name: &str,
-    max_limit: u16,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // read the current cache index
-    let cache_content = std::fs::read_to_string(cache_index_file)?;
-    let mut cache_index: Vec<String> = serde_json::from_str(&cache_content).unwrap_or_default();
+    #[tokio::test]
+    async fn test_switch_profile_clears_user_routes() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetAnimeList).await;
+        network.handle_network_event(IoEvent::GetAnime(5114)).await;
+        network
+            .handle_network_event(IoEvent::GetSuggestedAnime)
+            .await;
+
+        network
+            .handle_network_event(IoEvent::SwitchProfile(Some("work".to_string())))
+            .await;
+        let app = app.lock().await;
+        assert_eq!(app.active_profile.as_deref(), Some("work"));
+        assert!(app.anime_list_status.is_none());
+        assert!(app.anime_list.is_none());
+        let blocks: Vec<_> = app.navigator.history[1..]
+            .iter()
+            .map(|id| app.navigator.data[id].block)
+            .collect();
+        // only the user info of the new account is left
+        assert_eq!(blocks.last(), Some(&ActiveDisplayBlock::UserInfo));
+        assert!(!blocks.contains(&ActiveDisplayBlock::UserAnimeList));
+        assert!(!blocks.contains(&ActiveDisplayBlock::AnimeDetails));
+        assert!(!blocks.contains(&ActiveDisplayBlock::Suggestions));
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_user_anime_list_sync() {
+        let (fake, app) = setup();
+        app.lock().await.anime_list_status = Some(UserWatchStatus::Watching);
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetAnimeList).await;
+
+        let mut app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::UserAnimeList);
+        // everything on the list is kept, not just the tab on screen
+        assert_eq!(app.anime_list.as_ref().unwrap().len(), 3);
+        let ids = |app: &App| {
+            let list = &app.search_results.anime.as_ref().unwrap().data;
+            list.iter().map(|n| n.node.id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&app), vec![52991]);
+
+        // other tabs, sorts and filters come from the synced list
+        app.anime_list_status = None;
+        app.show_anime_list();
+        assert_eq!(ids(&app), vec![5114, 21, 52991]);
+        app.anime_list_sort = ListSort::Title;
+        app.anime_list_filter = "one".parse().unwrap();
+        app.show_anime_list();
+        assert_eq!(ids(&app), vec![21]);
+        assert_eq!(app.display_block_title, "My Anime List: All (one)");
+        let list_routes = app
+            .navigator
+            .history
+            .iter()
+            .filter(|id| app.navigator.data[id].block == ActiveDisplayBlock::UserAnimeList)
+            .count();
+        assert_eq!(list_routes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_synced_list() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetAnimeList).await;
+        network.handle_network_event(IoEvent::GetAnime(9253)).await;
+        let query = UpdateUserAnimeListStatusQuery {
+            status: Some(UserWatchStatus::Watching),
+            is_rewatching: None,
+            score: Some(9),
+            num_watched_episodes: None,
+            priority: None,
+            num_times_rewatched: None,
+            rewatch_value: None,
+            tags: None,
+            comments: None,
+            start_date: None,
+            finish_date: None,
+        };
+        network
+            .handle_network_event(IoEvent::UpdateAnimeListStatus(9253, query))
+            .await;
+
+        let mut app = app.lock().await;
+        app.load_previous_route();
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::UserAnimeList);
+        // added to the list without syncing it again
+        let list = &app.search_results.anime.as_ref().unwrap().data;
+        assert_eq!(list.len(), 4);
+        assert_eq!(list[1].node.id, 9253);
+    }
 
-    // add the new image name to the array
-    cache_index.push(file_name.to_string());
+    #[tokio::test]
+    async fn test_update_anime_list_status() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake.clone(), &app, 10);
+        network.handle_network_event(IoEvent::GetAnime(9253)).await;
+        let query = UpdateUserAnimeListStatusQuery {
+            status: Some(UserWatchStatus::Watching),
+            is_rewatching: None,
+            score: None,
+            num_watched_episodes: Some(1),
+            priority: None,
+            num_times_rewatched: None,
+            rewatch_value: None,
+            tags: None,
+            comments: None,
+            start_date: None,
+            finish_date: None,
+        };
+        network
+            .handle_network_event(IoEvent::UpdateAnimeListStatus(9253, query))
+            .await;
 
-    // Check if we've reached the capacity limit
-    if cache_index.len() > max_limit as usize {
-        // Remove the first element and delete the corresponding image
-        let oldest_image = cache_index.remove(0);
-        let oldest_image_path = image_dir_path.join(&oldest_image);
-        if oldest_image_path.exists() {
-            std::fs::remove_file(oldest_image_path).ok();
+        let app = app.lock().await;
+        assert!(app.popup_post_req_success);
+        assert!(!app.popup_is_loading);
+        let status = app.anime_details.as_ref().unwrap().my_list_status.as_ref();
+        assert_eq!(status.unwrap().num_episodes_watched, 1);
+        assert!(matches!(
+            fake.mutations().as_slice(),
+            [Mutation::UpdateAnime(9253, _)]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_next_page_appends_ranking() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 3);
+        network
+            .handle_network_event(IoEvent::GetAnimeRanking(AnimeRankingType::All))
+            .await;
+        assert!(app
+            .lock()
+            .await
+            .anime_ranking_data
+            .as_ref()
+            .unwrap()
+            .has_next_page());
+
+        network.handle_network_event(IoEvent::GetNextPage).await;
+        let app = app.lock().await;
+        let ranking = app.anime_ranking_data.as_ref().unwrap();
+        let ranks: Vec<_> = ranking.data.iter().map(|r| r.ranking.rank).collect();
+        assert_eq!(ranks, vec![1, 2, 3, 4]);
+        assert!(!ranking.has_next_page());
+        // going back to the ranking shows the appended page too
+        let route = &app.navigator.data[&app.navigator.history[app.navigator.index]];
+        match &route.data {
+            Some(Data::AnimeRanking(ranking)) => assert_eq!(ranking.data.len(), 4),
+            data => panic!("unexpected route data: {:?}", data),
         }
+        assert!(!app.is_loading);
     }
 
-    // Write the updated cache index back to the file
-    let updated_cache_json = serde_json::to_string(&cache_index)?;
-    std::fs::write(cache_index_file, updated_cache_json)?;
+    #[tokio::test]
+    async fn test_next_page_appends_search_results() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 1);
+        network
+            .handle_network_event(IoEvent::GetSearchResults("e".to_string()))
+            .await;
+        app.lock().await.search_results.selected_tab = SelectedSearchTab::Manga;
+
+        network.handle_network_event(IoEvent::GetNextPage).await;
+        // the last page has no next one to load
+        network.handle_network_event(IoEvent::GetNextPage).await;
+        let app = app.lock().await;
+        assert_eq!(
+            app.active_display_block,
+            ActiveDisplayBlock::SearchResultBlock
+        );
+        let manga = app.search_results.manga.as_ref().unwrap();
+        let ids: Vec<_> = manga.data.iter().map(|n| n.node.id).collect();
+        assert_eq!(ids, vec![2, 13]);
+        assert!(!manga.has_next_page());
+        // only the tab on screen got a page
+        assert_eq!(app.search_results.anime.as_ref().unwrap().data.len(), 1);
+    }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_anime_discussion_opens_thread() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 1);
+        network.handle_network_event(IoEvent::GetAnime(52991)).await;
+        network
+            .handle_network_event(IoEvent::GetAnimeDiscussion(52991))
+            .await;
+        {
+            let app = app.lock().await;
+            assert_eq!(app.active_display_block, ActiveDisplayBlock::ForumTopics);
+            assert_eq!(app.display_block_title, "Discussion: Sousou no Frieren");
+            assert_eq!(app.forum_topics.as_ref().unwrap().topics.data.len(), 1);
+        }
+
+        network.handle_network_event(IoEvent::GetNextPage).await;
+        let topic_id = {
+            let app = app.lock().await;
+            let topics = &app.forum_topics.as_ref().unwrap().topics;
+            let titles: Vec<_> = topics.data.iter().map(|t| t.title.as_str()).collect();
+            assert_eq!(
+                titles,
+                vec![
+                    "Sousou no Frieren Episode 1 Discussion",
+                    "Sousou no Frieren Episode 2 Discussion"
+                ]
+            );
+            topics.data[0].id
+        };
+
+        network
+            .handle_network_event(IoEvent::GetForumTopic(topic_id, 0))
+            .await;
+        let routes = {
+            let app = app.lock().await;
+            assert_eq!(app.active_display_block, ActiveDisplayBlock::ForumTopic);
+            assert_eq!(
+                app.display_block_title,
+                "Sousou no Frieren Episode 1 Discussion"
+            );
+            let thread = app.forum_thread.as_ref().unwrap();
+            assert_eq!(thread.page.data.posts.len(), 3);
+            assert_eq!(thread.page.data.posts[2].created_by.name, "Fern");
+            app.navigator.history.len()
+        };
+
+        // paging through the posts stays on the same route
+        network
+            .handle_network_event(IoEvent::GetForumTopic(topic_id, 0))
+            .await;
+        let app = app.lock().await;
+        assert_eq!(app.navigator.history.len(), routes);
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_manga_discussion_lists_chapters() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        network.handle_network_event(IoEvent::GetManga(2)).await;
+        network
+            .handle_network_event(IoEvent::GetMangaDiscussion(2))
+            .await;
+
+        let app = app.lock().await;
+        let topics = &app.forum_topics.as_ref().unwrap().topics;
+        assert_eq!(topics.data.len(), 1);
+        assert_eq!(topics.data[0].title, "Berserk Chapter 364 Discussion");
+        assert!(!topics.has_next_page());
+    }
+
+    #[tokio::test]
+    async fn test_missing_topic_shows_error() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        network
+            .handle_network_event(IoEvent::GetForumTopic(1, 0))
+            .await;
+
+        let app = app.lock().await;
+        assert_eq!(app.active_display_block, ActiveDisplayBlock::Error);
+        assert!(app.forum_thread.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_short_ranking_shows_error() {
+        let (fake, app) = setup();
+        let mut network = Network::new(fake, &app, 10);
+        // the fixtures only rank two manga
+        network
+            .handle_network_event(IoEvent::GetTopThree(TopThreeBlock::Manga(
+                MangaRankingType::All,
+            )))
+            .await;
+
+        let app = app.lock().await;
+        assert!(matches!(app.active_top_three, TopThreeBlock::Error(_)));
+        assert!(app.top_three_manga.all.is_none());
+        assert!(!app.is_loading);
+    }
+
+    #[tokio::test]
+    async fn test_panicked_fetch_is_finished() {
+        let (fake, app) = setup();
+        let network = Network::new(fake, &app, 10);
+        let request = Request::from(IoEvent::GetAnime(5114));
+        {
+            let mut app = app.lock().await;
+            app.requests.push(request.clone());
+            app.is_loading = true;
+        }
+        network.in_flight.fetch_add(1, Ordering::SeqCst);
+
+        let handle = tokio::spawn(async move {
+            let _guard = FinishGuard {
+                network: Some(network),
+                request,
+            };
+            panic!("fetch panicked");
+        });
+        assert!(handle.await.unwrap_err().is_panic());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let app = app.lock().await;
+        assert!(app.requests.is_empty());
+        assert!(!app.is_loading);
+    }
 }
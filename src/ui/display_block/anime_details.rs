@@ -57,6 +57,10 @@ pub fn draw_anime_detail(f: &mut Frame, app: &mut App, chunk: Rect) {
             ActiveAnimeDetailBlock::Rate => {
                 details_utils::draw_rate_popup(f, app, chunk);
             }
+
+            ActiveAnimeDetailBlock::Dates => {
+                details_utils::draw_dates_popup(f, app, chunk);
+            }
             _ => {}
         }
     }
@@ -213,6 +217,8 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
     if app.active_anime_detail_block == ActiveAnimeDetailBlock::AddToList
         || app.active_anime_detail_block == ActiveAnimeDetailBlock::Rate
         || app.active_anime_detail_block == ActiveAnimeDetailBlock::Episodes
+        || app.active_anime_detail_block == ActiveAnimeDetailBlock::Dates
+        || app.active_anime_detail_block == ActiveAnimeDetailBlock::Discussion
     {
         details_utils::draw_bordered_block(f, chunk, app.app_config.theme.hovered)
     } else {
@@ -239,17 +245,20 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
         .flex(Flex::Center)
         .areas(rest_chunk);
 
-    let [_, user_status_chunk, user_score_chunk, user_progress_chunk, _] = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Length(4),
-            Constraint::Min(23),
-            Constraint::Min(23),
-            Constraint::Min(23),
-            Constraint::Percentage(100),
-        ])
-        .flex(Flex::Center)
-        .areas(lower_chunk);
+    let [_, user_status_chunk, user_score_chunk, user_progress_chunk, dates_chunk, discussion_chunk, _] =
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Min(23),
+                Constraint::Min(23),
+                Constraint::Min(23),
+                Constraint::Min(16),
+                Constraint::Min(16),
+                Constraint::Percentage(100),
+            ])
+            .flex(Flex::Center)
+            .areas(lower_chunk);
     // draw_bordered_block(f, score_chunk);
     let [score_title_chunk, big_score_chunk, num_users_chunk] = Layout::default()
         .direction(Direction::Vertical)
@@ -442,6 +451,14 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
 
     let user_progress = format!("Episodes: {}/{}", watched_ep, total_ep);
     let mut user_progress_paragraph = Paragraph::new(user_progress)
+        .alignment(Alignment::Center)
+        .block(unhovered_block.clone());
+
+    let mut dates_paragraph = Paragraph::new("Dates")
+        .alignment(Alignment::Center)
+        .block(unhovered_block.clone());
+
+    let mut discussion_paragraph = Paragraph::new("Discussion")
         .alignment(Alignment::Center)
         .block(unhovered_block);
 
@@ -459,9 +476,17 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
         ActiveAnimeDetailBlock::Rate => {
             user_score_paragraph = user_score_paragraph.block(hovered_block);
         }
+        ActiveAnimeDetailBlock::Dates => {
+            dates_paragraph = dates_paragraph.block(hovered_block);
+        }
+        ActiveAnimeDetailBlock::Discussion => {
+            discussion_paragraph = discussion_paragraph.block(hovered_block);
+        }
         _ => {}
     }
 
+    f.render_widget(dates_paragraph, dates_chunk);
+    f.render_widget(discussion_paragraph, discussion_chunk);
     f.render_widget(user_score_paragraph, user_score_chunk);
     f.render_widget(user_progress_paragraph, user_progress_chunk);
     f.render_widget(user_status_paragraph, user_status_chunk);
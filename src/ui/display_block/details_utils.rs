@@ -10,7 +10,8 @@ use tui_scrollview::{ScrollView, ScrollbarVisibility};
 
 use crate::{
     api::model::{
-        AlternativeTitles, AnimeMediaType, AnimeStatus, MangaMediaType, MangaStatus, Source,
+        AlternativeTitles, AnimeMediaType, AnimeSeason, AnimeStatus, DateWrapper, MangaMediaType,
+        MangaStatus, Source,
     },
     app::{
         ActiveDisplayBlock, ActiveMangaDetailBlock, App, RATING_OPTIONS, USER_READ_STATUS,
@@ -43,13 +44,10 @@ pub fn draw_picture(f: &mut Frame, app: &mut App, chunk: Rect) {
     //     .constraints([Constraint::Length(1), Constraint::Fill(1)])
     //     .split(chunk)[1];
 
-    if app.media_image.is_some() {
+    if let Some((_, image_width, image_height)) = app.media_image {
         if let (Some(_), Some(_)) = (&app.picker, &app.image_state) {
             let image = StatefulImage::default();
 
-            let image_width = app.media_image.as_ref().unwrap().1;
-            let image_height = app.media_image.as_ref().unwrap().2;
-
             let perc_x = ((chunk.width as f32 / image_width as f32) * 100.0) as u16;
             let perc_y = ((chunk.height as f32 / image_height as f32) * 100.0) as u16;
             f.render_stateful_widget(
@@ -160,7 +158,7 @@ pub fn draw_synopsis_items(
     f.render_stateful_widget(scroll_view, chunk, &mut state);
 }
 
-fn construct_paragraph_lines(
+pub fn construct_paragraph_lines(
     text: &str,
     width: u16,
     word_width: u16,
@@ -204,21 +202,10 @@ pub fn construct_synopsis_layout<T: AsRef<str>>(
 
     for (title, text) in items {
         let (title, text, height) = match &text {
-            Some(content) => {
-                if !content.as_ref().is_empty() {
-                    let (title, text, height) =
-                        get_text_prop(title.to_string(), content.as_ref().to_string(), app);
-                    (title, text, height)
-                } else {
-                    let mut title = title.to_string();
-                    title.remove(title.len() - 1);
-                    let (title, text, height) = get_text_prop(
-                        title.to_string(),
-                        format!("No {} available.", title).to_ascii_lowercase(),
-                        app,
-                    );
-                    (title, text, height)
-                }
+            Some(content) if !content.as_ref().is_empty() => {
+                let (title, text, height) =
+                    get_text_prop(title.to_string(), content.as_ref().to_string(), app);
+                (title, text, height)
             }
             _ => {
                 let mut title = title.to_string();
@@ -382,9 +369,7 @@ pub fn get_anime_key_val_info(
         .unwrap()
         .start_season
         .as_ref()
-        .map_or("Unknown".to_string(), |s| {
-            format!("{} {}", s.season.clone(), s.year)
-        });
+        .map_or("Unknown".to_string(), |s| AnimeSeason::from(s).to_string());
     let premiered_title =
         Span::styled("Premiered: ", Style::default().add_modifier(Modifier::BOLD));
     let premiered_line = Line::from(vec![premiered_title, Span::raw(premiered)]);
@@ -818,3 +803,67 @@ pub fn draw_count_popup(f: &mut Frame, app: &App, chunk: Rect) {
 
     f.render_widget(episode_paragraph, center_area(chunk, 100, 30));
 }
+
+pub fn draw_dates_popup(f: &mut Frame, app: &App, chunk: Rect) {
+    let chunk = center_area(chunk, 30, 40);
+    let popup = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(app.app_config.theme.active);
+    f.render_widget(Clear, chunk);
+    f.render_widget(popup, chunk);
+
+    if app.result_popup {
+        let message_line = if app.popup_is_loading {
+            "Loading..."
+        } else if app.popup_post_req_success {
+            app.popup_post_req_success_message.as_ref().unwrap()
+        } else {
+            &app.api_error
+        };
+        loading::draw_centered_line(f, app, chunk, message_line);
+        return;
+    }
+
+    let picker = &app.date_picker;
+    let selected = Style::default()
+        .fg(app.app_config.theme.hovered)
+        .add_modifier(Modifier::BOLD);
+    let date_line = |title: &'static str, date: Option<&DateWrapper>, first_field: usize| {
+        let parts = date.map_or(
+            ["----".to_string(), "--".to_string(), "--".to_string()],
+            |d| {
+                [
+                    format!("{:04}", d.date.year()),
+                    format!("{:02}", d.date.month() as u8),
+                    format!("{:02}", d.date.day()),
+                ]
+            },
+        );
+        let mut spans = vec![Span::raw(title)];
+        for (i, part) in parts.into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw("-"));
+            }
+            if picker.selected_field == first_field + i {
+                spans.push(Span::styled(part, selected));
+            } else {
+                spans.push(Span::raw(part));
+            }
+        }
+        Line::from(spans).alignment(Alignment::Center)
+    };
+
+    let lines = vec![
+        date_line("Start:  ", picker.start_date.as_ref(), 0),
+        Line::raw(""),
+        date_line("Finish: ", picker.finish_date.as_ref(), 3),
+        Line::raw(""),
+        Line::styled(
+            "←/→ select  ↑/↓ change  ⌫ clear",
+            Style::default().fg(app.app_config.theme.inactive),
+        )
+        .alignment(Alignment::Center),
+    ];
+    f.render_widget(Paragraph::new(lines), center_area(chunk, 100, 50));
+}
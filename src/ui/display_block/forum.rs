@@ -0,0 +1,130 @@
+use ratatui::{
+    layout::{Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{api::model::ForumTopicDetail, app::App};
+
+use super::{details_utils::construct_paragraph_lines, draw_keys_bar, loading};
+
+pub fn draw_forum_topics(f: &mut Frame, app: &App, chunk: Rect) {
+    let chunk = draw_keys_bar(f, app, chunk);
+    let Some(forum) = &app.forum_topics else {
+        return;
+    };
+    if forum.topics.data.is_empty() {
+        loading::draw_centered_line(f, app, chunk, "No discussion threads found");
+        return;
+    }
+
+    let theme = &app.app_config.theme;
+    let items: Vec<ListItem> = forum
+        .topics
+        .data
+        .iter()
+        .map(|topic| {
+            let lock = if topic.is_locked { "🔒 " } else { "" };
+            let title = Line::styled(
+                format!("{}{}", lock, topic.title),
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            );
+            let info = Line::styled(
+                format!(
+                    "{} posts   |   by {}   |   last post {} by {}",
+                    topic.number_of_posts,
+                    topic.created_by.name,
+                    topic.last_post_created_at.datetime.date(),
+                    topic.last_post_created_by.name
+                ),
+                Style::default().fg(theme.inactive),
+            );
+            ListItem::new(vec![title, info, Line::raw("")])
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(Style::default().fg(theme.hovered))
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(forum.selected_index));
+    f.render_stateful_widget(list, chunk.inner(Margin::new(2, 1)), &mut state);
+}
+
+pub fn draw_forum_thread(f: &mut Frame, app: &mut App, chunk: Rect) {
+    let chunk = draw_keys_bar(f, app, chunk);
+    let chunk = chunk.inner(Margin::new(2, 1));
+    let Some(thread) = &app.forum_thread else {
+        return;
+    };
+    let lines = construct_thread_lines(app, &thread.page.data, chunk.width.saturating_sub(1));
+
+    // scrolling stops at the last line of the page
+    let max_scroll = lines.len().saturating_sub(chunk.height as usize) as u16;
+    app.forum_thread_scroll = app.forum_thread_scroll.min(max_scroll);
+
+    let paragraph = Paragraph::new(lines).scroll((app.forum_thread_scroll, 0));
+    f.render_widget(paragraph, chunk);
+}
+
+fn construct_thread_lines(app: &App, topic: &ForumTopicDetail, width: u16) -> Vec<Line<'static>> {
+    let theme = &app.app_config.theme;
+    let bold = Style::default().fg(theme.text).add_modifier(Modifier::BOLD);
+    let dimmed = Style::default().fg(theme.inactive);
+    let mut lines = Vec::new();
+
+    if let Some(poll) = &topic.poll {
+        lines.push(Line::styled(format!("Poll: {}", poll.question), bold));
+        for option in &poll.options {
+            lines.push(Line::raw(format!(
+                " - {} ({} votes)",
+                option.text, option.votes
+            )));
+        }
+        lines.push(Line::raw(""));
+    }
+
+    for post in &topic.posts {
+        lines.push(Line::from(vec![
+            Span::styled(format!("#{} ", post.number), dimmed),
+            Span::styled(post.created_by.name.clone(), bold),
+            Span::styled(format!("   {}", post.created_at.datetime.date()), dimmed),
+        ]));
+        lines.push(Line::raw(""));
+        for text in strip_bbcode(&post.body).lines() {
+            if text.trim().is_empty() {
+                lines.push(Line::raw(""));
+            } else {
+                lines.extend(construct_paragraph_lines(text, width, 1, 1).1);
+            }
+        }
+        lines.push(Line::styled("─".repeat(width as usize), dimmed));
+    }
+    lines
+}
+
+/// Drops BBCode tags like `[b]`, `[/quote]` or `[url=...]` from a post body
+fn strip_bbcode(body: &str) -> String {
+    let mut text = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find('[') {
+        text.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        match tail.find(']') {
+            Some(end) if is_bbcode_tag(&tail[1..end]) => rest = &tail[end + 1..],
+            _ => {
+                text.push('[');
+                rest = &tail[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn is_bbcode_tag(tag: &str) -> bool {
+    let tag = tag.strip_prefix('/').unwrap_or(tag);
+    let name = tag.split('=').next().unwrap_or_default();
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic() || c == '*')
+}
@@ -0,0 +1,53 @@
+use super::center_area;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::{App, LoginState};
+
+pub fn draw_login(f: &mut Frame, app: &App, chunk: Rect) {
+    let area = center_area(chunk, 60, 40);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.app_config.theme.error_border))
+        .border_type(BorderType::Double)
+        .title(Span::styled(
+            "SESSION EXPIRED",
+            Style::default().fg(app.app_config.theme.error_text),
+        ))
+        .title_alignment(Alignment::Center);
+
+    let highlight = Style::default()
+        .fg(app.app_config.theme.selected)
+        .add_modifier(Modifier::BOLD);
+    let lines = match &app.login {
+        Some(LoginState::Waiting(url)) => vec![
+            Line::from("Open this url to log in, the app resumes once you allow access:"),
+            Line::from(""),
+            Line::styled(url.as_str(), highlight),
+        ],
+        Some(LoginState::Failed(e)) => vec![
+            Line::from(format!("Login failed: {}", e)),
+            Line::from(""),
+            Line::styled("Press Enter to try again", highlight),
+        ],
+        _ => vec![
+            Line::from("Your MyAnimeList session expired."),
+            Line::from(""),
+            Line::styled("Press Enter to log in again", highlight),
+        ],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .style(Style::default().fg(app.app_config.theme.text))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(paragraph, center_area(area, 90, 60));
+}
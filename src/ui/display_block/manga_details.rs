@@ -50,6 +50,9 @@ pub fn draw_manga_detail(f: &mut Frame, app: &mut App, chunk: Rect) {
             ActiveMangaDetailBlock::Rate => {
                 details_utils::draw_rate_popup(f, app, chunk);
             }
+            ActiveMangaDetailBlock::Dates => {
+                details_utils::draw_dates_popup(f, app, chunk);
+            }
             _ => {
                 warn!("invalid block manga popup")
             }
@@ -249,6 +252,8 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
         || app.active_manga_detail_block == ActiveMangaDetailBlock::Rate
         || app.active_manga_detail_block == ActiveMangaDetailBlock::Chapters
         || app.active_manga_detail_block == ActiveMangaDetailBlock::Volumes
+        || app.active_manga_detail_block == ActiveMangaDetailBlock::Dates
+        || app.active_manga_detail_block == ActiveMangaDetailBlock::Discussion
     {
         draw_bordered_block(f, chunk, app.app_config.theme.hovered)
     } else {
@@ -275,7 +280,7 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
         .flex(Flex::Center)
         .areas(rest_chunk);
 
-    let [_, user_status_chunk, user_score_chunk, user_chapter_progress_chunk, user_volume_progress_chunk, _] =
+    let [_, user_status_chunk, user_score_chunk, user_chapter_progress_chunk, user_volume_progress_chunk, dates_chunk, discussion_chunk, _] =
         Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
@@ -284,6 +289,8 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
                 Constraint::Min(23),
                 Constraint::Min(23),
                 Constraint::Min(23),
+                Constraint::Min(16),
+                Constraint::Min(16),
                 Constraint::Percentage(100),
             ])
             .flex(Flex::Start)
@@ -470,6 +477,14 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
     let user_volume_progress = format!("Volumes: {} / {}", read_vol, total_vol);
 
     let mut user_volume_progress_paragraph = Paragraph::new(user_volume_progress)
+        .alignment(Alignment::Center)
+        .block(unhovered_block.clone());
+
+    let mut dates_paragraph = Paragraph::new("Dates")
+        .alignment(Alignment::Center)
+        .block(unhovered_block.clone());
+
+    let mut discussion_paragraph = Paragraph::new("Discussion")
         .alignment(Alignment::Center)
         .block(unhovered_block);
 
@@ -489,8 +504,16 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) {
         ActiveMangaDetailBlock::Rate => {
             user_score_paragraph = user_score_paragraph.block(hovered_block);
         }
+        ActiveMangaDetailBlock::Dates => {
+            dates_paragraph = dates_paragraph.block(hovered_block);
+        }
+        ActiveMangaDetailBlock::Discussion => {
+            discussion_paragraph = discussion_paragraph.block(hovered_block);
+        }
         _ => {}
     }
+    f.render_widget(dates_paragraph, dates_chunk);
+    f.render_widget(discussion_paragraph, discussion_chunk);
     f.render_widget(user_score_paragraph, user_score_chunk);
     f.render_widget(user_chapter_progress_paragraph, user_chapter_progress_chunk);
     f.render_widget(user_volume_progress_paragraph, user_volume_progress_chunk);
@@ -10,12 +10,16 @@ use super::util::get_color;
 mod anime_details;
 mod details_utils;
 mod empty;
+mod forum;
 mod loading;
+mod login;
 mod manga_details;
+mod profiles;
 mod ranking;
 mod results;
 mod search;
 mod suggestion;
+mod thumbnail;
 mod user;
 mod user_anime_list;
 mod user_manga_list;
@@ -52,8 +56,18 @@ pub fn draw_display_layout(f: &mut Frame, app: &mut App, chunk: Rect) {
 
         ActiveDisplayBlock::Error => error::draw_error(f, app, chunk),
 
+        ActiveDisplayBlock::Login => login::draw_login(f, app, chunk),
+
+        ActiveDisplayBlock::Profiles => profiles::draw_profiles(f, app, chunk),
+
+        ActiveDisplayBlock::ForumTopics => forum::draw_forum_topics(f, app, chunk),
+
+        ActiveDisplayBlock::ForumTopic => forum::draw_forum_thread(f, app, chunk),
+
         ActiveDisplayBlock::Loading => {
-            if app.is_loading {
+            if app.is_loading && app.is_throttled() {
+                loading::draw_centered_line(f, app, chunk, "Loading... (waiting on rate limit)");
+            } else if app.is_loading {
                 loading::draw_centered_line(f, app, chunk, "Loading...");
             }
         }
@@ -81,8 +95,29 @@ pub const NAVIGATION_KEYS: [(&str, &str); 5] = [
     ("n", "Next page"),
     ("p", "Previous page"),
 ];
+pub const SEASONAL_NAVIGATION_KEYS: [(&str, &str); 5] = [
+    ("s", "Pick season"),
+    ("arrows", "Navigate"),
+    ("[", "Previous season"),
+    ("]", "Next season"),
+    ("c", "Current season"),
+];
+pub const USER_LIST_NAVIGATION_KEYS: [(&str, &str); 6] = [
+    ("s", "Switch Status"),
+    ("r", "Sort"),
+    ("f", "Filter"),
+    ("arrows", "Navigate"),
+    ("n", "Next page"),
+    ("p", "Previous page"),
+];
 pub const DETAILS_NAVIGATION_KEYS: [(&str, &str); 3] =
     [("s/arrows", "Navigate"), ("q", "Quit"), ("enter", "Select")];
+pub const FORUM_NAVIGATION_KEYS: [(&str, &str); 4] = [
+    ("arrows", "Navigate"),
+    ("enter", "Open"),
+    ("n", "Next page"),
+    ("p", "Previous page"),
+];
 
 pub fn draw_keys_bar(f: &mut Frame, app: &App, chunk: Rect) -> Rect {
     let [display_chunk, keys_chunk] = Layout::default()
@@ -95,6 +130,13 @@ pub fn draw_keys_bar(f: &mut Frame, app: &App, chunk: Rect) -> Rect {
         ActiveDisplayBlock::AnimeDetails | ActiveDisplayBlock::MangaDetails => {
             DETAILS_NAVIGATION_KEYS.to_vec()
         }
+        ActiveDisplayBlock::ForumTopics | ActiveDisplayBlock::ForumTopic => {
+            FORUM_NAVIGATION_KEYS.to_vec()
+        }
+        ActiveDisplayBlock::Seasonal => SEASONAL_NAVIGATION_KEYS.to_vec(),
+        ActiveDisplayBlock::UserAnimeList | ActiveDisplayBlock::UserMangaList => {
+            USER_LIST_NAVIGATION_KEYS.to_vec()
+        }
         _ => NAVIGATION_KEYS.to_vec(),
     };
     let key_chunks = Layout::default()
@@ -0,0 +1,73 @@
+use super::center_area;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::App;
+
+pub fn draw_profiles(f: &mut Frame, app: &App, chunk: Rect) {
+    let area = center_area(chunk, 40, 50);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.app_config.theme.active))
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(
+            "PROFILES",
+            Style::default().fg(app.app_config.theme.banner),
+        ))
+        .title_alignment(Alignment::Center);
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let [list_area, hint_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(2)])
+        .areas(center_area(area, 90, 80));
+
+    if app.profiles.is_empty() {
+        let hint = Paragraph::new(vec![
+            Line::from("No profiles in oauth2.yml."),
+            Line::from(""),
+            Line::from("Add them under `profiles`, or start with `mal --profile NAME`."),
+        ])
+        .style(Style::default().fg(app.app_config.theme.text))
+        .alignment(Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: false });
+        f.render_widget(hint, list_area);
+        return;
+    }
+
+    let items: Vec<Line> = app
+        .profile_choices()
+        .iter()
+        .map(|profile| {
+            let name = profile.as_deref().unwrap_or("default");
+            let line = if *profile == app.active_profile {
+                format!("{} (active)", name)
+            } else {
+                name.to_string()
+            };
+            Line::from(line)
+                .alignment(Alignment::Center)
+                .style(Style::default().fg(app.app_config.theme.text))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(app.app_config.theme.selected)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    state.select(Some(app.profile_index));
+    f.render_stateful_widget(list, list_area, &mut state);
+
+    let hint = Paragraph::new("Enter to switch, your lists are loaded again")
+        .style(Style::default().fg(app.app_config.theme.inactive))
+        .alignment(Alignment::Center);
+    f.render_widget(hint, hint_area);
+}
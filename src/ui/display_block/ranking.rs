@@ -9,7 +9,10 @@ use ratatui::{
 };
 
 use crate::{
-    api::model::{AnimeMediaType, MangaMediaType, PageableData, UserWatchStatus},
+    api::{
+        model::{AnimeMediaType, MangaMediaType, PageableData, UserWatchStatus},
+        ImageKind,
+    },
     app::{
         ActiveBlock, ActiveDisplayBlock, App, ANIME_RANKING_TYPES, DISPLAY_COLUMN_NUMBER,
         DISPLAY_RAWS_NUMBER, MANGA_RANKING_TYPES,
@@ -17,9 +20,12 @@ use crate::{
     ui::{format_number_with_commas, get_end_card_index, util::get_color},
 };
 
-use super::{center_area, get_anime_status_color};
+use super::{
+    center_area, get_anime_status_color,
+    thumbnail::{draw_thumbnails, thumbnail_padding, thumbnail_url},
+};
 
-pub fn draw_anime_ranking(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_anime_ranking(f: &mut Frame, app: &mut App, chunk: Rect) {
     let chunk = draw_nav_bar(f, app, chunk);
     draw_anime_ranking_results(f, app, chunk);
     if app.popup {
@@ -27,7 +33,7 @@ pub fn draw_anime_ranking(f: &mut Frame, app: &App, chunk: Rect) {
     }
 }
 
-pub fn draw_manga_ranking(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_manga_ranking(f: &mut Frame, app: &mut App, chunk: Rect) {
     let chunk = draw_nav_bar(f, app, chunk);
     draw_manga_ranking_results(f, app, chunk);
     if app.popup {
@@ -84,7 +90,7 @@ pub fn draw_nav_bar(f: &mut Frame, app: &App, chunk: Rect) -> Rect {
     splitted_layout[1]
 }
 
-pub fn draw_anime_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_anime_ranking_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.anime_ranking_data.as_ref().unwrap();
     if results.data.is_empty() {
         // draw_no_results(f, app, chunk);
@@ -97,6 +103,7 @@ pub fn draw_anime_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
     let component_page = components[start_index..=end_index].to_vec();
 
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
+    let mut thumbnails = Vec::new();
 
     for (index, component_pair) in component_page.iter().enumerate() {
         let component = component_pair.node.clone();
@@ -176,14 +183,21 @@ pub fn draw_anime_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
                     .borders(Borders::ALL)
                     .border_style(get_color(is_active, app.app_config.theme))
                     .title(component_pair.ranking.rank.to_string())
-                    .title_style(get_color(is_active, app.app_config.theme)),
+                    .title_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             );
 
         f.render_widget(card, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Anime, thumbnails);
 }
 
-pub fn draw_manga_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_manga_ranking_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.manga_ranking_data.as_ref().unwrap();
     if results.data.is_empty() {
         // draw_no_results(f,app,chunk);
@@ -197,6 +211,7 @@ pub fn draw_manga_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
     let component_page = components[start_index..=end_index].to_vec();
 
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
+    let mut thumbnails = Vec::new();
 
     for (index, component_pair) in component_page.iter().enumerate() {
         let component = component_pair.node.clone();
@@ -266,10 +281,17 @@ pub fn draw_manga_ranking_results(f: &mut Frame, app: &App, chunk: Rect) {
                     .borders(Borders::ALL)
                     .border_style(get_color(is_active, app.app_config.theme))
                     .title(component_pair.ranking.rank.to_string())
-                    .title_style(get_color(is_active, app.app_config.theme)),
+                    .title_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             );
         f.render_widget(card, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Manga, thumbnails);
 }
 
 fn construct_cards_with_data<T: Clone + Debug>(
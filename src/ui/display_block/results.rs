@@ -4,6 +4,7 @@ use crate::api::model::MangaMediaType;
 use crate::api::model::Node;
 use crate::api::model::PageableData;
 use crate::api::model::UserReadStatus;
+use crate::api::ImageKind;
 use crate::app::DISPLAY_COLUMN_NUMBER;
 use crate::app::DISPLAY_RAWS_NUMBER;
 use crate::ui::format_number_with_commas;
@@ -22,8 +23,9 @@ use ratatui::{
 };
 
 use super::get_anime_status_color;
+use super::thumbnail::{draw_thumbnails, thumbnail_padding, thumbnail_url};
 
-pub fn draw_results(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     match app.search_results.selected_tab {
         SelectedSearchTab::Anime => {
             if app.search_results.anime.as_ref().is_some() {
@@ -42,7 +44,7 @@ pub fn draw_results(f: &mut Frame, app: &App, chunk: Rect) {
     }
 }
 
-pub fn draw_anime_search_results(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_anime_search_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.search_results.anime.as_ref().unwrap();
     if results.data.is_empty() {
         // draw_no_results(f, app, chunk);
@@ -59,6 +61,7 @@ pub fn draw_anime_search_results(f: &mut Frame, app: &App, chunk: Rect) {
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
 
     // let selected_card_index = 5;
+    let mut thumbnails = Vec::new();
 
     for (index, component) in component_page.iter().enumerate() {
         let is_active =
@@ -141,11 +144,18 @@ pub fn draw_anime_search_results(f: &mut Frame, app: &App, chunk: Rect) {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(get_color(is_active, app.app_config.theme)),
+                    .border_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             );
 
         f.render_widget(card, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Anime, thumbnails);
     /*
     we are gonna display these fields:
     1. title
@@ -158,7 +168,7 @@ pub fn draw_anime_search_results(f: &mut Frame, app: &App, chunk: Rect) {
     */
 }
 
-pub fn draw_manga_search_results(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_manga_search_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.search_results.manga.as_ref().unwrap();
     if results.data.is_empty() {
         //TODO: handle no results
@@ -171,6 +181,7 @@ pub fn draw_manga_search_results(f: &mut Frame, app: &App, chunk: Rect) {
     let component_page = components_result[start_index..=end_index].to_vec();
 
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
+    let mut thumbnails = Vec::new();
 
     for (index, component) in component_page.iter().enumerate() {
         if index >= cards.len() {
@@ -245,11 +256,18 @@ pub fn draw_manga_search_results(f: &mut Frame, app: &App, chunk: Rect) {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(get_color(is_active, app.app_config.theme)),
+                    .border_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             );
 
         f.render_widget(card, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Manga, thumbnails);
 }
 
 pub fn construct_cards_with_data<T: Clone + Debug>(
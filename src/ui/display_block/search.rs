@@ -12,7 +12,7 @@ use crate::{
 
 use super::results::draw_results;
 
-pub fn draw_search_result(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_search_result(f: &mut Frame, app: &mut App, chunk: Rect) {
     let chunk = draw_nav_bar(f, app, chunk);
     let chunk = super::draw_keys_bar(f, app, chunk);
 
@@ -1,6 +1,7 @@
 use super::center_area;
 use super::{draw_keys_bar, results};
-use crate::app::{App, SEASONS};
+use crate::api::model::Season;
+use crate::app::App;
 use ratatui::layout::{Alignment, Constraint, Direction, Flex, Layout};
 use ratatui::style::{Modifier, Style};
 use ratatui::text::Line;
@@ -11,7 +12,7 @@ use ratatui::{
     Frame,
 };
 
-pub fn draw_seasonal_anime(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_seasonal_anime(f: &mut Frame, app: &mut App, chunk: Rect) {
     let chunk = draw_keys_bar(f, app, chunk);
     results::draw_results(f, app, chunk);
     if app.popup {
@@ -57,15 +58,15 @@ fn draw_seasonal_popup(f: &mut Frame, app: &App, chunk: Rect) {
         season_block = season_block.title("Season")
     }
 
-    let list: Vec<Line> = SEASONS
+    let list: Vec<Line> = Season::ALL
         .iter()
         .map(|s| {
-            Line::from(*s)
+            Line::from(s.name())
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(app.app_config.theme.text))
         })
         .collect();
-    let season_selected: Option<usize> = Some(app.anime_season.selected_season.into());
+    let season_selected: Option<usize> = app.anime_season.popup_season.season.index();
 
     let mut state = ListState::default();
     state.select(season_selected);
@@ -102,14 +103,15 @@ fn draw_seasonal_popup(f: &mut Frame, app: &App, chunk: Rect) {
         year_block = year_block.title("Year")
     }
 
+    let selected_year = app.anime_season.popup_season.year;
     let list: Vec<Line> = vec![
-        (app.anime_season.selected_year + 1).to_string(),
-        (app.anime_season.selected_year).to_string(),
-        (app.anime_season.selected_year - 1).to_string(),
+        (selected_year + 1).to_string(),
+        selected_year.to_string(),
+        selected_year.saturating_sub(1).to_string(),
     ]
     .into_iter()
     .map(|s| {
-        if s == app.anime_season.selected_year.to_string() {
+        if s == selected_year.to_string() {
             return Line::raw(s)
                 .alignment(Alignment::Center)
                 .style(Style::default().fg(app.app_config.theme.selected));
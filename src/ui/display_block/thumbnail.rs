@@ -0,0 +1,58 @@
+use ratatui::{layout::Rect, widgets::Padding, Frame};
+use ratatui_image::StatefulImage;
+
+use crate::{
+    api::{model::Picture, ImageKind},
+    app::{App, Thumbnail},
+};
+
+/// Rows inside the border of a card below which no poster is drawn
+const MIN_THUMBNAIL_ROWS: u16 = 4;
+/// Columns the text of a card keeps next to its poster
+const MIN_TEXT_COLUMNS: u16 = 20;
+
+/// Columns of the poster of a card, `None` when posters are off or the card is too small
+fn thumbnail_width(app: &App, card: Rect) -> Option<u16> {
+    if !app.app_config.show_thumbnails {
+        return None;
+    }
+    let (font_width, font_height) = app.picker?.font_size();
+    let rows = card.height.saturating_sub(2);
+    if rows < MIN_THUMBNAIL_ROWS {
+        return None;
+    }
+    // covers are about 2:3, in cells of `font_width` by `font_height` pixels
+    let width = (rows as u32 * font_height as u32 * 2 / (3 * font_width.max(1) as u32)) as u16;
+    let text = card.width.saturating_sub(2 + width + 1);
+    (width > 0 && text >= MIN_TEXT_COLUMNS).then_some(width)
+}
+
+/// Room kept left of the text of a card for its poster
+pub fn thumbnail_padding(app: &App, card: Rect) -> Padding {
+    Padding::left(thumbnail_width(app, card).map_or(0, |width| width + 1))
+}
+
+/// The medium picture of a card, its thumbnail
+pub fn thumbnail_url(picture: &Option<Picture>) -> Option<String> {
+    picture.as_ref().and_then(|p| p.medium.clone())
+}
+
+/// Draws the posters of the cards that have room for one, the missing ones are
+/// fetched after the frame by [`App::request_thumbnails`]
+pub fn draw_thumbnails(
+    f: &mut Frame,
+    app: &mut App,
+    kind: ImageKind,
+    cards: Vec<(Rect, u64, Option<String>)>,
+) {
+    for (card, id, url) in cards {
+        let Some(width) = thumbnail_width(app, card) else {
+            continue;
+        };
+        app.want_thumbnail(kind, id, url);
+        if let Some(Thumbnail::Ready(state)) = app.thumbnails.get_mut(&(kind, id)) {
+            let area = Rect::new(card.x + 1, card.y + 1, width, card.height - 2);
+            f.render_stateful_widget(StatefulImage::default(), area, state);
+        }
+    }
+}
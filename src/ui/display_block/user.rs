@@ -182,11 +182,11 @@ fn draw_info(f: &mut Frame, app: &App, chunk: Rect) -> Rect {
     let mut total_eps = 0;
     let mut total_days = 0.0;
     let stats = app.user_profile.as_ref().unwrap().anime_statistics.as_ref();
-    if stats.is_some() {
-        total_items = stats.unwrap().num_items;
-        mean_score = stats.unwrap().mean_score;
-        total_days = stats.unwrap().num_days;
-        total_eps = stats.unwrap().num_episodes;
+    if let Some(stats) = stats {
+        total_items = stats.num_items;
+        mean_score = stats.mean_score;
+        total_days = stats.num_days;
+        total_eps = stats.num_episodes;
     }
 
     let location = app
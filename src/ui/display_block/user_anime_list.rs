@@ -1,20 +1,29 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListState, Padding, Paragraph, Wrap},
     Frame,
 };
 
 use crate::{
-    api::model::{AnimeMediaType, UserReadStatus, UserWatchStatus},
+    api::{
+        model::{AnimeMediaType, UserReadStatus, UserWatchStatus},
+        ImageKind,
+    },
     app::{ActiveBlock, App},
     config::app_config::Theme,
     ui::util::get_color,
+    user_list::ListSort,
 };
 
-use super::results::construct_cards_with_data;
+use super::{
+    center_area,
+    results::construct_cards_with_data,
+    thumbnail::{draw_thumbnails, thumbnail_padding, thumbnail_url},
+};
 
-pub fn draw_user_anime_list(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_user_anime_list(f: &mut Frame, app: &mut App, chunk: Rect) {
     // order matters, it should be the same as the Status enum
     let statuses = vec![
         "add",
@@ -29,6 +38,82 @@ pub fn draw_user_anime_list(f: &mut Frame, app: &App, chunk: Rect) {
 
     let chunk = super::draw_keys_bar(f, app, chunk);
     draw_anime_list_results(f, app, chunk);
+    if app.popup {
+        let styles = ListSort::ALL.iter().map(|s| s.name()).collect();
+        draw_sort_popup(f, app, chunk, styles);
+    }
+    draw_filter_prompt(f, app, chunk);
+}
+
+/// The filter being typed, with why the last one was refused
+pub fn draw_filter_prompt(f: &mut Frame, app: &App, chunk: Rect) {
+    let Some(input) = &app.list_filter_input else {
+        return;
+    };
+    let area = center_area(chunk, 60, 20);
+    let block = Block::default()
+        .title("Filter")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .padding(Padding::horizontal(1));
+
+    let hint = match &app.list_filter_error {
+        Some(e) => Line::styled(
+            e.as_str(),
+            Style::default().fg(app.app_config.theme.error_text),
+        ),
+        None => Line::styled(
+            "e.g. type:tv score:8 genre:slice_of_life year:2020 tag:rewatch",
+            Style::default().fg(app.app_config.theme.inactive),
+        ),
+    };
+    let lines = vec![
+        Line::styled(
+            format!("{}_", input),
+            Style::default().fg(app.app_config.theme.text),
+        ),
+        Line::default(),
+        hint,
+    ];
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Lists the sort styles of a user list, the highlighted one is picked with enter
+pub fn draw_sort_popup(f: &mut Frame, app: &App, chunk: Rect, styles: Vec<&str>) {
+    let area = center_area(chunk, 20, 50);
+    let popup = Block::default()
+        .title("Sort By")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double);
+
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+
+    let list = styles.into_iter().map(|s| {
+        Line::from(s)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(app.app_config.theme.text))
+    });
+    let mut state = ListState::default();
+    state.select(Some(app.list_sort_index));
+
+    let sort_list = List::new(list)
+        .block(Block::default().padding(Padding::symmetric(1, 2)))
+        .highlight_style(
+            Style::default()
+                .fg(app.app_config.theme.selected)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_stateful_widget(sort_list, center_area(area, 60, 90), &mut state);
 }
 
 pub fn draw_user_list_nav_bar(
@@ -104,7 +189,7 @@ pub fn draw_tab(f: &mut Frame, tab_chunk: Rect, content: String, is_active: bool
     f.render_widget(paragraph, tab_chunk);
 }
 
-fn draw_anime_list_results(f: &mut Frame, app: &App, chunk: Rect) {
+fn draw_anime_list_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.search_results.anime.as_ref().unwrap();
     if results.data.is_empty() {
         // draw_no_results(f, app, chunk);
@@ -117,6 +202,8 @@ fn draw_anime_list_results(f: &mut Frame, app: &App, chunk: Rect) {
 
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
 
+    let mut thumbnails = Vec::new();
+
     for (index, component) in components.iter().enumerate() {
         if index >= cards.len() {
             break;
@@ -170,13 +257,20 @@ fn draw_anime_list_results(f: &mut Frame, app: &App, chunk: Rect) {
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
-                    .border_style(get_color(is_active, app.app_config.theme)),
+                    .border_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             )
             .alignment(Alignment::Left)
             .wrap(Wrap { trim: true });
 
         f.render_widget(paragraph, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Anime, thumbnails);
 }
 
 fn eq_manga_status(status: &Option<UserReadStatus>, status_str: &str) -> bool {
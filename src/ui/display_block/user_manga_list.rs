@@ -6,14 +6,19 @@ use ratatui::{
 };
 
 use crate::{
-    api::model::MangaMediaType,
+    api::{model::MangaMediaType, ImageKind},
     app::{ActiveBlock, App},
     ui::util::get_color,
+    user_list::ListSort,
 };
 
-use super::{results::construct_cards_with_data, user_anime_list::draw_user_list_nav_bar};
+use super::{
+    results::construct_cards_with_data,
+    thumbnail::{draw_thumbnails, thumbnail_padding, thumbnail_url},
+    user_anime_list::{draw_filter_prompt, draw_sort_popup, draw_user_list_nav_bar},
+};
 
-pub fn draw_user_manga_list(f: &mut Frame, app: &App, chunk: Rect) {
+pub fn draw_user_manga_list(f: &mut Frame, app: &mut App, chunk: Rect) {
     let statuses = vec![
         "add",
         "reading",
@@ -25,9 +30,14 @@ pub fn draw_user_manga_list(f: &mut Frame, app: &App, chunk: Rect) {
     let chunk = draw_user_list_nav_bar(f, app, chunk, false, statuses);
     let chunk = super::draw_keys_bar(f, app, chunk);
     draw_manga_list_results(f, app, chunk);
+    if app.popup {
+        let styles = ListSort::ALL.iter().map(|s| s.name()).collect();
+        draw_sort_popup(f, app, chunk, styles);
+    }
+    draw_filter_prompt(f, app, chunk);
 }
 
-fn draw_manga_list_results(f: &mut Frame, app: &App, chunk: Rect) {
+fn draw_manga_list_results(f: &mut Frame, app: &mut App, chunk: Rect) {
     let results = app.search_results.manga.as_ref().unwrap();
     if results.data.is_empty() {
         return;
@@ -38,6 +48,8 @@ fn draw_manga_list_results(f: &mut Frame, app: &App, chunk: Rect) {
     let components = cards_results.1;
     let selected_card_index = app.search_results.selected_display_card_index.unwrap_or(0);
 
+    let mut thumbnails = Vec::new();
+
     for (index, component) in components.iter().enumerate() {
         if index >= cards.len() {
             break;
@@ -90,11 +102,18 @@ fn draw_manga_list_results(f: &mut Frame, app: &App, chunk: Rect) {
                 Block::default()
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
-                    .border_style(get_color(is_active, app.app_config.theme)),
+                    .border_style(get_color(is_active, app.app_config.theme))
+                    .padding(thumbnail_padding(app, cards[index])),
             )
             .alignment(Alignment::Left)
             .wrap(Wrap { trim: true });
 
         f.render_widget(paragraph, cards[index]);
+        thumbnails.push((
+            cards[index],
+            component.id,
+            thumbnail_url(&component.main_picture),
+        ));
     }
+    draw_thumbnails(f, app, ImageKind::Manga, thumbnails);
 }
@@ -72,10 +72,38 @@ pub fn draw_input_and_help_box(f: &mut Frame, app: &App, layout_chunk: Rect) {
     if title.is_empty() {
         title = "Home".to_string(); // Default title , since i couldn't initialize it in app.rs:15
     }
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(app.app_config.theme.inactive));
+    if let Some(stored_at) = app.current_route_stale_since() {
+        let age = crate::api::cache::now().saturating_sub(stored_at);
+        block = block.title_bottom(
+            Line::styled(
+                format!(" cached {} ago ", format_age(age)),
+                Style::default().fg(app.app_config.theme.status_on_hold),
+            )
+            .alignment(Alignment::Left),
+        );
+    }
+    if app.is_offline() {
+        block = block.title_top(
+            Line::styled(
+                " offline ",
+                Style::default().fg(app.app_config.theme.status_on_hold),
+            )
+            .alignment(Alignment::Right),
+        );
+    }
+    if app.is_throttled() {
+        block = block.title_bottom(
+            Line::styled(
+                " throttled ",
+                Style::default().fg(app.app_config.theme.status_on_hold),
+            )
+            .alignment(Alignment::Right),
+        );
+    }
 
     let lines = Line::from(Span::from(title))
         .alignment(Alignment::Center)
@@ -88,6 +116,15 @@ pub fn draw_input_and_help_box(f: &mut Frame, app: &App, layout_chunk: Rect) {
     f.render_widget(help, title_chunk);
 }
 
+/// Rough age like `5m`, `3h` or `2d`
+fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
+
 pub fn format_number_with_commas(number: u64) -> String {
     let num_str = number.to_string();
     let mut result = String::new();
@@ -106,58 +143,26 @@ pub fn format_number_with_commas(number: u64) -> String {
 }
 
 fn get_end_index(app: &App, typ: &str) -> usize {
-    match typ {
-        "anime" => {
-            let data_len = app.search_results.anime.as_ref().unwrap().data.len();
-            if app.start_card_list_index as usize + DISPLAY_RAWS_NUMBER * DISPLAY_COLUMN_NUMBER
-                > data_len - 1
-            // end is bigger than last index
-            {
-                data_len - 1
-            } else {
-                // start index + 14 to get the last index
-                app.start_card_list_index as usize + DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER - 1
-            }
-        }
-        "manga" => {
-            let data_len = app.search_results.manga.as_ref().unwrap().data.len();
-            if app.start_card_list_index as usize + DISPLAY_RAWS_NUMBER * DISPLAY_COLUMN_NUMBER
-                > data_len - 1
-            // end is bigger than last index in the data
-            {
-                data_len - 1
-            } else {
-                // start index + 14 to get the last index
-                app.start_card_list_index as usize + DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER - 1
-            }
-        }
-        //TODO: handle these cases:
-        "anime_ranking" => {
-            let data_len = app.anime_ranking_data.as_ref().unwrap().data.len();
-            if app.start_card_list_index as usize + DISPLAY_RAWS_NUMBER * DISPLAY_COLUMN_NUMBER
-                > data_len - 1
-            // end is bigger than last index in the data
-            {
-                data_len - 1
-            } else {
-                // start index + 14 to get the last index
-                app.start_card_list_index as usize + DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER - 1
-            }
-        }
-        "manga_ranking" => {
-            let data_len = app.manga_ranking_data.as_ref().unwrap().data.len();
-            if app.start_card_list_index as usize + DISPLAY_RAWS_NUMBER * DISPLAY_COLUMN_NUMBER
-                > data_len - 1
-            // end is bigger than last index in the data
-            {
-                data_len - 1
-            } else {
-                // start index + 14 to get the last index
-                app.start_card_list_index as usize + DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER - 1
-            }
-        }
+    let data_len = match typ {
+        "anime" => app
+            .search_results
+            .anime
+            .as_ref()
+            .map_or(0, |d| d.data.len()),
+        "manga" => app
+            .search_results
+            .manga
+            .as_ref()
+            .map_or(0, |d| d.data.len()),
+        "anime_ranking" => app.anime_ranking_data.as_ref().map_or(0, |d| d.data.len()),
+        "manga_ranking" => app.manga_ranking_data.as_ref().map_or(0, |d| d.data.len()),
         _ => panic!("Unknown type: {}", typ),
-    }
+    };
+    // the last card of the screen, or of the data when it ends first;
+    // 0 for no data at all, callers check for that before indexing
+    (app.start_card_list_index as usize + DISPLAY_COLUMN_NUMBER * DISPLAY_RAWS_NUMBER)
+        .min(data_len)
+        .saturating_sub(1)
 }
 
 pub fn get_end_card_index(app: &App) -> usize {
@@ -168,6 +173,7 @@ pub fn get_end_card_index(app: &App) -> usize {
         },
         ActiveDisplayBlock::AnimeRanking => get_end_index(app, "anime_ranking"),
         ActiveDisplayBlock::MangaRanking => get_end_index(app, "manga_ranking"),
+        ActiveDisplayBlock::UserMangaList => get_end_index(app, "manga"),
         _ => {
             // Default case, if no specific block is active
             get_end_index(app, "anime")
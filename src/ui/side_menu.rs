@@ -46,10 +46,7 @@ pub fn draw_anime_routes(f: &mut Frame, app: &App, layout_chunk: Rect) {
 
     f.render_widget(block, layout_chunk);
 
-    let mut index = Some(app.library.selected_index);
-    if !ANIME_OPTIONS_RANGE.contains(&app.library.selected_index) {
-        index = None;
-    }
+    let index = app.library.index_in(ANIME_OPTIONS_RANGE);
     let [list_layout] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3)])
@@ -81,13 +78,10 @@ pub fn draw_user_routes(f: &mut Frame, app: &App, layout_chunk: Rect) {
 
     f.render_widget(block, layout_chunk);
 
-    let mut index = Some(app.library.selected_index);
-    if !USER_OPTIONS_RANGE.contains(&app.library.selected_index) {
-        index = None;
-    }
+    let index = app.library.index_in(USER_OPTIONS_RANGE);
     let [list_layout] = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3)])
+        .constraints([Constraint::Length(USER_OPTIONS.len() as u16)])
         .flex(Flex::Center)
         .areas(layout_chunk);
     draw_selectable_list(f, app, list_layout, items, index);
@@ -117,10 +111,7 @@ pub fn draw_options_routes(f: &mut Frame, app: &App, layout_chunk: Rect) {
 
     f.render_widget(block, layout_chunk);
 
-    let mut index = Some(app.library.selected_index);
-    if !GENERAL_OPTIONS_RANGE.contains(&app.library.selected_index) {
-        index = None;
-    }
+    let index = app.library.index_in(GENERAL_OPTIONS_RANGE);
     let [list_layout] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3)])
@@ -135,7 +126,7 @@ pub fn draw_user_block(f: &mut Frame, app: &App, layout_chunk: Rect) {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(5),
-                Constraint::Length(5),
+                Constraint::Length(6),
                 Constraint::Length(5),
                 Constraint::Fill(1),
             ])
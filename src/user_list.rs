@@ -0,0 +1,414 @@
+use crate::api::model::*;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Entries asked for per page when syncing a user list, the most MAL sends at once
+pub const USER_LIST_PAGE_LIMIT: u64 = 1000;
+
+/// What sorting and filtering needs to know about an entry of the user's list
+pub trait ListEntry {
+    fn id(&self) -> u64;
+    fn title(&self) -> &str;
+    /// `tv`, `movie`, `manga`, `light_novel`...
+    fn media_type(&self) -> &str;
+    /// the user's score, 0 when not scored
+    fn score(&self) -> u8;
+    fn mean(&self) -> Option<f64>;
+    fn members(&self) -> Option<u64>;
+    /// episodes watched or chapters read
+    fn progress(&self) -> u64;
+    fn start_date(&self) -> Option<time::Date>;
+    fn updated_at(&self) -> Option<time::PrimitiveDateTime>;
+    fn genres(&self) -> &[Genre];
+    fn tags(&self) -> &[String];
+}
+
+impl ListEntry for Anime {
+    fn id(&self) -> u64 {
+        self.id
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn media_type(&self) -> &str {
+        self.media_type.as_ref().map_or("unknown", |t| t.into())
+    }
+    fn score(&self) -> u8 {
+        self.my_list_status.as_ref().map_or(0, |s| s.score)
+    }
+    fn mean(&self) -> Option<f64> {
+        self.mean
+    }
+    fn members(&self) -> Option<u64> {
+        self.num_list_users
+    }
+    fn progress(&self) -> u64 {
+        self.my_list_status
+            .as_ref()
+            .map_or(0, |s| s.num_episodes_watched)
+    }
+    fn start_date(&self) -> Option<time::Date> {
+        self.start_date.as_ref().map(|d| d.date)
+    }
+    fn updated_at(&self) -> Option<time::PrimitiveDateTime> {
+        self.my_list_status.as_ref().map(|s| s.updated_at.datetime)
+    }
+    fn genres(&self) -> &[Genre] {
+        self.genres.as_deref().unwrap_or_default()
+    }
+    fn tags(&self) -> &[String] {
+        self.my_list_status
+            .as_ref()
+            .and_then(|s| s.tags.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl ListEntry for Manga {
+    fn id(&self) -> u64 {
+        self.id
+    }
+    fn title(&self) -> &str {
+        &self.title
+    }
+    fn media_type(&self) -> &str {
+        self.media_type.as_ref().map_or("unknown", |t| t.into())
+    }
+    fn score(&self) -> u8 {
+        self.my_list_status.as_ref().map_or(0, |s| s.score)
+    }
+    fn mean(&self) -> Option<f64> {
+        self.mean
+    }
+    fn members(&self) -> Option<u64> {
+        self.num_list_users
+    }
+    fn progress(&self) -> u64 {
+        self.my_list_status
+            .as_ref()
+            .map_or(0, |s| s.num_chapters_read)
+    }
+    fn start_date(&self) -> Option<time::Date> {
+        self.start_date.as_ref().map(|d| d.date)
+    }
+    fn updated_at(&self) -> Option<time::PrimitiveDateTime> {
+        self.my_list_status.as_ref().map(|s| s.updated_at.datetime)
+    }
+    fn genres(&self) -> &[Genre] {
+        self.genres.as_deref().unwrap_or_default()
+    }
+    fn tags(&self) -> &[String] {
+        self.my_list_status
+            .as_ref()
+            .and_then(|s| s.tags.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// How a synced list is ordered, done locally so any field works
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ListSort {
+    #[default]
+    Score,
+    LastUpdated,
+    Title,
+    StartDate,
+    Mean,
+    Members,
+    Progress,
+    Id,
+}
+
+impl ListSort {
+    /// In the order of the sort popup
+    pub const ALL: [ListSort; 8] = [
+        ListSort::Score,
+        ListSort::LastUpdated,
+        ListSort::Title,
+        ListSort::StartDate,
+        ListSort::Mean,
+        ListSort::Members,
+        ListSort::Progress,
+        ListSort::Id,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            ListSort::Score => "Score",
+            ListSort::LastUpdated => "Last Updated",
+            ListSort::Title => "Title",
+            ListSort::StartDate => "Start Date",
+            ListSort::Mean => "Mean Score",
+            ListSort::Members => "Members",
+            ListSort::Progress => "Progress",
+            ListSort::Id => "Id",
+        }
+    }
+
+    /// Titles and ids go up, everything else puts the highest or newest first
+    pub fn sort<T: ListEntry>(&self, entries: &mut [T]) {
+        entries.sort_by(|a, b| self.compare(a, b).then_with(|| a.title().cmp(b.title())));
+    }
+
+    fn compare<T: ListEntry>(&self, a: &T, b: &T) -> Ordering {
+        match self {
+            ListSort::Score => b.score().cmp(&a.score()),
+            ListSort::LastUpdated => b.updated_at().cmp(&a.updated_at()),
+            ListSort::Title => a.title().to_lowercase().cmp(&b.title().to_lowercase()),
+            ListSort::StartDate => b.start_date().cmp(&a.start_date()),
+            ListSort::Mean => b.mean().unwrap_or(0.).total_cmp(&a.mean().unwrap_or(0.)),
+            ListSort::Members => b.members().cmp(&a.members()),
+            ListSort::Progress => b.progress().cmp(&a.progress()),
+            ListSort::Id => a.id().cmp(&b.id()),
+        }
+    }
+}
+
+/// Narrows a synced list down, parsed from what the user types, e.g.
+/// `type:tv score:8 genre:action year:2020 tag:rewatch fullmetal`.
+///
+/// `score` is a minimum, words without a key have to be in the title,
+/// text matches ignore case.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListFilter {
+    pub title: Option<String>,
+    pub media_type: Option<String>,
+    pub min_score: Option<u8>,
+    pub genre: Option<String>,
+    pub year: Option<i32>,
+    pub tag: Option<String>,
+}
+
+impl ListFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn matches<T: ListEntry>(&self, entry: &T) -> bool {
+        let contains = |text: &str, part: &str| text.to_lowercase().contains(&part.to_lowercase());
+        self.title
+            .as_ref()
+            .is_none_or(|t| contains(entry.title(), t))
+            && self
+                .media_type
+                .as_ref()
+                .is_none_or(|t| entry.media_type().eq_ignore_ascii_case(t))
+            && self.min_score.is_none_or(|min| entry.score() >= min)
+            && self
+                .genre
+                .as_ref()
+                .is_none_or(|g| entry.genres().iter().any(|genre| contains(&genre.name, g)))
+            && self
+                .year
+                .is_none_or(|year| entry.start_date().is_some_and(|d| d.year() == year))
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|t| entry.tags().iter().any(|tag| tag.eq_ignore_ascii_case(t)))
+    }
+
+    /// The entries to show, filtered and sorted
+    pub fn apply<'a, T: ListEntry + Clone + 'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a T>,
+        sort: ListSort,
+    ) -> Vec<T> {
+        let mut shown: Vec<T> = entries
+            .into_iter()
+            .filter(|e| self.matches(*e))
+            .cloned()
+            .collect();
+        sort.sort(&mut shown);
+        shown
+    }
+}
+
+impl FromStr for ListFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = ListFilter::default();
+        let mut words = Vec::new();
+        for term in s.split_whitespace() {
+            let Some((key, value)) = term.split_once(':') else {
+                words.push(term);
+                continue;
+            };
+            // multi word genres and tags are typed with underscores
+            let text = || Some(value.replace('_', " "));
+            match key.to_lowercase().as_str() {
+                "type" => filter.media_type = Some(value.to_string()),
+                "score" => {
+                    filter.min_score = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|s| *s <= 10)
+                            .ok_or(format!("score has to be 0 to 10, not {}", value))?,
+                    )
+                }
+                "genre" => filter.genre = text(),
+                "year" => {
+                    filter.year = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("{} is not a year", value))?,
+                    )
+                }
+                "tag" => filter.tag = text(),
+                // part of a title, e.g. `re:zero`
+                _ => words.push(term),
+            }
+        }
+        if !words.is_empty() {
+            filter.title = Some(words.join(" "));
+        }
+        Ok(filter)
+    }
+}
+
+/// Back to what the user typed, so it can be edited again
+impl fmt::Display for ListFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms = Vec::new();
+        if let Some(t) = &self.media_type {
+            terms.push(format!("type:{}", t));
+        }
+        if let Some(s) = self.min_score {
+            terms.push(format!("score:{}", s));
+        }
+        if let Some(g) = &self.genre {
+            terms.push(format!("genre:{}", g.replace(' ', "_")));
+        }
+        if let Some(y) = self.year {
+            terms.push(format!("year:{}", y));
+        }
+        if let Some(t) = &self.tag {
+            terms.push(format!("tag:{}", t.replace(' ', "_")));
+        }
+        if let Some(t) = &self.title {
+            terms.push(t.clone());
+        }
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+/// Whether an anime is in the status tab, `None` being all of them
+pub fn has_watch_status(anime: &Anime, status: &Option<UserWatchStatus>) -> bool {
+    match (status, &anime.my_list_status) {
+        (None, _) => true,
+        (Some(status), Some(list)) => list.status == *status,
+        (Some(_), None) => false,
+    }
+}
+
+/// Whether a manga is in the status tab, `None` being all of them
+pub fn has_read_status(manga: &Manga, status: &Option<UserReadStatus>) -> bool {
+    match (status, &manga.my_list_status) {
+        (None, _) => true,
+        (Some(status), Some(list)) => list.status == *status,
+        (Some(_), None) => false,
+    }
+}
+
+pub fn get_status_string(status: &Option<UserWatchStatus>) -> String {
+    match status {
+        Some(s) => match s {
+            UserWatchStatus::Completed => "completed".to_string(),
+            UserWatchStatus::Watching => "watching".to_string(),
+            UserWatchStatus::OnHold => "on_hold".to_string(),
+            UserWatchStatus::Dropped => "dropped".to_string(),
+            UserWatchStatus::PlanToWatch => "plan_to_watch".to_string(),
+            UserWatchStatus::Other(_) => "All".to_string(),
+        },
+        None => "All".to_string(),
+    }
+}
+
+pub fn get_manga_status_string(status: &Option<UserReadStatus>) -> String {
+    match status {
+        Some(s) => match s {
+            UserReadStatus::Completed => "completed".to_string(),
+            UserReadStatus::Reading => "reading".to_string(),
+            UserReadStatus::OnHold => "on_hold".to_string(),
+            UserReadStatus::Dropped => "dropped".to_string(),
+            UserReadStatus::PlanToRead => "plan_to_read".to_string(),
+            UserReadStatus::Other(_) => "All".to_string(),
+        },
+        None => "All".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anime(id: u64, title: &str, score: u8, year: i32) -> Anime {
+        let mut anime: Anime = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "title": title,
+            "media_type": if id.is_multiple_of(2) { "tv" } else { "movie" },
+            "start_date": format!("{}-04-01", year),
+            "genres": [{"id": 1, "name": "Action"}],
+            "my_list_status": {
+                "status": "completed",
+                "score": score,
+                "num_episodes_watched": 12,
+                "is_rewatching": false,
+                "tags": ["rewatch"],
+                "updated_at": "2024-01-01T00:00:00+00:00"
+            }
+        }))
+        .unwrap();
+        if id == 3 {
+            anime.genres = None;
+        }
+        anime
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        let filter: ListFilter = "type:tv score:8 genre:slice_of_life year:2009 full metal"
+            .parse()
+            .unwrap();
+        assert_eq!(filter.media_type.as_deref(), Some("tv"));
+        assert_eq!(filter.min_score, Some(8));
+        assert_eq!(filter.genre.as_deref(), Some("slice of life"));
+        assert_eq!(filter.year, Some(2009));
+        assert_eq!(filter.title.as_deref(), Some("full metal"));
+        assert_eq!(filter.to_string().parse::<ListFilter>().unwrap(), filter);
+
+        assert!("score:11".parse::<ListFilter>().is_err());
+        let title = |s: &str| s.parse::<ListFilter>().unwrap().title;
+        assert_eq!(title("re:zero").as_deref(), Some("re:zero"));
+        assert_eq!(
+            title("steins;gate 0: score:7").as_deref(),
+            Some("steins;gate 0:")
+        );
+        assert!("".parse::<ListFilter>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let list = vec![
+            anime(1, "Bakemonogatari", 9, 2009),
+            anime(2, "Angel Beats", 7, 2010),
+            anime(3, "Clannad", 10, 2007),
+            anime(4, "Durarara", 8, 2010),
+        ];
+        let ids = |shown: Vec<Anime>| shown.iter().map(|a| a.id).collect::<Vec<_>>();
+
+        let all = ListFilter::default();
+        assert_eq!(ids(all.apply(&list, ListSort::Score)), vec![3, 1, 4, 2]);
+        assert_eq!(ids(all.apply(&list, ListSort::Title)), vec![2, 1, 3, 4]);
+        assert_eq!(ids(all.apply(&list, ListSort::StartDate)), vec![2, 4, 1, 3]);
+
+        let filter: ListFilter = "score:8 year:2010".parse().unwrap();
+        assert_eq!(ids(filter.apply(&list, ListSort::Score)), vec![4]);
+        let filter: ListFilter = "type:TV genre:action".parse().unwrap();
+        assert_eq!(ids(filter.apply(&list, ListSort::Id)), vec![2, 4]);
+        let filter: ListFilter = "tag:Rewatch clan".parse().unwrap();
+        assert_eq!(ids(filter.apply(&list, ListSort::Id)), vec![3]);
+    }
+}